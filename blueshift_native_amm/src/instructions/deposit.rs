@@ -2,7 +2,7 @@ use constant_product_curve::ConstantProduct;
 use pinocchio::{
     ProgramResult,
     account_info::AccountInfo,
-    instruction::{Seed, Signer},
+    instruction::Signer,
     program_error::ProgramError,
     sysvars::{Sysvar, clock::Clock},
 };
@@ -11,7 +11,7 @@ use pinocchio_token::{
     state::{Mint, TokenAccount},
 };
 
-use crate::{CONFIG_SEED, Config};
+use crate::{Config, seeds::ConfigSeeds};
 
 pub struct DepositAccounts<'a> {
     pub user: &'a AccountInfo,
@@ -104,6 +104,10 @@ impl<'a> Deposit<'a> {
             // AmmState::Initialized
             return Err(ProgramError::InvalidAccountData);
         }
+        // 只能向本池子的 LP mint 铸造
+        if accounts.mint_lp.key().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
 
         // 3. 反序列化代币账户信息 (使用 Pinocchio-token 提供的 unchecked 方法提升性能)
         let mint_lp = unsafe { Mint::from_account_info_unchecked(accounts.mint_lp)? };
@@ -150,19 +154,8 @@ impl<'a> Deposit<'a> {
         .invoke()?;
 
         // 7. 签署并执行 MintTo (Config PDA -> 用户)
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x = config.mint_x(); // Returns &Pubkey
-        let mint_y = config.mint_y(); // Returns &Pubkey
-        let bump = config.config_bump(); // Returns [u8; 1]
-
-        // 2. Now create the seeds using references to those stable variables
-        let config_seeds = [
-            Seed::from(CONFIG_SEED),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x.as_ref()),
-            Seed::from(mint_y.as_ref()),
-            Seed::from(&bump), // Reference to the local variable 'bump'
-        ];
+        let config_seeds = ConfigSeeds::from_config(&config);
+        let config_seeds = config_seeds.as_seeds();
         let signer = Signer::from(&config_seeds);
 
         MintTo {
@@ -10,7 +10,7 @@ use pinocchio::{
 use pinocchio_system::instructions::CreateAccount;
 use pinocchio_token::{instructions::InitializeMint2, state::Mint};
 
-use crate::{CONFIG_SEED, Config, MINT_LP_SEED};
+use crate::{Config, MINT_LP_SEED, seeds::ConfigSeeds};
 
 /// 初始化 Config 账户，并存储 AMM 正常运行所需的所有信息。
 /// 创建 mint_lp 铸币账户，并将 mint_authority 分配给 config 账户。
@@ -122,14 +122,13 @@ impl<'a> Initialize<'a> {
 
         // --- 1. 创建 Config 账户 ---
         let config_lamports = rent.minimum_balance(Config::LEN); // 动态计算
-        let seed_binding = instruction_data.seed.to_le_bytes();
-        let config_seeds = [
-            Seed::from(CONFIG_SEED),
-            Seed::from(seed_binding.as_ref()),
-            Seed::from(instruction_data.mint_x.as_ref()),
-            Seed::from(instruction_data.mint_y.as_ref()),
-            Seed::from(&instruction_data.config_bump),
-        ];
+        let config_seeds = ConfigSeeds::new(
+            instruction_data.seed,
+            instruction_data.mint_x,
+            instruction_data.mint_y,
+            instruction_data.config_bump,
+        );
+        let config_seeds = config_seeds.as_seeds();
         let config_signer = Signer::from(&config_seeds);
         // 计算 Config 账户所需的租金空间 (使用我们在 state.rs 定义的 LEN)
         CreateAccount {
@@ -142,16 +141,7 @@ impl<'a> Initialize<'a> {
         .invoke_signed(&[config_signer])?;
 
         // --- 2. 初始化 Config 数据 ---
-        // 获取账户内存的可变引用
-        let config_account = unsafe { Config::load_mut_unchecked(accounts.config)? };
-        config_account.set_inner(
-            instruction_data.seed,
-            instruction_data.authority, // 将 [u8;32] 转为 Pubkey
-            instruction_data.mint_x,
-            instruction_data.mint_y,
-            instruction_data.fee,
-            instruction_data.config_bump,
-        )?;
+        self.write_config()?;
 
         // --- 3. 创建 Mint LP 账户 ---
         let mint_space = size_of::<Mint>();
@@ -183,4 +173,44 @@ impl<'a> Initialize<'a> {
 
         Ok(())
     }
+
+    /// 把指令数据与即将创建的 LP mint 地址写入刚创建的 Config 账户
+    fn write_config(&self) -> ProgramResult {
+        let instruction_data = &self.instruction_data;
+        let accounts = &self.accounts;
+
+        // 获取账户内存的可变引用
+        let config_account = unsafe { Config::load_mut_unchecked(accounts.config)? };
+        config_account.set_inner(
+            instruction_data.seed,
+            instruction_data.authority, // 将 [u8;32] 转为 Pubkey
+            instruction_data.mint_x,
+            instruction_data.mint_y,
+            instruction_data.fee,
+            instruction_data.config_bump,
+            *accounts.mint_lp.key(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+
+    #[test]
+    fn config_records_the_created_lp_mint() {
+        let mut initializer = TestAccount::new([8; 32], [0; 32], &[]).signer().writable();
+        let mut mint_lp = TestAccount::new([3; 32], [0; 32], &[]).writable();
+        let mut config = TestAccount::new([1; 32], [0; 32], &[0; Config::LEN]).writable();
+        let accounts = [initializer.info(), mint_lp.info(), config.info()];
+        let data = [0u8; size_of::<InitializeInstructionData>() - 32];
+
+        Initialize::try_from((&data[..], &accounts[..]))
+            .unwrap()
+            .write_config()
+            .unwrap();
+        let config = unsafe { Config::from_bytes_unchecked(config.data()) };
+        assert_eq!(config.mint_lp(), &[3; 32]);
+    }
 }
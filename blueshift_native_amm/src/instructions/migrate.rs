@@ -0,0 +1,131 @@
+use pinocchio::{
+    ProgramResult,
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::find_program_address,
+    sysvars::{Sysvar, rent::Rent},
+};
+
+use crate::{Config, MINT_LP_SEED};
+
+/*
+    把新增 `mint_lp` 字段之前创建的 Config (`Config::LEGACY_LEN` 字节) 迁移到当前布局。
+
+    扩容到 `Config::LEN`，免租金差额由 payer 补足，并写入池子的 LP mint 地址。
+    LP mint 是 ["mint_lp", config] 的 PDA，地址可以直接推导，因此任何人都可以发起迁移；
+    迁移之前 Deposit / Withdraw / Swap 都会因为 Config 长度不符而失败。
+    已经是当前布局的 Config 不做任何修改。
+*/
+pub struct MigrateAccounts<'a> {
+    /// 支付补足的租金，须签名且可写
+    pub payer: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub mint_lp: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for MigrateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let mut iter = accounts.iter();
+        Ok(Self {
+            payer: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            mint_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+pub struct Migrate<'a> {
+    pub accounts: MigrateAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for Migrate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = MigrateAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Migrate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // 1. 已迁移的 Config 直接返回
+        if accounts.config.data_len() == Config::LEN {
+            return Ok(());
+        }
+        if accounts.config.data_len() != Config::LEGACY_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if accounts.config.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        if !accounts.payer.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // 2. 传入的 mint_lp 必须是 Initialize 为这个 Config 创建的 PDA
+        let (mint_lp, _) =
+            find_program_address(&[MINT_LP_SEED, accounts.config.key().as_ref()], &crate::ID);
+        if accounts.mint_lp.key().ne(&mint_lp) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // 3. 扩容并写入 LP mint 地址
+        let minimum_balance = Rent::get()?.minimum_balance(Config::LEN);
+        Config::realloc_legacy(accounts.config, accounts.payer, minimum_balance)?;
+        Config::load_mut(accounts.config)?.set_mint_lp(mint_lp);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+
+    fn migrate(config: &mut TestAccount, payer: TestAccount) -> ProgramResult {
+        let mut payer = payer;
+        let mut mint_lp = TestAccount::new([3; 32], pinocchio_token::ID, &[]);
+        let accounts = [payer.info(), config.info(), mint_lp.info()];
+        Migrate::try_from((&[][..], &accounts[..]))?.process()
+    }
+
+    fn payer() -> TestAccount {
+        TestAccount::new([2; 32], [0; 32], &[]).signer().writable()
+    }
+
+    #[test]
+    fn migrated_config_is_left_untouched() {
+        let data = [5u8; Config::LEN];
+        let mut config = TestAccount::new([1; 32], crate::ID, &data).writable();
+        assert_eq!(migrate(&mut config, payer()), Ok(()));
+        assert_eq!(config.data(), &data[..]);
+    }
+
+    #[test]
+    fn only_signed_legacy_configs_of_this_program_are_migrated() {
+        let mut short = TestAccount::new([1; 32], crate::ID, &[0; Config::LEGACY_LEN - 1]);
+        assert_eq!(
+            migrate(&mut short, payer()),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut foreign = TestAccount::new([1; 32], [3; 32], &[0; Config::LEGACY_LEN]);
+        assert_eq!(
+            migrate(&mut foreign, payer()),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+        let mut legacy = TestAccount::new([1; 32], crate::ID, &[0; Config::LEGACY_LEN]);
+        assert_eq!(
+            migrate(&mut legacy, TestAccount::new([2; 32], [0; 32], &[])),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+}
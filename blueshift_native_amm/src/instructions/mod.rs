@@ -1,9 +1,11 @@
 pub mod deposit;
 pub mod initialize;
+pub mod migrate;
 pub mod swap;
 pub mod withdraw;
 
 pub use deposit::*;
 pub use initialize::*;
+pub use migrate::*;
 pub use swap::*;
 pub use withdraw::*;
@@ -2,13 +2,13 @@ use constant_product_curve::{ConstantProduct, LiquidityPair};
 use pinocchio::{
     ProgramResult,
     account_info::AccountInfo,
-    instruction::{Seed, Signer},
+    instruction::Signer,
     program_error::ProgramError,
     sysvars::{Sysvar, clock::Clock},
 };
 use pinocchio_token::{instructions::Transfer, state::TokenAccount};
 
-use crate::{CONFIG_SEED, Config};
+use crate::{Config, seeds::ConfigSeeds};
 
 /*
     计算通过将一定数量的 mint_y 发送到 AMM（或反之）后，能够接收到的 mint_x 的数量，包括手续费。
@@ -124,18 +124,8 @@ impl<'a> Swap<'a> {
             .map_err(|_| ProgramError::InvalidArgument)?;
 
         // 4. 准备签名种子 (用于从金库转出)
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x_key = config.mint_x();
-        let mint_y_key = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(CONFIG_SEED),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x_key.as_ref()),
-            Seed::from(mint_y_key.as_ref()),
-            Seed::from(&bump),
-        ];
+        let config_seeds = ConfigSeeds::from_config(&config);
+        let config_seeds = config_seeds.as_seeds();
         let signer = Signer::from(&config_seeds);
 
         // 5. 执行原子转账
@@ -2,7 +2,7 @@ use constant_product_curve::ConstantProduct;
 use pinocchio::{
     ProgramResult,
     account_info::AccountInfo,
-    instruction::{Seed, Signer},
+    instruction::Signer,
     program_error::ProgramError,
     sysvars::{Sysvar, clock::Clock},
 };
@@ -11,7 +11,7 @@ use pinocchio_token::{
     state::{Mint, TokenAccount},
 };
 
-use crate::{CONFIG_SEED, Config};
+use crate::{Config, seeds::ConfigSeeds};
 
 /*
     根据用户希望 burn 的 LP 数量，提取 mint_x 和 mint_y 代币。
@@ -111,6 +111,10 @@ impl<'a> Withdraw<'a> {
         if config.state() == 2 {
             return Err(ProgramError::InvalidAccountData);
         }
+        // 只能销毁本池子的 LP，否则可以用其他 mint 的代币赎回本池子的储备
+        if accounts.mint_lp.key().ne(config.mint_lp()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
 
         // 3. 反序列化代币信息
         let mint_lp = unsafe { Mint::from_account_info_unchecked(accounts.mint_lp)? };
@@ -148,18 +152,8 @@ impl<'a> Withdraw<'a> {
         .invoke()?;
 
         // 7. 构造 Config PDA 签名以从金库转账
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x_key = config.mint_x();
-        let mint_y_key = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(CONFIG_SEED),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x_key.as_ref()),
-            Seed::from(mint_y_key.as_ref()),
-            Seed::from(&bump),
-        ];
+        let config_seeds = ConfigSeeds::from_config(&config);
+        let config_seeds = config_seeds.as_seeds();
         let signer = Signer::from(&config_seeds);
 
         // 8. 转移 Token X 和 Y (Config PDA 签名)
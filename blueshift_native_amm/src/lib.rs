@@ -10,6 +10,11 @@ pub use instructions::*;
 pub mod state;
 pub use state::*;
 
+pub mod seeds;
+
+#[cfg(test)]
+mod test_utils;
+
 // 22222222222222222222222222222222222222222222
 pub const ID: Pubkey = [
     0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
@@ -28,6 +33,7 @@ fn process_instruction(
         Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((data, accounts))?.process(),
         Some((Withdraw::DISCRIMINATOR, data)) => Withdraw::try_from((data, accounts))?.process(),
         Some((Swap::DISCRIMINATOR, data)) => Swap::try_from((data, accounts))?.process(),
+        Some((Migrate::DISCRIMINATOR, data)) => Migrate::try_from((data, accounts))?.process(),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -0,0 +1,78 @@
+//! Config PDA 的签名种子。
+//!
+//! `Seed` 只借用字节，签名种子数组又必须活到 CPI 结束，因此这里不直接返回 `Signer`：
+//! 调用方先拿到种子数组，再在自己的作用域里构造 `Signer::from(&seeds)`。
+//! 与 pinocchio_amm 的 `seeds` 模块使用相同的做法。
+
+use pinocchio::{instruction::Seed, pubkey::Pubkey};
+
+use crate::{CONFIG_SEED, Config};
+
+/// Config PDA 的种子 `["config", seed, mint_x, mint_y, bump]`。
+///
+/// 持有种子字节的拷贝，构造签名时不需要继续借用 Config 账户，
+/// 各指令不必再各自把 `seed` / `bump` 绑定到局部变量上以延长生命周期。
+pub struct ConfigSeeds {
+    seed: [u8; 8],
+    mint_x: Pubkey,
+    mint_y: Pubkey,
+    bump: [u8; 1],
+}
+
+impl ConfigSeeds {
+    #[inline(always)]
+    pub fn new(seed: u64, mint_x: Pubkey, mint_y: Pubkey, bump: [u8; 1]) -> Self {
+        Self {
+            seed: seed.to_le_bytes(),
+            mint_x,
+            mint_y,
+            bump,
+        }
+    }
+
+    /// 从已初始化的 Config 中拷贝种子
+    #[inline(always)]
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.seed(),
+            *config.mint_x(),
+            *config.mint_y(),
+            config.config_bump(),
+        )
+    }
+
+    #[inline(always)]
+    pub fn as_seeds(&self) -> [Seed<'_>; 5] {
+        [
+            Seed::from(CONFIG_SEED),
+            Seed::from(&self.seed),
+            Seed::from(self.mint_x.as_ref()),
+            Seed::from(self.mint_y.as_ref()),
+            Seed::from(&self.bump),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_address::Address;
+
+    #[test]
+    fn seeds_derive_the_config_pda() {
+        let (seed, mint_x, mint_y) = (42u64, [2; 32], [3; 32]);
+        let program_id = Address::new_from_array(crate::ID);
+        let (expected, bump) = Address::find_program_address(
+            &[CONFIG_SEED, &seed.to_le_bytes(), &mint_x, &mint_y],
+            &program_id,
+        );
+
+        let seeds = ConfigSeeds::new(seed, mint_x, mint_y, [bump]);
+        let seeds = seeds.as_seeds();
+        let seeds: Vec<&[u8]> = seeds.iter().map(|seed| &**seed).collect();
+        assert_eq!(
+            Address::create_program_address(&seeds, &program_id),
+            Ok(expected)
+        );
+    }
+}
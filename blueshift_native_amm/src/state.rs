@@ -1,9 +1,11 @@
 use core::mem::size_of;
 use pinocchio::{
+    ProgramResult,
     account_info::{AccountInfo, Ref, RefMut},
     program_error::ProgramError,
     pubkey::Pubkey,
 };
+use pinocchio_system::instructions::Transfer;
 
 pub const CONFIG_SEED: &[u8] = b"config";
 pub const MINT_LP_SEED: &[u8] = b"mint_lp";
@@ -17,8 +19,29 @@ pub struct Config {
     mint_y: Pubkey,
     fee: [u8; 2],
     config_bump: [u8; 1],
+    mint_lp: Pubkey,
 }
 
+// 所有字段都是 1 字节对齐，`#[repr(C)]` 不会插入填充。这里锁定本 crate 自己的偏移，字段重排或改变大小
+// 会在编译期报错。
+//
+// 0..108 (state 到 config_bump) 与 pinocchio_amm 的 Config 排列相同，从第 108 字节起两者分叉：
+// 这里是 `mint_lp` (108..140)，pinocchio_amm 则是 `fee_mode` (108)、`fee_recipient` (109..141) 等
+// 后续追加的字段。两个 crate 互不依赖，一侧的布局变化不会让另一侧报错，两者的 Config 账户也不能互相解析。
+// 共有的前缀由两个 crate 的 state.rs 测试分别与 `fixtures/config_prefix.hex` 比较，任何一侧漂移都会失败。
+const _: () = {
+    use core::mem::offset_of;
+    assert!(offset_of!(Config, state) == 0);
+    assert!(offset_of!(Config, seed) == 1);
+    assert!(offset_of!(Config, authority) == 9);
+    assert!(offset_of!(Config, mint_x) == 41);
+    assert!(offset_of!(Config, mint_y) == 73);
+    assert!(offset_of!(Config, fee) == 105);
+    assert!(offset_of!(Config, config_bump) == 107);
+    assert!(offset_of!(Config, mint_lp) == 108);
+    assert!(Config::LEN == 140);
+};
+
 #[repr(u8)]
 pub enum AmmState {
     Uninitialized = 0u8,
@@ -30,6 +53,9 @@ pub enum AmmState {
 impl Config {
     pub const LEN: usize = size_of::<Config>();
 
+    /// 新增 `mint_lp` 之前的 Config 长度。这样的账户需要先由 `Migrate` 扩容并补上 LP mint 地址
+    pub const LEGACY_LEN: usize = core::mem::offset_of!(Config, mint_lp);
+
     #[inline(always)]
     pub fn load<'a>(account_info: &'a AccountInfo) -> Result<Ref<'a, Self>, ProgramError> {
         if account_info.data_len() != Self::LEN {
@@ -107,6 +133,11 @@ impl Config {
     pub fn config_bump(&self) -> [u8; 1] {
         self.config_bump
     }
+    /// Initialize 创建的 LP mint 地址，Deposit / Withdraw 用它校验传入的 mint_lp
+    #[inline(always)]
+    pub fn mint_lp(&self) -> &Pubkey {
+        &self.mint_lp
+    }
 
     #[inline(always)]
     pub fn load_mut<'a>(account_info: &'a AccountInfo) -> Result<RefMut<'a, Self>, ProgramError> {
@@ -122,6 +153,36 @@ impl Config {
         ))
     }
 
+    /// 把 `LEGACY_LEN` 字节的旧版 Config 扩容到 `LEN`，免租金差额由 `payer` 补足，
+    /// 新增的字节为 0。已经是 `LEN` 的账户不做任何修改
+    pub fn realloc_legacy(
+        account_info: &AccountInfo,
+        payer: &AccountInfo,
+        minimum_balance: u64,
+    ) -> ProgramResult {
+        let len = account_info.data_len();
+        if len == Self::LEN {
+            return Ok(());
+        }
+        if len != Self::LEGACY_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if account_info.owner().ne(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let top_up = minimum_balance.saturating_sub(account_info.lamports());
+        if top_up > 0 {
+            Transfer {
+                from: payer,
+                to: account_info,
+                lamports: top_up,
+            }
+            .invoke()?;
+        }
+        account_info.resize(Self::LEN)
+    }
+
     #[inline(always)]
     pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
         if state.ge(&(AmmState::WithdrawOnly as u8)) {
@@ -166,6 +227,12 @@ impl Config {
     }
 
     #[inline(always)]
+    pub fn set_mint_lp(&mut self, mint_lp: Pubkey) {
+        self.mint_lp = mint_lp;
+    }
+
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
     pub fn set_inner(
         &mut self,
         seed: u64,
@@ -174,6 +241,7 @@ impl Config {
         mint_y: Pubkey,
         fee: u16,
         config_bump: [u8; 1],
+        mint_lp: Pubkey,
     ) -> Result<(), ProgramError> {
         self.set_state(AmmState::Initialized as u8)?;
         self.set_seed(seed);
@@ -182,6 +250,7 @@ impl Config {
         self.set_mint_y(mint_y);
         self.set_fee(fee)?;
         self.set_config_bump(config_bump);
+        self.set_mint_lp(mint_lp);
         Ok(())
     }
 
@@ -211,3 +280,69 @@ impl Config {
         Ok(unsafe { Self::from_bytes_unchecked_mut(account_info.borrow_mut_data_unchecked()) })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+
+    #[test]
+    fn legacy_config_is_extended_with_a_zeroed_mint_lp() {
+        let mut legacy = [7u8; Config::LEGACY_LEN];
+        legacy[0] = AmmState::Initialized as u8;
+        let mut config = TestAccount::new([1; 32], crate::ID, &legacy).writable();
+        let mut payer = TestAccount::new([2; 32], [0; 32], &[]).signer().writable();
+
+        Config::realloc_legacy(&config.info(), &payer.info(), 1_000).unwrap();
+        let data = config.data();
+        assert_eq!(data.len(), Config::LEN);
+        assert_eq!(&data[..Config::LEGACY_LEN], &legacy[..]);
+        assert_eq!(&data[Config::LEGACY_LEN..], &[0; 32]);
+
+        // 已经是当前长度时不再扩容
+        Config::realloc_legacy(&config.info(), &payer.info(), 1_000).unwrap();
+        assert_eq!(config.data().len(), Config::LEN);
+    }
+
+    #[test]
+    fn only_legacy_configs_owned_by_the_program_are_extended() {
+        let mut payer = TestAccount::new([2; 32], [0; 32], &[]).signer().writable();
+        let mut short = TestAccount::new([1; 32], crate::ID, &[0; Config::LEGACY_LEN - 1]);
+        assert_eq!(
+            Config::realloc_legacy(&short.info(), &payer.info(), 1_000),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mut foreign = TestAccount::new([1; 32], [3; 32], &[0; Config::LEGACY_LEN]);
+        assert_eq!(
+            Config::realloc_legacy(&foreign.info(), &payer.info(), 1_000),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
+
+    /// `fixtures/config_prefix.hex` 中两个 crate 共有的 Config 前缀
+    fn shared_prefix_fixture() -> Vec<u8> {
+        let hex: String = include_str!("../../fixtures/config_prefix.hex")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn shared_prefix_matches_pinocchio_amm() {
+        // pinocchio_amm 的 state.rs 以相同的字段值与同一个文件比较
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_state(AmmState::Initialized as u8).unwrap();
+        config.set_seed(0x0102_0304_0506_0708);
+        config.set_authority([9; 32]);
+        config.set_mint_x([2; 32]);
+        config.set_mint_y([3; 32]);
+        config.set_fee(30).unwrap();
+        config.set_config_bump([254]);
+        assert_eq!(&data[..Config::LEGACY_LEN], &shared_prefix_fixture()[..]);
+    }
+}
@@ -0,0 +1,93 @@
+//! 主机单元测试用的内存账户。
+//!
+//! 按运行时传给程序的输入布局 (账户头 + 账户数据 + 可扩容区) 分配缓冲区，
+//! 再由此构造 `AccountInfo`，不需要 SVM 就能驱动账户解析与校验逻辑。
+//! 主机上的 CPI 与 sysvar 都不可用 (`black_box`)，依赖它们的路径请用链上集成测试覆盖。
+//! 与 pinocchio_amm 的 `test_utils` 使用相同的做法。
+
+use pinocchio::{
+    account_info::{AccountInfo, MAX_PERMITTED_DATA_INCREASE},
+    entrypoint::NON_DUP_MARKER,
+    pubkey::Pubkey,
+};
+
+/// 运行时输入区中的账户头，与 `pinocchio::account_info::Account` (crate 私有) 的布局相同
+#[repr(C)]
+struct RawAccount {
+    borrow_state: u8,
+    is_signer: u8,
+    is_writable: u8,
+    executable: u8,
+    resize_delta: i32,
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data_len: u64,
+}
+
+/// 一个内存中的账户。缓冲区按 8 字节对齐，与运行时的输入区一致
+pub struct TestAccount {
+    buf: Vec<u64>,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: &[u8]) -> Self {
+        let header = size_of::<RawAccount>();
+        let len = header + data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let mut account = Self {
+            buf: vec![0u64; len.div_ceil(8)],
+        };
+        let raw = account.raw();
+        unsafe {
+            raw.write(RawAccount {
+                borrow_state: NON_DUP_MARKER,
+                is_signer: 0,
+                is_writable: 0,
+                executable: 0,
+                resize_delta: 0,
+                key,
+                owner,
+                lamports: 0,
+                data_len: data.len() as u64,
+            });
+        }
+        account.bytes_mut()[header..header + data.len()].copy_from_slice(data);
+        account
+    }
+
+    pub fn signer(mut self) -> Self {
+        unsafe { (*self.raw()).is_signer = 1 };
+        self
+    }
+
+    pub fn writable(mut self) -> Self {
+        unsafe { (*self.raw()).is_writable = 1 };
+        self
+    }
+
+    /// 构造指向该缓冲区的 `AccountInfo`；`TestAccount` 必须比返回的 `AccountInfo` 活得更久
+    pub fn info(&mut self) -> AccountInfo {
+        // AccountInfo 是只包含一个账户头指针的 `#[repr(C)]` 结构体
+        unsafe { core::mem::transmute::<*mut RawAccount, AccountInfo>(self.raw()) }
+    }
+
+    /// 账户当前的数据 (按 `data_len`，包含扩容后的部分)
+    pub fn data(&self) -> &[u8] {
+        let header = size_of::<RawAccount>();
+        let len = unsafe { (*(self.buf.as_ptr() as *const RawAccount)).data_len } as usize;
+        let bytes = unsafe {
+            core::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.buf.len() * 8)
+        };
+        &bytes[header..header + len]
+    }
+
+    fn raw(&mut self) -> *mut RawAccount {
+        self.buf.as_mut_ptr() as *mut RawAccount
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            core::slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut u8, self.buf.len() * 8)
+        }
+    }
+}
@@ -0,0 +1,480 @@
+//! 客户端辅助函数 (需开启 `client` feature)。
+//!
+//! 与链上使用完全相同的种子方案推导 PDA，避免集成方在客户端硬编码种子；
+//! 并按各指令 `try_from` 期望的顺序构造账户列表，避免账户顺序或读写标记出错。
+//!
+//! ## 0.2.0 的账户列表变更 (不兼容)
+//!
+//! 相对 0.1.0，下列指令的固定账户列表变长，按 0.1.0 顺序构造的交易会以
+//! `NotEnoughAccountKeys` 失败，追加了可选账户的交易则会被错位解析，须改用本模块的函数重新构造：
+//!
+//! * `Deposit`：在 Token 程序之后追加 `global_state` (下标 9) 与 `lp_authority` (下标 10)；
+//!   LP mint 的 mint authority 由 Config 改为 LP 权限 PDA
+//! * `Swap`：在 Token 程序之后追加 `global_state` (下标 7)，可选账户从下标 8 开始
+//!
+//! 客户端可以先调用 `GetVersion`，按返回的 `program_version` (`0x0002_0000` 起) 选择账户布局。
+
+use pinocchio::Address;
+
+use crate::{
+    CONFIG_SEED, GLOBAL_SEED, LP_AUTHORITY_SEED, LP_ESCROW_SEED, MINT_LP_SEED, USER_DEPOSIT_SEED,
+};
+
+/// 推导 Config PDA 及其 bump。
+///
+/// 种子：`["config", seed (LE), mint_x, mint_y]`，与 `Initialize` 中的签名种子一致。
+/// 返回的 bump 即为 `InitializeInstructionData.config_bump`。
+pub fn find_config_pda(seed: u64, mint_x: &Address, mint_y: &Address) -> (Address, u8) {
+    Address::find_program_address(
+        &[
+            CONFIG_SEED,
+            &seed.to_le_bytes(),
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+        ],
+        &crate::ID,
+    )
+}
+
+/// 推导 LP Mint PDA 及其 bump。
+///
+/// 种子：`["mint_lp", config]`。返回的 bump 即为 `InitializeInstructionData.lp_bump`。
+pub fn find_lp_mint_pda(config: &Address) -> (Address, u8) {
+    Address::find_program_address(&[MINT_LP_SEED, config.as_ref()], &crate::ID)
+}
+
+/// 推导 LP mint 权限 PDA 及其 bump。
+///
+/// 种子：`["lp_authority", config]`。`Deposit` 等铸造 LP 的指令需要传入该账户。
+pub fn find_lp_authority_pda(config: &Address) -> (Address, u8) {
+    Address::find_program_address(&[LP_AUTHORITY_SEED, config.as_ref()], &crate::ID)
+}
+
+/// 推导用户在某个池子下的存款记录 PDA 及其 bump。
+///
+/// 种子：`["user_deposit", config, user]`。`Config.lock_duration > 0` 时 Deposit / ClaimLockedLp 需要传入该账户。
+pub fn find_user_deposit_pda(config: &Address, user: &Address) -> (Address, u8) {
+    Address::find_program_address(
+        &[USER_DEPOSIT_SEED, config.as_ref(), user.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// 客户端构造指令时使用的账户元数据，字段含义与 Solana SDK 的 `AccountMeta` 相同。
+///
+/// 这里单独定义而不是依赖 SDK，`client` feature 只需要引入地址类型。
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AccountMeta {
+    pub address: Address,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl AccountMeta {
+    /// 可写账户
+    pub fn writable(address: &Address, is_signer: bool) -> Self {
+        Self {
+            address: address.clone(),
+            is_signer,
+            is_writable: true,
+        }
+    }
+
+    /// 只读账户
+    pub fn readonly(address: &Address, is_signer: bool) -> Self {
+        Self {
+            address: address.clone(),
+            is_signer,
+            is_writable: false,
+        }
+    }
+}
+
+/// 推导全局暂停单例 PDA。Deposit / Swap 总是需要传入该地址，即使尚未创建。
+pub fn find_global_state_pda() -> (Address, u8) {
+    Address::find_program_address(&[GLOBAL_SEED], &crate::ID)
+}
+
+/// `Initialize` 的账户列表，顺序与 `InitializeAccounts::try_from` 一致。
+///
+/// `mints` 为 `(mint_x, mint_y)`，仅在 `max_decimals_diff` 不是 `DECIMALS_DIFF_UNCHECKED` 时需要；
+/// CPI 所需的 System Program 与 Token 程序追加在最后。
+pub fn initialize_accounts(
+    initializer: &Address,
+    config: &Address,
+    #[cfg(feature = "creation-fee")] treasury: &Address,
+    mints: Option<(&Address, &Address)>,
+) -> Vec<AccountMeta> {
+    let (mint_lp, _) = find_lp_mint_pda(config);
+    let mut metas = vec![
+        AccountMeta::writable(initializer, true),
+        AccountMeta::writable(&mint_lp, false),
+        AccountMeta::writable(config, false),
+    ];
+    #[cfg(feature = "creation-fee")]
+    metas.push(AccountMeta::writable(treasury, false));
+    if let Some((mint_x, mint_y)) = mints {
+        metas.push(AccountMeta::readonly(mint_x, false));
+        metas.push(AccountMeta::readonly(mint_y, false));
+    }
+    metas.push(AccountMeta::readonly(&pinocchio_system::ID, false));
+    metas.push(AccountMeta::readonly(&pinocchio_token::ID, false));
+    metas
+}
+
+/// 推导用户在某个池子下的 LP 托管代币账户 PDA 及其 bump。
+///
+/// 种子：`["lp_escrow", config, user]`。`Config.lock_duration > 0` 时 Deposit 把 LP 铸造到该账户，
+/// 锁定期结束后由 `ClaimLockedLp` 转给用户。
+pub fn find_lp_escrow_pda(config: &Address, user: &Address) -> (Address, u8) {
+    Address::find_program_address(
+        &[LP_ESCROW_SEED, config.as_ref(), user.as_ref()],
+        &crate::ID,
+    )
+}
+
+/// `Deposit` 的账户列表，顺序与 `DepositAccounts::try_from` 一致。
+/// LP mint、LP 权限与全局暂停 PDA 由 `config` 推导。
+///
+/// 池子启用 LP 锁定期时需追加可写的存款记录 PDA ([`find_user_deposit_pda`])、
+/// LP 托管账户 ([`find_lp_escrow_pda`]) 与 System Program；
+/// 用户可能需要支付这两个 PDA 的租金，因此标记为可写。
+pub fn deposit_accounts(
+    user: &Address,
+    config: &Address,
+    vault_x: &Address,
+    vault_y: &Address,
+    user_x_ata: &Address,
+    user_y_ata: &Address,
+    user_lp_ata: &Address,
+) -> Vec<AccountMeta> {
+    let (mint_lp, _) = find_lp_mint_pda(config);
+    let (lp_authority, _) = find_lp_authority_pda(config);
+    let (global_state, _) = find_global_state_pda();
+    vec![
+        AccountMeta::writable(user, true),
+        AccountMeta::writable(&mint_lp, false),
+        AccountMeta::writable(vault_x, false),
+        AccountMeta::writable(vault_y, false),
+        AccountMeta::writable(user_x_ata, false),
+        AccountMeta::writable(user_y_ata, false),
+        AccountMeta::writable(user_lp_ata, false),
+        AccountMeta::readonly(config, false),
+        AccountMeta::readonly(&pinocchio_token::ID, false),
+        AccountMeta::readonly(&global_state, false),
+        AccountMeta::readonly(&lp_authority, false),
+    ]
+}
+
+/// `Withdraw` 的账户列表，顺序与 `WithdrawAccounts::try_from` 一致。LP mint 由 `config` 推导。
+pub fn withdraw_accounts(
+    user: &Address,
+    config: &Address,
+    vault_x: &Address,
+    vault_y: &Address,
+    user_x_ata: &Address,
+    user_y_ata: &Address,
+    user_lp_ata: &Address,
+) -> Vec<AccountMeta> {
+    let (mint_lp, _) = find_lp_mint_pda(config);
+    vec![
+        AccountMeta::readonly(user, true),
+        AccountMeta::writable(&mint_lp, false),
+        AccountMeta::writable(vault_x, false),
+        AccountMeta::writable(vault_y, false),
+        AccountMeta::writable(user_x_ata, false),
+        AccountMeta::writable(user_y_ata, false),
+        AccountMeta::writable(user_lp_ata, false),
+        AccountMeta::readonly(config, false),
+        AccountMeta::readonly(&pinocchio_token::ID, false),
+    ]
+}
+
+/// `ClaimLockedLp` 的账户列表，顺序与 `ClaimLockedLpAccounts::try_from` 一致。
+/// 存款记录、托管账户与 LP 权限 PDA 由 `config` 与 `user` 推导。
+pub fn claim_locked_lp_accounts(
+    user: &Address,
+    config: &Address,
+    user_lp_ata: &Address,
+) -> Vec<AccountMeta> {
+    let (user_deposit_state, _) = find_user_deposit_pda(config, user);
+    let (lp_escrow, _) = find_lp_escrow_pda(config, user);
+    let (lp_authority, _) = find_lp_authority_pda(config);
+    vec![
+        AccountMeta::readonly(user, true),
+        AccountMeta::readonly(config, false),
+        AccountMeta::readonly(&user_deposit_state, false),
+        AccountMeta::writable(&lp_escrow, false),
+        AccountMeta::writable(user_lp_ata, false),
+        AccountMeta::readonly(&lp_authority, false),
+        AccountMeta::readonly(&pinocchio_token::ID, false),
+    ]
+}
+
+/// `Swap` 固定部分的账户列表，顺序与 `SwapAccounts::try_from` 一致。
+///
+/// 可选账户需按 `SwapAccounts::remaining` 文档中的顺序追加在后面。
+/// 用户可能需要支付交换记录 PDA 的租金或 tip，因此标记为可写。
+pub fn swap_accounts(
+    user: &Address,
+    config: &Address,
+    vault_x: &Address,
+    vault_y: &Address,
+    user_x_ata: &Address,
+    user_y_ata: &Address,
+) -> Vec<AccountMeta> {
+    let (global_state, _) = find_global_state_pda();
+    vec![
+        AccountMeta::writable(user, true),
+        AccountMeta::writable(user_x_ata, false),
+        AccountMeta::writable(user_y_ata, false),
+        AccountMeta::writable(vault_x, false),
+        AccountMeta::writable(vault_y, false),
+        AccountMeta::writable(config, false),
+        AccountMeta::readonly(&pinocchio_token::ID, false),
+        AccountMeta::readonly(&global_state, false),
+    ]
+}
+
+/// 只需要 `[authority, config]` 的管理指令 (`SetBaseFee`、`SetState`、`SetMaxSwapBps` 等) 的账户列表
+pub fn admin_accounts(authority: &Address, config: &Address) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::readonly(authority, true),
+        AccountMeta::writable(config, false),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        seeds::{ConfigSeeds, mint_lp_seeds},
+        test_utils::TestAccount,
+    };
+    use pinocchio::AccountView;
+
+    /// 用签名种子 (带 bump) 重新计算地址，与链上 `invoke_signed` 的校验方式相同
+    fn create(seeds: &[pinocchio::cpi::Seed]) -> Address {
+        let seeds: Vec<&[u8]> = seeds.iter().map(|seed| &**seed).collect();
+        Address::create_program_address(&seeds, &crate::ID).unwrap()
+    }
+
+    #[test]
+    fn derived_pdas_match_the_program_signer_seeds() {
+        let (mint_x, mint_y) = (
+            Address::new_from_array([2; 32]),
+            Address::new_from_array([3; 32]),
+        );
+        let (config, config_bump) = find_config_pda(42, &mint_x, &mint_y);
+        let seeds = ConfigSeeds::new(42, mint_x.clone(), mint_y.clone(), [config_bump]);
+        assert_eq!(create(&seeds.as_seeds()), config);
+        // seed 参与推导：换一个 seed 得到另一个池子
+        assert_ne!(find_config_pda(43, &mint_x, &mint_y).0, config);
+
+        let (mint_lp, lp_bump) = find_lp_mint_pda(&config);
+        assert_eq!(create(&mint_lp_seeds(&config, &[lp_bump])), mint_lp);
+    }
+
+    /// 按 `metas` 构造账户交给程序的解析函数，返回 `fields` 各字段拿到的账户 (地址与标记)
+    fn parse(
+        metas: &[AccountMeta],
+        fields: impl FnOnce(&[AccountView]) -> Vec<&AccountView>,
+    ) -> Vec<AccountMeta> {
+        let mut accounts: Vec<TestAccount> = metas
+            .iter()
+            .map(|meta| {
+                let account = TestAccount::new(meta.address.clone(), Address::default(), &[]);
+                match (meta.is_signer, meta.is_writable) {
+                    (true, true) => account.signer().writable(),
+                    (true, false) => account.signer(),
+                    (false, true) => account.writable(),
+                    (false, false) => account,
+                }
+            })
+            .collect();
+        let views: Vec<AccountView> = accounts.iter_mut().map(TestAccount::view).collect();
+        fields(&views)
+            .into_iter()
+            .map(|view| AccountMeta {
+                address: view.address().clone(),
+                is_signer: view.is_signer(),
+                is_writable: view.is_writable(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builders_follow_each_instructions_account_order() {
+        use crate::{
+            AdminAccounts, ClaimLockedLpAccounts, DepositAccounts, InitializeAccounts,
+            SwapAccounts, WithdrawAccounts,
+        };
+        let [user, config, vault_x, vault_y, user_x, user_y, user_lp] =
+            [6, 1, 4, 5, 7, 8, 10].map(|byte| Address::new_from_array([byte; 32]));
+        let (mint_lp, _) = find_lp_mint_pda(&config);
+        let (lp_authority, _) = find_lp_authority_pda(&config);
+        let (global_state, _) = find_global_state_pda();
+        let token = pinocchio_token::ID;
+        let (w, r) = (AccountMeta::writable, AccountMeta::readonly);
+
+        let metas = deposit_accounts(
+            &user, &config, &vault_x, &vault_y, &user_x, &user_y, &user_lp,
+        );
+        assert_eq!(
+            parse(&metas, |accounts| {
+                let a = DepositAccounts::try_from(accounts).unwrap();
+                assert!(a.remaining.is_empty());
+                vec![
+                    a.user,
+                    a.mint_lp,
+                    a.vault_x,
+                    a.vault_y,
+                    a.user_x_ata,
+                    a.user_y_ata,
+                    a.user_lp_ata,
+                    a.config,
+                    a.token_program,
+                    a.global_state,
+                    a.lp_authority,
+                ]
+            }),
+            [
+                w(&user, true),
+                w(&mint_lp, false),
+                w(&vault_x, false),
+                w(&vault_y, false),
+                w(&user_x, false),
+                w(&user_y, false),
+                w(&user_lp, false),
+                r(&config, false),
+                r(&token, false),
+                r(&global_state, false),
+                r(&lp_authority, false),
+            ]
+        );
+
+        let metas = withdraw_accounts(
+            &user, &config, &vault_x, &vault_y, &user_x, &user_y, &user_lp,
+        );
+        assert_eq!(
+            parse(&metas, |accounts| {
+                let a = WithdrawAccounts::try_from(accounts).unwrap();
+                vec![
+                    a.user,
+                    a.mint_lp,
+                    a.vault_x,
+                    a.vault_y,
+                    a.user_x_ata,
+                    a.user_y_ata,
+                    a.user_lp_ata,
+                    a.config,
+                    a.token_program,
+                ]
+            }),
+            [
+                r(&user, true),
+                w(&mint_lp, false),
+                w(&vault_x, false),
+                w(&vault_y, false),
+                w(&user_x, false),
+                w(&user_y, false),
+                w(&user_lp, false),
+                r(&config, false),
+                r(&token, false),
+            ]
+        );
+
+        let metas = swap_accounts(&user, &config, &vault_x, &vault_y, &user_x, &user_y);
+        assert_eq!(
+            parse(&metas, |accounts| {
+                let a = SwapAccounts::try_from(accounts).unwrap();
+                assert!(a.remaining.is_empty());
+                vec![
+                    a.user,
+                    a.user_x_ata,
+                    a.user_y_ata,
+                    a.vault_x,
+                    a.vault_y,
+                    a.config,
+                    a.token_program,
+                    a.global_state,
+                ]
+            }),
+            [
+                w(&user, true),
+                w(&user_x, false),
+                w(&user_y, false),
+                w(&vault_x, false),
+                w(&vault_y, false),
+                w(&config, false),
+                r(&token, false),
+                r(&global_state, false),
+            ]
+        );
+
+        let metas = claim_locked_lp_accounts(&user, &config, &user_lp);
+        assert_eq!(
+            parse(&metas, |accounts| {
+                let a = ClaimLockedLpAccounts::try_from(accounts).unwrap();
+                vec![
+                    a.user,
+                    a.config,
+                    a.user_deposit_state,
+                    a.lp_escrow,
+                    a.user_lp_ata,
+                    a.lp_authority,
+                    a.token_program,
+                ]
+            }),
+            [
+                r(&user, true),
+                r(&config, false),
+                r(&find_user_deposit_pda(&config, &user).0, false),
+                w(&find_lp_escrow_pda(&config, &user).0, false),
+                w(&user_lp, false),
+                r(&lp_authority, false),
+                r(&token, false),
+            ]
+        );
+
+        assert_eq!(
+            parse(&admin_accounts(&user, &config), |accounts| {
+                let a = AdminAccounts::try_from(accounts).unwrap();
+                vec![a.authority, a.config]
+            }),
+            [r(&user, true), w(&config, false)]
+        );
+
+        // Initialize：mint 账户与 CPI 程序都落在 remaining 中
+        #[cfg(feature = "creation-fee")]
+        let metas =
+            initialize_accounts(&user, &config, &crate::TREASURY, Some((&vault_x, &vault_y)));
+        #[cfg(not(feature = "creation-fee"))]
+        let metas = initialize_accounts(&user, &config, Some((&vault_x, &vault_y)));
+        assert_eq!(
+            parse(&metas, |accounts| {
+                let a = InitializeAccounts::try_from(accounts).unwrap();
+                let mut fields = vec![a.initializer, a.mint_lp, a.config];
+                #[cfg(feature = "creation-fee")]
+                fields.push(a.treasury);
+                fields.extend(a.remaining);
+                fields
+            }),
+            metas
+        );
+        let fixed = metas.len() - 4;
+        assert_eq!(
+            metas[..3],
+            [w(&user, true), w(&mint_lp, false), w(&config, false)]
+        );
+        assert_eq!(
+            metas[fixed..],
+            [
+                r(&vault_x, false),
+                r(&vault_y, false),
+                r(&pinocchio_system::ID, false),
+                r(&token, false),
+            ]
+        );
+    }
+}
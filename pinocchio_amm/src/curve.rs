@@ -0,0 +1,732 @@
+//! 交换数学的补充实现。
+//!
+//! ## 取整策略
+//!
+//! 所有整数除法都朝着对池子有利的方向取整，保证每笔交换之后 `k = x * y` 不会减少：
+//!
+//! * Exact-in (给定输入)：由 [`exact_in_swap`] 计算，手续费向上取整、输出向下取整。
+//!   `constant_product_curve::swap` 的有效输入与输出都朝用户有利的方向取整，
+//!   且 Y -> X 方向按未扣费的输入计算输出，因此不再用于交换。
+//! * Exact-out (给定输出)：由 [`exact_out_input`] 计算，所需的有效输入和含手续费的总输入
+//!   都向上取整，用户最多多付 1 个最小单位，池子不会因为舍入而吃亏。
+//! * 按比例存款 / 取款：由 [`deposit_amounts`] / [`withdraw_amounts`] 按 LP 份额在 u128 中精确计算，
+//!   存款向上取整、取款向下取整，取整误差都留给池子中现有的 LP。
+//!   X / Y 分别取整，存款比例与储备比例可能有微小偏差，这部分偏差总是被接受 (由存款人承担)。
+//!   用户可以额外按 [`ratio_within_tolerance`] 要求实际存款比例接近自己报价时的比例。
+
+use constant_product_curve::SwapResult;
+use pinocchio::{ProgramResult, error::ProgramError};
+
+use crate::AmmError;
+
+/// 向上取整的除法 `ceil(n / d)`
+#[inline(always)]
+fn div_ceil(n: u128, d: u128) -> Result<u128, ProgramError> {
+    if d == 0 {
+        return Err(ProgramError::ArithmeticOverflow);
+    }
+    Ok(n.div_ceil(d))
+}
+
+/// 价格定点数的小数位数：价格以 Q64.64 表示，即 `price = value / 2^64`。
+pub const PRICE_FRACTION_BITS: u32 = 64;
+
+/// 边际 (现货) 价格：1 单位 base 值多少 quote，仅由储备推导，不含手续费。
+///
+/// 返回 Q64.64 定点数 `reserve_quote * 2^64 / reserve_base` (向下取整)。
+/// 任一储备为 0 时价格没有意义，返回 `InsufficientLiquidity`。
+pub fn spot_price(reserve_base: u64, reserve_quote: u64) -> Result<u128, ProgramError> {
+    if reserve_base == 0 || reserve_quote == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    // reserve_quote < 2^64，左移 64 位后仍在 u128 范围内
+    Ok(((reserve_quote as u128) << PRICE_FRACTION_BITS) / reserve_base as u128)
+}
+
+/// `reserve * lp_amount / supply`，按 `round_up` 向上或向下取整
+#[inline(always)]
+fn pro_rata(
+    reserve: u64,
+    lp_amount: u64,
+    supply: u64,
+    round_up: bool,
+) -> Result<u64, ProgramError> {
+    let n = reserve as u128 * lp_amount as u128;
+    let amount = if round_up {
+        div_ceil(n, supply as u128)?
+    } else {
+        n / supply as u128
+    };
+    u64::try_from(amount).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// 按比例存款：在已有 `supply` 个 LP 的池子里铸造 `lp_amount` 个 LP 需要存入的 `(x, y)`。
+///
+/// `x = ceil(reserve_x * lp_amount / supply)`，`y` 同理。向上取整，存款人不能利用取整
+/// 以更少的代币换到同样的份额。`supply` 为 0 (首次存款) 时没有比例可言，返回 `InsufficientLiquidity`。
+pub fn deposit_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if supply == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    Ok((
+        pro_rata(reserve_x, lp_amount, supply, true)?,
+        pro_rata(reserve_y, lp_amount, supply, true)?,
+    ))
+}
+
+/// 向已有 `supply` 个 LP 的池子按比例存款，铸造 `lp_amount` 个 LP 需要存入的 `(x, y)`：
+///
+/// * 任一储备为 0 时比例没有意义，返回 `PoolInsolvent`
+/// * 数量由 [`deposit_amounts`] 向上取整计算；任一侧为 0 时返回 `ZeroDepositAmount`，
+///   不允许不付出代币就拿到 LP
+pub fn proportional_deposit(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if reserve_x == 0 || reserve_y == 0 {
+        return Err(AmmError::PoolInsolvent.into());
+    }
+    let (x, y) = deposit_amounts(reserve_x, reserve_y, supply, lp_amount)?;
+    if x == 0 || y == 0 {
+        return Err(AmmError::ZeroDepositAmount.into());
+    }
+    Ok((x, y))
+}
+
+/// 按比例取款：从已有 `supply` 个 LP 的池子销毁 `lp_amount` 个 LP 可取回的 `(x, y)`。
+///
+/// `x = floor(reserve_x * lp_amount / supply)`，`y` 同理。向下取整，取整误差留给剩余的 LP。
+/// `lp_amount` 超过 `supply` 时返回 `InsufficientLiquidity`。
+pub fn withdraw_amounts(
+    reserve_x: u64,
+    reserve_y: u64,
+    supply: u64,
+    lp_amount: u64,
+) -> Result<(u64, u64), ProgramError> {
+    if supply == 0 || lp_amount > supply {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    // lp_amount <= supply，因此结果不超过储备
+    Ok((
+        pro_rata(reserve_x, lp_amount, supply, false)?,
+        pro_rata(reserve_y, lp_amount, supply, false)?,
+    ))
+}
+
+/// 判断存入的 `(x, y)` 与比例 `reserve_x : reserve_y` (例如客户端报价时的存款数量) 的偏差
+/// 是否在 `tolerance_bps` 以内。
+///
+/// 交叉相乘比较 `x * reserve_y` 与 `y * reserve_x`，偏差相对两者中较大的一方计算。
+pub fn ratio_within_tolerance(
+    x: u64,
+    y: u64,
+    reserve_x: u64,
+    reserve_y: u64,
+    tolerance_bps: u16,
+) -> bool {
+    let lhs = (x as u128) * (reserve_y as u128);
+    let rhs = (y as u128) * (reserve_x as u128);
+    let deviation = lhs.abs_diff(rhs);
+    // 两个 u64 的乘积可能接近 u128 上限，再乘 10_000 会溢出，因此使用饱和乘法
+    deviation.saturating_mul(10_000) <= lhs.max(rhs).saturating_mul(tolerance_bps as u128)
+}
+
+/// Exact-in：扣费后的有效输入 `effective_in` 能换出的数量，
+/// `reserve_out * effective_in / (reserve_in + effective_in)`，向下取整。
+pub fn exact_in_output(
+    reserve_in: u64,
+    reserve_out: u64,
+    effective_in: u64,
+) -> Result<u64, ProgramError> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    let amount_out = (reserve_out as u128) * (effective_in as u128)
+        / (reserve_in as u128 + effective_in as u128);
+    u64::try_from(amount_out).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// Exact-in：输入 `amount_in` (含手续费) 时能换出的数量及其中的手续费。
+///
+/// * 手续费 `fee_amount = ceil(amount_in * fee / 10_000)`
+/// * 输出 `exact_in_output(reserve_in, reserve_out, amount_in - fee_amount)`，向下取整
+///
+/// 返回 `(amount_out, fee_amount)`。
+pub fn exact_in_swap(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_in: u64,
+    fee: u16,
+) -> Result<(u64, u64), ProgramError> {
+    if fee >= 10_000 {
+        return Err(AmmError::InvalidFee.into());
+    }
+    // fee < 10_000，因此 fee_amount <= amount_in
+    let fee_amount = div_ceil(amount_in as u128 * fee as u128, 10_000)? as u64;
+    let amount_out = exact_in_output(reserve_in, reserve_out, amount_in - fee_amount)?;
+    Ok((amount_out, fee_amount))
+}
+
+/// Exact-out：为了从池子拿到 `amount_out`，用户需要支付的总输入及其中的手续费。
+///
+/// * 有效输入 `a2 = ceil(reserve_in * amount_out / (reserve_out - amount_out))`
+/// * 总输入 `a = ceil(a2 * 10_000 / (10_000 - fee))`，手续费为 `a - a2`
+///
+/// 返回 `(a, fee)`。`amount_out` 必须小于 `reserve_out`。
+pub fn exact_out_input(
+    reserve_in: u64,
+    reserve_out: u64,
+    amount_out: u64,
+    fee: u16,
+) -> Result<(u64, u64), ProgramError> {
+    if amount_out == 0 || reserve_in == 0 || amount_out >= reserve_out {
+        return Err(AmmError::InsufficientLiquidity.into());
+    }
+    if fee >= 10_000 {
+        return Err(AmmError::InvalidFee.into());
+    }
+
+    let effective_in = div_ceil(
+        (reserve_in as u128) * (amount_out as u128),
+        (reserve_out - amount_out) as u128,
+    )?;
+    let gross_in = div_ceil(effective_in * 10_000, (10_000 - fee) as u128)?;
+
+    let gross_in = u64::try_from(gross_in).map_err(|_| ProgramError::ArithmeticOverflow)?;
+    let fee_amount = gross_in - effective_in as u64;
+    Ok((gross_in, fee_amount))
+}
+
+/// 交换后输出方金库至少保留交换前储备的比例 (bps)。
+/// 防止一笔交换几乎抽干某一侧储备，把价格推向无穷大导致池子不可用。
+pub const MIN_RESERVE_RETAIN_BPS: u64 = 100;
+
+/// 计算一笔交换所需的池子参数。
+///
+/// Swap、dry-run 与报价都通过 [`SwapPool::preview_swap`] 计算，保证结果完全一致。
+pub struct SwapPool {
+    /// 属于 LP 的储备 (已扣除协议手续费)
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    /// 本次交换适用的费率 (bps)
+    pub fee_bps: u16,
+    /// 单笔交换的绝对手续费上限，0 表示不限制
+    pub max_abs_fee: u64,
+}
+
+impl SwapPool {
+    /// 单笔交换最多能换出的数量：换出之后输出方储备恰好保留交换前的 `MIN_RESERVE_RETAIN_BPS`
+    pub fn max_output(&self, is_x: bool) -> u64 {
+        let reserve_out = if is_x { self.reserve_y } else { self.reserve_x };
+        let retained = (reserve_out as u128 * MIN_RESERVE_RETAIN_BPS as u128).div_ceil(10_000);
+        // MIN_RESERVE_RETAIN_BPS <= 10_000，因此 retained <= reserve_out
+        reserve_out - retained as u64
+    }
+
+    /// `allow_partial` 时 exact-out 最多能成交的输出：不超过 [`Self::max_output`]，
+    /// 并且按 exact-in 换算所需的输入不超过 `max_input` (例如 `Config.max_swap_bps` 允许的输入)。
+    /// exact-in 的输出向下取整、exact-out 的输入向上取整，因此买入这个数量的输入不会超过 `max_input`
+    pub fn max_partial_output(
+        &self,
+        is_x: bool,
+        max_input: Option<u64>,
+    ) -> Result<u64, ProgramError> {
+        let max_output = self.max_output(is_x);
+        let Some(max_input) = max_input else {
+            return Ok(max_output);
+        };
+        let (reserve_in, reserve_out) = if is_x {
+            (self.reserve_x, self.reserve_y)
+        } else {
+            (self.reserve_y, self.reserve_x)
+        };
+
+        let (output, fee) = exact_in_swap(reserve_in, reserve_out, max_input, self.fee_bps)?;
+        let output = if self.max_abs_fee > 0 && fee > self.max_abs_fee {
+            exact_in_output(reserve_in, reserve_out, max_input - self.max_abs_fee)?
+        } else {
+            output
+        };
+        Ok(output.min(max_output))
+    }
+
+    /// 计算交换结果，不检查滑点：
+    ///
+    /// * exact-in：`amount` 为输入，见 [`exact_in_swap`]
+    /// * exact-out：`amount` 为期望输出，见 [`exact_out_input`]
+    /// * 手续费超过 `max_abs_fee` 时超出部分不收取：exact-in 计入有效输入、多换出一些，
+    ///   exact-out 直接从应付输入中扣除
+    /// * 输出为 0 时返回 `ZeroOutput`，输出方储备低于交换前的 `MIN_RESERVE_RETAIN_BPS`
+    ///   时返回 `ReserveBelowMinimum`
+    pub fn preview_swap(
+        &self,
+        is_x: bool,
+        exact_out: bool,
+        amount: u64,
+    ) -> Result<SwapResult, ProgramError> {
+        let (reserve_in, reserve_out) = if is_x {
+            (self.reserve_x, self.reserve_y)
+        } else {
+            (self.reserve_y, self.reserve_x)
+        };
+
+        let (deposit, withdraw, fee) = if exact_out {
+            let (deposit, fee) = exact_out_input(reserve_in, reserve_out, amount, self.fee_bps)?;
+            (deposit, amount, fee)
+        } else {
+            let (withdraw, fee) = exact_in_swap(reserve_in, reserve_out, amount, self.fee_bps)?;
+            (amount, withdraw, fee)
+        };
+
+        let (deposit, withdraw, fee) = if self.max_abs_fee > 0 && fee > self.max_abs_fee {
+            if exact_out {
+                (
+                    deposit - (fee - self.max_abs_fee),
+                    withdraw,
+                    self.max_abs_fee,
+                )
+            } else {
+                let withdraw =
+                    exact_in_output(reserve_in, reserve_out, deposit - self.max_abs_fee)?;
+                (deposit, withdraw, self.max_abs_fee)
+            }
+        } else {
+            (deposit, withdraw, fee)
+        };
+
+        // 手续费接近 10_000 bps 时小额交换的输出可能被取整为 0，明确拒绝而不是转出 0 个代币
+        if withdraw == 0 {
+            return Err(AmmError::ZeroOutput.into());
+        }
+
+        let remaining_out = reserve_out
+            .checked_sub(withdraw)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+        if (remaining_out as u128) * 10_000 < (reserve_out as u128) * MIN_RESERVE_RETAIN_BPS as u128
+        {
+            return Err(AmmError::ReserveBelowMinimum.into());
+        }
+
+        Ok(SwapResult {
+            deposit,
+            fee,
+            withdraw,
+        })
+    }
+}
+
+/// 闪电交换回调结束后的结算校验。
+///
+/// 金额都是金库的实际余额：`vault_*_before` 为交换前读取的余额，`reserve_*` 为其中属于 LP 的储备，
+/// 两者之差是尚未提取的协议手续费，回调前后保持不变。`protocol_fee` 为本次交换新增的协议手续费
+/// (`FeeMode::Protocol` 时为交换手续费，否则为 0)，不计入 LP 储备。
+pub struct FlashSettlement {
+    pub vault_in_before: u64,
+    pub vault_out_before: u64,
+    pub reserve_in: u64,
+    pub reserve_out: u64,
+    /// 回调需要偿还的输入 (含手续费)
+    pub amount_owed: u64,
+    pub protocol_fee: u64,
+}
+
+impl FlashSettlement {
+    /// 校验回调结束后的金库余额：
+    ///
+    /// * 输入方金库至少收到 `amount_owed`，否则返回 `TransferNotReceived`
+    /// * 按 LP 储备计算的恒定乘积没有减少，否则返回 `InsufficientLiquidity`
+    pub fn check(&self, vault_in_after: u64, vault_out_after: u64) -> ProgramResult {
+        let expected_in = self
+            .vault_in_before
+            .checked_add(self.amount_owed)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if vault_in_after < expected_in {
+            return Err(AmmError::TransferNotReceived.into());
+        }
+
+        let reserve_in_after = vault_in_after
+            .checked_sub(self.vault_in_before - self.reserve_in)
+            .and_then(|r| r.checked_sub(self.protocol_fee))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let reserve_out_after = vault_out_after
+            .checked_sub(self.vault_out_before - self.reserve_out)
+            .ok_or(AmmError::InsufficientLiquidity)?;
+        if (reserve_in_after as u128) * (reserve_out_after as u128)
+            < (self.reserve_in as u128) * (self.reserve_out as u128)
+        {
+            return Err(AmmError::InsufficientLiquidity.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1_000_000 / 2_000_000 的池子以 0.3% 费率换出 10_000 Y，模拟闪电交换的结算
+    fn flash(protocol_fee_mode: bool) -> (FlashSettlement, SwapResult) {
+        let pool = SwapPool {
+            reserve_x: 1_000_000,
+            reserve_y: 2_000_000,
+            fee_bps: 30,
+            max_abs_fee: 0,
+        };
+        let result = pool.preview_swap(true, true, 10_000).unwrap();
+        // 金库中另有 500 X / 700 Y 尚未提取的协议手续费
+        let settlement = FlashSettlement {
+            vault_in_before: 1_000_500,
+            vault_out_before: 2_000_700,
+            reserve_in: 1_000_000,
+            reserve_out: 2_000_000,
+            amount_owed: result.deposit,
+            protocol_fee: if protocol_fee_mode { result.fee } else { 0 },
+        };
+        (settlement, result)
+    }
+
+    #[test]
+    fn flash_repaid_in_full_settles() {
+        for protocol_fee_mode in [false, true] {
+            let (s, result) = flash(protocol_fee_mode);
+            s.check(
+                s.vault_in_before + result.deposit,
+                s.vault_out_before - result.withdraw,
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn flash_under_repaid_is_rejected() {
+        let (s, result) = flash(false);
+        assert_eq!(
+            s.check(
+                s.vault_in_before + result.deposit - 1,
+                s.vault_out_before - result.withdraw,
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn flash_that_shrinks_k_is_rejected() {
+        // 输入足额偿还，但输出方金库比交换本身多少了一部分
+        let (s, result) = flash(false);
+        assert_eq!(
+            s.check(
+                s.vault_in_before + result.deposit,
+                s.vault_out_before - result.withdraw - 10_000,
+            ),
+            Err(AmmError::InsufficientLiquidity.into())
+        );
+    }
+
+    #[test]
+    fn withdraw_with_9_decimal_lp_is_exactly_pro_rata() {
+        // 2_000 个 9 位小数的 LP，取出其中的 1/4
+        let supply = 2_000 * 10u64.pow(9);
+        let (x, y) =
+            withdraw_amounts(8_000_000_000, 6_000_000_000_000, supply, supply / 4).unwrap();
+        assert_eq!((x, y), (2_000_000_000, 1_500_000_000_000));
+    }
+
+    #[test]
+    fn withdraw_of_one_lp_unit_does_not_drain_the_pool() {
+        let supply = 2_000 * 10u64.pow(9);
+        let (x, y) = withdraw_amounts(8_000_000_000, 6_000_000_000_000, supply, 1).unwrap();
+        // 1 / 2e12 的份额：X 向下取整为 0，Y 只有 3
+        assert_eq!((x, y), (0, 3));
+    }
+
+    #[test]
+    fn withdraw_rounds_down_and_deposit_rounds_up() {
+        assert_eq!(withdraw_amounts(10, 20, 3, 1).unwrap(), (3, 6));
+        assert_eq!(deposit_amounts(10, 20, 3, 1).unwrap(), (4, 7));
+    }
+
+    #[test]
+    fn withdraw_of_the_whole_supply_returns_the_whole_reserves() {
+        assert_eq!(withdraw_amounts(10, 20, 3, 3).unwrap(), (10, 20));
+    }
+
+    #[test]
+    fn pro_rata_rejects_empty_supply_and_excess_lp() {
+        assert!(deposit_amounts(10, 20, 0, 1).is_err());
+        assert!(withdraw_amounts(10, 20, 0, 1).is_err());
+        assert!(withdraw_amounts(10, 20, 3, 4).is_err());
+    }
+
+    #[test]
+    fn ratio_within_tolerance_compares_cross_products() {
+        // 1 : 2 对 1_000 : 1_500 的偏差为 500 / 2_000 = 2_500 bps
+        assert!(ratio_within_tolerance(1, 2, 1_000, 1_500, 2_500));
+        assert!(!ratio_within_tolerance(1, 2, 1_000, 1_500, 2_499));
+        assert!(ratio_within_tolerance(2, 3, 1_000, 1_500, 0));
+    }
+
+    #[test]
+    fn rounding_off_the_ratio_never_rejects_a_deposit() {
+        // ceil(1_500 / 1_000) = 2，取整让 Y 多出约 1/3，仍按取整后的数量收取
+        assert_eq!(
+            proportional_deposit(1_000, 1_500, 1_000, 1).unwrap(),
+            (1, 2)
+        );
+    }
+
+    #[test]
+    fn tiny_deposit_is_never_free() {
+        // LP 数量远大于储备时 1 个 LP 对应不到 1 个代币，向上取整后仍需各存入 1 个
+        assert_eq!(
+            proportional_deposit(1_000_000, 1_000_000, 10_000_000, 1).unwrap(),
+            (1, 1)
+        );
+        assert_eq!(
+            proportional_deposit(0, 1_000_000, 10_000_000, 1),
+            Err(AmmError::PoolInsolvent.into())
+        );
+    }
+
+    #[test]
+    fn deposit_overflowing_u64_is_rejected() {
+        assert_eq!(
+            deposit_amounts(u64::MAX, 1, 1, 2),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    /// 交换前后按 LP 储备计算的恒定乘积
+    fn k_after(reserve_in: u64, reserve_out: u64, effective_in: u64, out: u64) -> (u128, u128) {
+        (
+            reserve_in as u128 * reserve_out as u128,
+            (reserve_in + effective_in) as u128 * (reserve_out - out) as u128,
+        )
+    }
+
+    #[test]
+    fn exact_in_rounds_the_output_down() {
+        // 1_000 * 3 / 1_003 = 2.99...，向下取整为 2
+        assert_eq!(exact_in_swap(1_000, 1_000, 3, 0).unwrap(), (2, 0));
+    }
+
+    #[test]
+    fn exact_out_rounds_the_input_up() {
+        // 换出 2 需要 1_000 * 2 / 998 = 2.004... 的有效输入，向上取整为 3
+        assert_eq!(exact_out_input(1_000, 1_000, 2, 0).unwrap(), (3, 0));
+        // 含 0.3% 手续费的总输入 3 * 10_000 / 9_970 = 3.009...，向上取整为 4
+        assert_eq!(exact_out_input(1_000, 1_000, 2, 30).unwrap(), (4, 1));
+    }
+
+    #[test]
+    fn neither_swap_mode_decreases_k() {
+        for (reserve_in, reserve_out) in [(997, 1_009), (1_009, 997), (7, 1_000_003)] {
+            for fee in [0, 1, 30, 9_999] {
+                for amount in 1..=300 {
+                    if let Ok((out, fee_amount)) =
+                        exact_in_swap(reserve_in, reserve_out, amount, fee)
+                    {
+                        let (before, after) =
+                            k_after(reserve_in, reserve_out, amount - fee_amount, out);
+                        assert!(
+                            after >= before,
+                            "exact-in {reserve_in}/{reserve_out} {amount}"
+                        );
+                    }
+                    if let Ok((gross, fee_amount)) =
+                        exact_out_input(reserve_in, reserve_out, amount, fee)
+                    {
+                        let (before, after) =
+                            k_after(reserve_in, reserve_out, gross - fee_amount, amount);
+                        assert!(
+                            after >= before,
+                            "exact-out {reserve_in}/{reserve_out} {amount}"
+                        );
+                        // 按 exact-out 报出的输入做 exact-in 交换，换出的不会少于期望输出
+                        assert!(
+                            exact_in_swap(reserve_in, reserve_out, gross, fee)
+                                .unwrap()
+                                .0
+                                >= amount
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_fee_with_a_remainder_rounds_up() {
+        // 1_001 * 30 / 10_000 = 3.003，收取 4
+        let (out, fee) = exact_in_swap(1_000_000, 1_000_000, 1_001, 30).unwrap();
+        assert_eq!(fee, 4);
+        assert_eq!(out, exact_in_output(1_000_000, 1_000_000, 997).unwrap());
+        // 任何非零费率下，1 个最小单位的输入也要收取 1 个单位的手续费
+        assert_eq!(exact_in_swap(1_000_000, 1_000_000, 1, 1).unwrap(), (0, 1));
+        // 整除时不多收
+        assert_eq!(
+            exact_in_swap(1_000_000, 1_000_000, 10_000, 30).unwrap().1,
+            30
+        );
+    }
+
+    /// `(deposit, withdraw, fee)`
+    fn parts(result: SwapResult) -> (u64, u64, u64) {
+        (result.deposit, result.withdraw, result.fee)
+    }
+
+    fn pool(fee_bps: u16, max_abs_fee: u64) -> SwapPool {
+        SwapPool {
+            reserve_x: 1_000_000,
+            reserve_y: 2_000_000,
+            fee_bps,
+            max_abs_fee,
+        }
+    }
+
+    #[test]
+    fn partial_output_never_needs_more_than_the_input_limit() {
+        for (fee_bps, max_abs_fee) in [(0, 0), (30, 0), (30, 50), (9_999, 0)] {
+            let pool = pool(fee_bps, max_abs_fee);
+            assert_eq!(pool.max_partial_output(true, None).unwrap(), 1_980_000);
+            for max_input in [1_000, 300_000, 20_000_000] {
+                let output = pool.max_partial_output(true, Some(max_input)).unwrap();
+                assert!(output <= pool.max_output(true));
+                if let Ok(result) = pool.preview_swap(true, true, output) {
+                    assert!(result.deposit <= max_input, "{fee_bps} {max_input}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn preview_matches_the_curve_in_both_directions() {
+        let pool = pool(30, 0);
+        let (withdraw, fee) = exact_in_swap(2_000_000, 1_000_000, 5_000, 30).unwrap();
+        assert_eq!(
+            parts(pool.preview_swap(false, false, 5_000).unwrap()),
+            (5_000, withdraw, fee)
+        );
+        let (deposit, fee) = exact_out_input(1_000_000, 2_000_000, 5_000, 30).unwrap();
+        assert_eq!(
+            parts(pool.preview_swap(true, true, 5_000).unwrap()),
+            (deposit, 5_000, fee)
+        );
+    }
+
+    #[test]
+    fn preview_caps_the_fee_at_max_abs_fee() {
+        // 100_000 * 0.3% = 300，上限 100
+        let uncapped = pool(30, 0).preview_swap(true, false, 100_000).unwrap();
+        let capped = pool(30, 100).preview_swap(true, false, 100_000).unwrap();
+        assert_eq!((uncapped.fee, capped.fee), (300, 100));
+        assert_eq!(
+            capped.withdraw,
+            exact_in_output(1_000_000, 2_000_000, 100_000 - 100).unwrap()
+        );
+
+        let uncapped = pool(30, 0).preview_swap(true, true, 100_000).unwrap();
+        let capped = pool(30, 100).preview_swap(true, true, 100_000).unwrap();
+        assert_eq!(capped.fee, 100);
+        assert_eq!(capped.deposit, uncapped.deposit - (uncapped.fee - 100));
+    }
+
+    #[test]
+    fn preview_keeps_the_minimum_reserve() {
+        let pool = pool(0, 0);
+        let max = pool.max_output(true);
+        assert_eq!(max, 2_000_000 - 20_000);
+        assert!(pool.preview_swap(true, true, max).is_ok());
+        assert_eq!(
+            pool.preview_swap(true, true, max + 1).err(),
+            Some(AmmError::ReserveBelowMinimum.into())
+        );
+    }
+
+    #[test]
+    fn preview_rejects_a_zero_output() {
+        assert_eq!(
+            pool(30, 0).preview_swap(false, false, 1).err(),
+            Some(AmmError::ZeroOutput.into())
+        );
+    }
+
+    #[test]
+    fn spot_price_is_the_reserve_ratio_in_q64_64() {
+        let one = 1u128 << PRICE_FRACTION_BITS;
+        // 1_000_000 X / 2_000_000 Y：1 X = 2 Y，1 Y = 0.5 X
+        assert_eq!(spot_price(1_000_000, 2_000_000), Ok(2 * one));
+        assert_eq!(spot_price(2_000_000, 1_000_000), Ok(one / 2));
+        // 3 / 7 除不尽时向下取整
+        assert_eq!(spot_price(7, 3), Ok((3 * one) / 7));
+        assert!(spot_price(7, 3).unwrap() * 7 <= 3 * one);
+        assert_eq!(spot_price(1, u64::MAX), Ok((u64::MAX as u128) << 64));
+
+        for (base, quote) in [(0, 1), (1, 0), (0, 0)] {
+            assert_eq!(
+                spot_price(base, quote),
+                Err(AmmError::InsufficientLiquidity.into())
+            );
+        }
+    }
+
+    /// 逐行执行 `curve_vectors.txt` 中的测试向量，调用与各指令相同的函数：
+    /// Deposit 的 `first_deposit_lp` / `proportional_deposit`、Withdraw 的 `withdraw_amounts`
+    /// 以及 Swap 的 `SwapPool::preview_swap`
+    #[test]
+    fn vectors() {
+        let mut count = 0;
+        for (number, line) in include_str!("curve_vectors.txt").lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (input, expected) = line.split_once(" -> ").expect("缺少 ->");
+            let mut input = input.split_whitespace();
+            let kind = input.next().unwrap();
+            let input: Vec<u64> = input.map(|n| n.parse().unwrap()).collect();
+            let expected: Vec<u64> = expected
+                .split_whitespace()
+                .map(|n| n.parse().unwrap())
+                .collect();
+
+            let actual = match (kind, &input[..]) {
+                ("first_deposit", &[x, y]) => vec![crate::instructions::first_deposit_lp(x, y)],
+                ("deposit", &[reserve_x, reserve_y, supply, lp]) => {
+                    let (x, y) = proportional_deposit(reserve_x, reserve_y, supply, lp).unwrap();
+                    vec![x, y]
+                }
+                ("withdraw", &[reserve_x, reserve_y, supply, lp]) => {
+                    let (x, y) = withdraw_amounts(reserve_x, reserve_y, supply, lp).unwrap();
+                    vec![x, y]
+                }
+                (
+                    "swap_in" | "swap_out",
+                    &[reserve_x, reserve_y, fee, max_abs_fee, is_x, amount],
+                ) => {
+                    let pool = SwapPool {
+                        reserve_x,
+                        reserve_y,
+                        fee_bps: fee as u16,
+                        max_abs_fee,
+                    };
+                    let (deposit, withdraw, fee) = parts(
+                        pool.preview_swap(is_x == 1, kind == "swap_out", amount)
+                            .unwrap(),
+                    );
+                    vec![deposit, withdraw, fee]
+                }
+                _ => panic!("第 {} 行无法识别: {line}", number + 1),
+            };
+            assert_eq!(actual, expected, "第 {} 行: {line}", number + 1);
+            count += 1;
+        }
+        assert!(count >= 20);
+    }
+}
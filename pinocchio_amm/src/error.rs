@@ -0,0 +1,116 @@
+use constant_product_curve::CurveError;
+use pinocchio::error::ProgramError;
+
+/// AMM 自定义错误码，通过 `ProgramError::Custom` 返回给客户端。
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmError {
+    /// 池子储备不足以完成操作 (曲线返回 ZeroBalance / InsufficientBalance)
+    InsufficientLiquidity = 0,
+    /// 实际成交结果超出用户设定的滑点范围
+    SlippageExceeded = 1,
+    /// 曲线精度参数无效
+    InvalidPrecision = 2,
+    /// 手续费参数无效
+    InvalidFee = 3,
+    /// 交换后储备低于允许的最小值
+    ReserveBelowMinimum = 4,
+    /// 该操作已被管理员暂停 (Config.flags)
+    OperationPaused = 5,
+    /// 程序处于全局紧急暂停状态 (GlobalState)
+    GloballyPaused = 6,
+    /// 存入的 X / Y 比例偏离存款人报价时的比例超过允许的容差
+    RatioOutOfTolerance = 7,
+    /// 转入金库后余额没有按预期增加 (Token 程序空操作或转账被截留)
+    TransferNotReceived = 8,
+    /// 距离该用户上一次交换的 slot 数小于 Config.cooldown_slots
+    SwapCooldown = 9,
+    /// 不允许的池子状态迁移 (见 `Config::transition`)
+    InvalidStateTransition = 10,
+    /// 扣除手续费后交换输出为 0 (输入过小或手续费接近 100%)
+    ZeroOutput = 11,
+    /// 池子要求滑点保护，但请求的滑点参数为 0 (或无上限)
+    SlippageProtectionRequired = 12,
+    /// 池子仍处于创建后的交换冷静期 (见 `Config::swaps_enabled_at`)
+    SwapsNotYetEnabled = 13,
+    /// mint_x 与 mint_y 的小数位数相差超过允许值
+    DecimalsGapTooLarge = 14,
+    /// 交换路径包含多跳，当前尚不支持路由
+    UnsupportedRoute = 15,
+    /// 单笔交换的输入超过输入方储备的 max_swap_bps
+    SwapTooLarge = 16,
+    /// 存款铸造的 LP 数量为 0，用户会白白转入代币
+    ZeroLpMinted = 17,
+    /// 要求互不相同的账户传入了同一个地址
+    DuplicateAccount = 18,
+    /// LP 供应量大于 0，但某一侧储备为 0，无法按比例定价
+    PoolInsolvent = 19,
+    /// 池子启用了准入代币，但交换者没有提供持有该代币的账户
+    GateTokenRequired = 20,
+    /// 距离该用户最近一次存款还未满 lock_duration，托管的 LP 暂不能领取
+    LpLocked = 21,
+    /// 按比例存款算出某一侧的存入数量为 0，铸造的 LP 没有对应的代币
+    ZeroDepositAmount = 22,
+}
+
+impl From<AmmError> for ProgramError {
+    fn from(e: AmmError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+/// 将 `constant_product_curve` 的错误映射为具体的 `ProgramError`，
+/// 而不是统一折叠成 `ArithmeticOverflow` / `InvalidArgument`。
+///
+/// 由于 `CurveError` 和 `ProgramError` 都是外部类型，孤儿规则不允许直接实现 `From`，
+/// 因此以函数形式提供，配合 `map_err(curve_error)` 使用。
+#[inline(always)]
+pub fn curve_error(e: CurveError) -> ProgramError {
+    match e {
+        CurveError::Overflow | CurveError::Underflow => ProgramError::ArithmeticOverflow,
+        CurveError::ZeroBalance | CurveError::InsufficientBalance => {
+            AmmError::InsufficientLiquidity.into()
+        }
+        CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded.into(),
+        CurveError::InvalidPrecision => AmmError::InvalidPrecision.into(),
+        CurveError::InvalidFeeAmount => AmmError::InvalidFee.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_curve_error_maps_to_its_own_program_error() {
+        let cases = [
+            (CurveError::Overflow, ProgramError::ArithmeticOverflow),
+            (CurveError::Underflow, ProgramError::ArithmeticOverflow),
+            (
+                CurveError::ZeroBalance,
+                AmmError::InsufficientLiquidity.into(),
+            ),
+            (
+                CurveError::InsufficientBalance,
+                AmmError::InsufficientLiquidity.into(),
+            ),
+            (
+                CurveError::SlippageLimitExceeded,
+                AmmError::SlippageExceeded.into(),
+            ),
+            (
+                CurveError::InvalidPrecision,
+                AmmError::InvalidPrecision.into(),
+            ),
+            (CurveError::InvalidFeeAmount, AmmError::InvalidFee.into()),
+        ];
+        for (curve, expected) in cases {
+            assert_eq!(curve_error(curve), expected);
+        }
+        // AmmError 按判别值编码为 Custom 错误码
+        assert_eq!(
+            ProgramError::from(AmmError::InvalidFee),
+            ProgramError::Custom(3)
+        );
+    }
+}
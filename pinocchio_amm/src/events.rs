@@ -0,0 +1,76 @@
+//! 通过 `sol_log_data` 发出的事件，供索引器解析。
+//!
+//! 每条事件日志由两段数据组成：`[tag: u8]` 与事件结构体的原始字节。
+//! 事件结构体与 `ret` 中的响应一样是 `#[repr(C, packed)]`、多字节字段按小端序存放。
+
+use solana_program_log::log_data;
+
+/// 事件类型标识，作为日志的第一段数据
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventTag {
+    FeeAccrued = 0,
+    ReservesUpdated = 1,
+    ForcedDisable = 2,
+}
+
+/// 可以写入程序日志的事件结构体。
+///
+/// # Safety
+///
+/// 实现者必须是 `#[repr(C, packed)]` 的纯数据结构体 (无填充、无指针)。
+pub unsafe trait Event: Copy + Sized {
+    const TAG: EventTag;
+
+    /// 写入程序日志
+    #[inline(always)]
+    fn emit(&self) {
+        let bytes = unsafe {
+            core::slice::from_raw_parts(self as *const Self as *const u8, size_of::<Self>())
+        };
+        log_data(&[&[Self::TAG as u8], bytes]);
+    }
+}
+
+/// 一笔交换实现的手续费，以及交换之后属于 LP 的储备 (Swap)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct FeeAccrued {
+    /// 手续费只以输入代币收取，因此 `fee_x` 与 `fee_y` 至多一个非零
+    pub fee_x: u64,
+    pub fee_y: u64,
+    /// 手续费去向，见 `FeeMode`；只有 `Lp` 模式下手续费会计入 LP 储备
+    pub fee_mode: u8,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+}
+
+unsafe impl Event for FeeAccrued {
+    const TAG: EventTag = EventTag::FeeAccrued;
+}
+
+/// 交换完成后两个金库的实际余额 (Swap，需启用 `reserve-log` feature)
+///
+/// 与 `FeeAccrued` 不同，这里是金库的原始余额，包含尚未提取的协议手续费。
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct ReservesUpdated {
+    pub vault_x: u64,
+    pub vault_y: u64,
+}
+
+unsafe impl Event for ReservesUpdated {
+    const TAG: EventTag = EventTag::ReservesUpdated;
+}
+
+/// 管理员绕过迁移规则强制停用了池子 (ForceDisable)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct ForcedDisable {
+    /// 停用之前的 `AmmState`
+    pub previous_state: u8,
+}
+
+unsafe impl Event for ForcedDisable {
+    const TAG: EventTag = EventTag::ForcedDisable;
+}
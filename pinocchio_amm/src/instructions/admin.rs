@@ -0,0 +1,134 @@
+//! 只需要 `[authority, config]` 两个账户的管理指令的公共部分。
+//!
+//! 账户解析、指令数据解码与管理员校验都由 [`Admin`] 完成，各指令只在自己的指令数据上实现
+//! [`AdminUpdate`] (判别符与对 Config 的修改)，并以 `Admin<'a, 指令数据>` 的类型别名导出。
+
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+
+use crate::Config;
+
+pub struct AdminAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl AdminAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 2;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AdminAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            authority: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+/// 一条管理指令的指令数据，以及它对 Config 的修改。
+///
+/// # Safety
+///
+/// 实现者必须是 `#[repr(C, packed)]` 的纯数据结构体 (无填充、无指针)，指令数据按内存布局直接读取。
+pub unsafe trait AdminUpdate: Copy + Sized {
+    const DISCRIMINATOR: &'static u8;
+
+    /// 管理员校验通过之后修改 Config
+    fn apply(&self, config: &mut Config) -> ProgramResult;
+}
+
+pub struct Admin<'a, D> {
+    pub accounts: AdminAccounts<'a>,
+    pub instruction_data: D,
+}
+
+impl<'a, D: AdminUpdate> TryFrom<(&'a [u8], &'a [AccountView])> for Admin<'a, D> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = AdminAccounts::try_from(accounts)?;
+
+        if data.len() < size_of::<D>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let instruction_data = unsafe { (data.as_ptr() as *const D).read_unaligned() };
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a, D: AdminUpdate> Admin<'a, D> {
+    pub const DISCRIMINATOR: &'a u8 = D::DISCRIMINATOR;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        let mut config = Config::load_mut(accounts.config)?;
+        config.check_authority(accounts.authority)?;
+
+        self.instruction_data.apply(&mut config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instructions::SetBaseFee,
+        test_utils::{TestPool, empty_account},
+    };
+    use pinocchio::Address;
+
+    fn set_base_fee(pool: &mut TestPool, authority: Address, data: &[u8]) -> ProgramResult {
+        let mut authority = empty_account(authority).signer();
+        let accounts = [authority.view(), pool.config.view()];
+        SetBaseFee::try_from((data, &accounts[..]))?.process()
+    }
+
+    #[test]
+    fn authority_updates_the_config() {
+        let mut pool = TestPool::new(0, 0, 0);
+        set_base_fee(&mut pool, TestPool::AUTHORITY, &30u16.to_le_bytes()).unwrap();
+        assert_eq!(Config::load(&pool.config.view()).unwrap().fee(), 30);
+    }
+
+    #[test]
+    fn only_the_authority_can_update_the_config() {
+        let mut pool = TestPool::new(0, 0, 0);
+        assert_eq!(
+            set_base_fee(
+                &mut pool,
+                Address::new_from_array([8; 32]),
+                &30u16.to_le_bytes()
+            ),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        assert_eq!(Config::load(&pool.config.view()).unwrap().fee(), 0);
+    }
+
+    #[test]
+    fn short_instruction_data_and_missing_accounts_are_rejected() {
+        let mut pool = TestPool::new(0, 0, 0);
+        assert_eq!(
+            set_base_fee(&mut pool, TestPool::AUTHORITY, &[30]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+
+        let accounts = [pool.config.view()];
+        assert_eq!(
+            SetBaseFee::try_from((&30u16.to_le_bytes()[..], &accounts[..])).err(),
+            Some(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use pinocchio::{AccountView, Address, ProgramResult, cpi::Signer, error::ProgramError};
+use pinocchio_token::{instructions::Transfer, state::TokenAccount};
+
+use crate::{
+    Config, LP_ESCROW_SEED, UserDepositState, check_token_program, get_clock,
+    seeds::lp_authority_seeds,
+};
+
+/*
+    领取锁定期已结束的 LP。
+
+    Config.lock_duration > 0 时 Deposit 把 LP 铸造到用户的托管代币账户 ["lp_escrow", config, user]，
+    距离最近一次存款满 lock_duration 秒之后，用户通过本指令把托管账户中的全部 LP 转到自己的 LP 账户。
+    锁定期按领取时的 lock_duration 计算，管理员缩短或关闭锁定期后可以提前领取。
+    池子处于任何状态 (包括 Disabled / WithdrawOnly) 都可以领取。
+*/
+pub struct ClaimLockedLpAccounts<'a> {
+    pub user: &'a AccountView,
+    pub config: &'a AccountView,
+    /// 用户的存款记录 PDA (`["user_deposit", config, user]`)
+    pub user_deposit_state: &'a AccountView,
+    /// 用户的 LP 托管代币账户 PDA (`["lp_escrow", config, user]`)
+    pub lp_escrow: &'a AccountView,
+    /// 接收 LP 的代币账户
+    pub user_lp_ata: &'a AccountView,
+    /// LP 权限 PDA (`["lp_authority", config]`)，托管账户的 authority
+    pub lp_authority: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl ClaimLockedLpAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 7;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ClaimLockedLpAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            user: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            user_deposit_state: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            lp_escrow: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            user_lp_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            lp_authority: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            token_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+pub struct ClaimLockedLp<'a> {
+    pub accounts: ClaimLockedLpAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ClaimLockedLp<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ClaimLockedLpAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> ClaimLockedLp<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &33;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // 1. 托管的 LP 属于签名用户
+        if !accounts.user.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // 2. 校验账户都属于 (config, user)
+        let config = Config::load(accounts.config)?;
+        check_token_program(accounts.token_program)?;
+        config.check_lp_authority(accounts.config, accounts.lp_authority)?;
+        let deposited_at = UserDepositState::check_address(
+            accounts.user_deposit_state,
+            accounts.config,
+            accounts.user,
+        )?;
+        let (expected, _) = Address::find_program_address(
+            &[
+                LP_ESCROW_SEED,
+                accounts.config.address().as_ref(),
+                accounts.user.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if expected.ne(accounts.lp_escrow.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        // 3. 锁定期已结束
+        let now = get_clock()?
+            .map(|clock| clock.unix_timestamp)
+            .ok_or(ProgramError::UnsupportedSysvar)?;
+        UserDepositState::check_unlocked(deposited_at, now, config.lock_duration())?;
+
+        // 4. 转出托管账户中的全部 LP (LP 权限 PDA 签名)
+        let amount = TokenAccount::from_account_view(accounts.lp_escrow)?.amount();
+        let lp_authority_bump = config.lp_authority_bump();
+        let signer_seeds = lp_authority_seeds(accounts.config.address(), &lp_authority_bump);
+
+        Transfer {
+            from: accounts.lp_escrow,
+            to: accounts.user_lp_ata,
+            authority: accounts.lp_authority,
+            amount,
+        }
+        .invoke_signed(&[Signer::from(&signer_seeds)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        USER_DEPOSIT_SEED,
+        test_utils::{TestAccount, TestPool, empty_account, token_account},
+    };
+
+    const USER: Address = Address::new_from_array([6; 32]);
+
+    /// 已有锁定期存款记录的用户领取托管的 LP
+    fn claim(pool: &mut TestPool, mut user: TestAccount, lp_escrow: Address) -> ProgramResult {
+        let config = pool.config.view().address().clone();
+        let (record, bump) = Address::find_program_address(
+            &[USER_DEPOSIT_SEED, config.as_ref(), USER.as_ref()],
+            &crate::ID,
+        );
+        let mut data = [0u8; UserDepositState::LEN];
+        data[8] = bump;
+        let mut record = TestAccount::new(record, crate::ID, &data);
+        let lp_authority = pool.lp_authority.view().address().clone();
+        let mint_lp = pool.mint_lp.view().address().clone();
+        let mut lp_escrow = token_account(lp_escrow, &mint_lp, &lp_authority, 100).writable();
+        let mut user_lp = token_account(Address::new_from_array([10; 32]), &mint_lp, &USER, 0);
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let accounts = [
+            user.view(),
+            pool.config.view(),
+            record.view(),
+            lp_escrow.view(),
+            user_lp.view(),
+            pool.lp_authority.view(),
+            token_program.view(),
+        ];
+        ClaimLockedLp::try_from((&[][..], &accounts[..]))?.process()
+    }
+
+    fn escrow_address(pool: &mut TestPool) -> Address {
+        let config = pool.config.view().address().clone();
+        Address::find_program_address(
+            &[LP_ESCROW_SEED, config.as_ref(), USER.as_ref()],
+            &crate::ID,
+        )
+        .0
+    }
+
+    #[test]
+    fn claim_checks_the_signer_and_the_escrow_address() {
+        let mut pool = TestPool::new(0, 0, 0);
+        let escrow = escrow_address(&mut pool);
+        assert_eq!(
+            claim(&mut pool, empty_account(USER), escrow.clone()),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        assert_eq!(
+            claim(
+                &mut pool,
+                empty_account(USER).signer(),
+                Address::new_from_array([11; 32])
+            ),
+            Err(ProgramError::InvalidSeeds)
+        );
+        // 主机上没有 Clock sysvar：账户校验全部通过之后在读取时间处失败
+        assert_eq!(
+            claim(&mut pool, empty_account(USER).signer(), escrow),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    error::ProgramError,
+    sysvars::{Sysvar, rent::Rent},
+};
+
+use crate::{AmmState, Config, create_mint_lp, verify_mint_lp_address};
+
+/// 修复只完成了一半的 `Initialize`：Config 已创建且处于 Initialized 状态，
+/// 但 Mint LP 账户尚未创建 (例如 CU 耗尽导致后半段失败)，此时补齐剩余步骤。
+pub struct CompleteInitializeAccounts<'a> {
+    pub initializer: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub config: &'a AccountView,
+}
+
+impl CompleteInitializeAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 3;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CompleteInitializeAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            initializer: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            mint_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct CompleteInitializeInstructionData {
+    pub lp_bump: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [u8]> for CompleteInitializeInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { *(data.as_ptr() as *const Self) })
+    }
+}
+
+pub struct CompleteInitialize<'a> {
+    pub accounts: CompleteInitializeAccounts<'a>,
+    pub instruction_data: CompleteInitializeInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CompleteInitialize<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = CompleteInitializeAccounts::try_from(accounts)?;
+        let instruction_data = CompleteInitializeInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> CompleteInitialize<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let data = &self.instruction_data;
+
+        // 1. Config 必须已由本程序创建并完成初始化
+        let lp_authority = {
+            let config = Config::load(accounts.config)?;
+            if config.state() != AmmState::Initialized as u8 {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            config.lp_authority_address(accounts.config.address())?
+        };
+
+        // 2. Mint LP 必须还是空账户，已创建的池子不需要也不允许重复执行
+        if accounts.mint_lp.data_len() != 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        verify_mint_lp_address(accounts.config, accounts.mint_lp, &data.lp_bump)?;
+
+        // 3. 补齐 Initialize 剩余的步骤
+        let rent = Rent::get()?;
+        create_mint_lp(
+            accounts.initializer,
+            accounts.mint_lp,
+            accounts.config,
+            &lp_authority,
+            &data.lp_bump,
+            &rent,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestPool, empty_account};
+
+    /// 只完成了前半段的池子：Config 已初始化，mint_lp 还是空账户
+    fn half_initialized() -> TestPool {
+        let mut pool = TestPool::new(0, 0, 0);
+        let mint_lp = pool.mint_lp.view().address().clone();
+        pool.mint_lp = empty_account(mint_lp).writable();
+        pool
+    }
+
+    fn complete(pool: &mut TestPool, lp_bump: u8) -> ProgramResult {
+        let mut initializer = empty_account(pinocchio::Address::new_from_array([6; 32])).signer();
+        let accounts = [initializer.view(), pool.mint_lp.view(), pool.config.view()];
+        CompleteInitialize::try_from((&[lp_bump][..], &accounts[..]))?.process()
+    }
+
+    fn lp_bump(pool: &mut TestPool) -> u8 {
+        Config::load(&pool.config.view()).unwrap().mint_lp_bump()[0]
+    }
+
+    #[test]
+    fn only_a_missing_lp_mint_is_completed() {
+        // 已经创建了 LP mint 的池子不能重复执行
+        let mut pool = TestPool::new(0, 0, 0);
+        let bump = lp_bump(&mut pool);
+        assert_eq!(
+            complete(&mut pool, bump),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+
+        let mut pool = half_initialized();
+        assert_eq!(
+            complete(&mut pool, bump.wrapping_sub(1)),
+            Err(ProgramError::InvalidSeeds)
+        );
+        // 主机上没有 Rent sysvar：校验全部通过之后在读取 Rent 处失败
+        assert_eq!(
+            complete(&mut pool, bump),
+            Err(ProgramError::UnsupportedSysvar)
+        );
+
+        pool.update_config(|config| config.set_state(AmmState::Uninitialized as u8).unwrap());
+        assert_eq!(
+            complete(&mut pool, bump),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
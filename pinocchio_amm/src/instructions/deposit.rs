@@ -1,16 +1,22 @@
-use constant_product_curve::ConstantProduct;
 use pinocchio::{
-    AccountView, ProgramResult,
-    cpi::{Seed, Signer},
+    AccountView, Address, ProgramResult,
+    cpi::Signer,
     error::ProgramError,
-    sysvars::{Sysvar, clock::Clock},
+    sysvars::{Sysvar, rent::Rent},
 };
 use pinocchio_token::{
-    instructions::{MintTo, Transfer},
+    instructions::{InitializeAccount3, MintTo, Transfer},
     state::{Mint, TokenAccount},
 };
 
-use crate::Config;
+use crate::{
+    AmmError, Config, FLAG_DEPOSITS_PAUSED, GlobalState, LP_ESCROW_SEED, USER_DEPOSIT_SEED,
+    UserDepositState, check_distinct_vaults, check_expiration, check_token_program,
+    create_program_account,
+    curve::{proportional_deposit, ratio_within_tolerance},
+    get_clock,
+    seeds::{lp_authority_seeds, lp_escrow_seeds, user_deposit_seeds},
+};
 
 pub struct DepositAccounts<'a> {
     pub user: &'a AccountView,
@@ -22,14 +28,43 @@ pub struct DepositAccounts<'a> {
     pub user_lp_ata: &'a AccountView,
     pub config: &'a AccountView,
     pub token_program: &'a AccountView,
+    /// 全局暂停单例 PDA (`["global"]`)，尚未创建时传入其地址即可
+    pub global_state: &'a AccountView,
+    /// LP mint 权限 PDA (`["lp_authority", config]`)
+    pub lp_authority: &'a AccountView,
+    /// 可选账户，仅在对应功能启用时才需要提供：
+    /// 0. `user_deposit_state`、`lp_escrow` 与 `system_program`：用户的存款记录 PDA
+    ///    (`["user_deposit", config, user]`)、LP 托管代币账户 PDA (`["lp_escrow", config, user]`)
+    ///    及用于首次创建它们的 System Program，`Config.lock_duration > 0` 时需要；
+    ///    首次创建时由 `user` 出资。此时 LP 铸造到托管账户而不是 `user_lp_ata`，
+    ///    锁定期结束后通过 `ClaimLockedLp` 领取
+    pub remaining: &'a [AccountView],
+}
+
+impl DepositAccounts<'_> {
+    /// 指令所需的固定账户数量，之后的账户按功能需要作为可选账户 (见 `remaining`)。
+    pub const LEN: usize = 11;
+
+    /// 一次性校验与池子绑定的账户，返回遇到的第一个错误：
+    /// Token 程序、LP 权限 PDA、LP mint、金库币种。金库的 authority 在读取储备时校验。
+    pub fn validate(&self, config: &Config) -> ProgramResult {
+        check_token_program(self.token_program)?;
+        config.check_lp_authority(self.config, self.lp_authority)?;
+        config.check_mint_lp(self.config, self.mint_lp)?;
+        config.check_vault_mints(self.vault_x, self.vault_y)
+    }
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
         let mut account_iter = accounts.iter();
-        Ok(Self {
+        let accounts = Self {
             user: account_iter
                 .next()
                 .ok_or(ProgramError::NotEnoughAccountKeys)?,
@@ -57,17 +92,33 @@ impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
             token_program: account_iter
                 .next()
                 .ok_or(ProgramError::NotEnoughAccountKeys)?,
-        })
+            global_state: account_iter
+                .next()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?,
+            lp_authority: account_iter
+                .next()
+                .ok_or(ProgramError::NotEnoughAccountKeys)?,
+            remaining: account_iter.as_slice(),
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        Ok(accounts)
     }
 }
 
 #[repr(C, packed)]
 #[derive(Clone, Copy)]
 pub struct DepositInstructionData {
+    /// 按比例计算存款数量所用的 LP 数量；实际铸造的 LP 会再扣除 `Config::deposit_fee`
     pub amount: u64,
     pub max_x: u64,
     pub max_y: u64,
+    /// 订单的最后有效时间 (unix 秒，包含边界)：与 Clock sysvar 的 `unix_timestamp` 比较，
+    /// `unix_timestamp == expiration` 时仍可执行，大于时拒绝；`clock-free` 构建中被忽略
     pub expiration: i64,
+    /// 按比例存款时，实际存入的 X / Y 比例允许偏离报价比例 `max_x : max_y` 的范围 (bps)，
+    /// 用于在池子价格变化时拒绝存款；0 表示不检查，首次存款也不检查。
+    /// 按储备比例取整带来的偏差总是被接受
+    pub ratio_tolerance_bps: u16,
 }
 
 impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
@@ -75,13 +126,19 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
 
     fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
         if data.len() < size_of::<Self>() {
-            // 32 = 8 + 8 + 8 + 8
+            // 34 = 8 + 8 + 8 + 8 + 2
             return Err(ProgramError::InvalidInstructionData);
         }
         Ok(unsafe { *(data.as_ptr() as *const Self) })
     }
 }
 
+/// 首次注入流动性时应铸造的 LP 数量：x 与 y 的几何平均数 `sqrt(x * y)`（向下取整）。
+#[inline(always)]
+pub fn first_deposit_lp(x: u64, y: u64) -> u64 {
+    ((x as u128) * (y as u128)).isqrt() as u64
+}
+
 pub struct Deposit<'a> {
     pub accounts: DepositAccounts<'a>,
     pub instruction_data: DepositInstructionData,
@@ -110,10 +167,11 @@ impl<'a> Deposit<'a> {
         let data = &self.instruction_data;
 
         // 1. 过期检查
-        let clock = Clock::get()?;
-        if clock.unix_timestamp > data.expiration {
-            return Err(ProgramError::InvalidArgument); // 订单已过期
-        }
+        let clock = get_clock()?;
+        check_expiration(clock.as_ref(), data.expiration)?;
+
+        // 全局紧急暂停时拒绝存款
+        GlobalState::check_not_paused(accounts.global_state)?;
 
         // 2. 加载 Config 并验证状态
         let config = Config::load(accounts.config)?;
@@ -121,32 +179,71 @@ impl<'a> Deposit<'a> {
             // AmmState::Initialized
             return Err(ProgramError::InvalidAccountData);
         }
+        if config.is_paused(FLAG_DEPOSITS_PAUSED) {
+            return Err(AmmError::OperationPaused.into());
+        }
+        accounts.validate(&config)?;
+        config.check_slippage_max(data.max_x)?;
+        config.check_slippage_max(data.max_y)?;
+
+        // LP 锁定期：LP 铸造到用户的托管账户并记录本次存款时间，锁定期结束前无法领取
+        let lock_duration = config.lock_duration();
+        let lp_lock = if lock_duration > 0 {
+            let now = clock
+                .as_ref()
+                .map(|clock| clock.unix_timestamp)
+                .ok_or(ProgramError::UnsupportedSysvar)?;
+            let mut remaining = accounts.remaining.iter();
+            let user_deposit_state = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let lp_escrow = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let _system_program = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let record_bump =
+                check_deposit_record(user_deposit_state, accounts.config, accounts.user)?;
+            let escrow_bump = check_lp_escrow(lp_escrow, accounts, &config)?;
+            Some((user_deposit_state, record_bump, lp_escrow, escrow_bump, now))
+        } else {
+            None
+        };
 
         // 3. 反序列化代币账户信息 (使用 Pinocchio-token 提供的 unchecked 方法提升性能)
         let mint_lp = unsafe { Mint::from_account_view_unchecked(accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_y)? };
+        let (vault_x_before, vault_y_before) = unsafe {
+            Config::read_reserves_unchecked(accounts.config, accounts.vault_x, accounts.vault_y)?
+        };
+        let (reserve_x, reserve_y) = config.lp_reserves(vault_x_before, vault_y_before)?;
 
         // 4. 计算存款金额 (x, y)
-        let (x, y) = if mint_lp.supply() == 0 {
+        // data.amount 是扣除存款手续费之前的 LP 数量：首次存款时它必须等于 sqrt(max_x * max_y)，
+        // 注入数量过小会被取整为 0，此时拒绝而不是收下代币却不发 LP
+        if data.amount == 0 {
+            return Err(AmmError::ZeroLpMinted.into());
+        }
+        let first_deposit = mint_lp.supply() == 0;
+        let (x, y) = if first_deposit {
+            // 初始流动性：LP 数量必须等于 sqrt(max_x * max_y)，
+            // 否则 LP 份额与实际注入的价值脱钩
+            if data.amount != first_deposit_lp(data.max_x, data.max_y) {
+                return Err(ProgramError::InvalidArgument);
+            }
             // 初始流动性：使用用户指定的 max 值
             (data.max_x, data.max_y)
         } else {
-            // 后续流动性：基于比例计算
-            let amounts = ConstantProduct::xy_deposit_amounts_from_l(
-                vault_x.amount(),
-                vault_y.amount(),
-                mint_lp.supply(),
-                data.amount,
-                6, // 假设 LP 小数位为 6
-            )
-            .map_err(|_| ProgramError::ArithmeticOverflow)?;
-            (amounts.x, amounts.y)
+            // 后续流动性：按 LP 份额向上取整计算
+            proportional_deposit(reserve_x, reserve_y, mint_lp.supply(), data.amount)?
         };
 
         // 5. 滑点保护检查
         if x > data.max_x || y > data.max_y {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::SlippageExceeded.into());
+        }
+        if data.ratio_tolerance_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if !first_deposit
+            && data.ratio_tolerance_bps > 0
+            && !ratio_within_tolerance(x, y, data.max_x, data.max_y, data.ratio_tolerance_bps)
+        {
+            return Err(AmmError::RatioOutOfTolerance.into());
         }
 
         // 6. 执行代币转移 (用户 -> 金库)
@@ -166,30 +263,439 @@ impl<'a> Deposit<'a> {
         }
         .invoke()?;
 
-        // 7. 签署并执行 MintTo (Config PDA -> 用户)
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x = config.mint_x(); // Returns &Pubkey
-        let mint_y = config.mint_y(); // Returns &Pubkey
-        let bump = config.config_bump(); // Returns [u8; 1]
-
-        // 2. Now create the seeds using references to those stable variables
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x.as_ref()),
-            Seed::from(mint_y.as_ref()),
-            Seed::from(&bump), // Reference to the local variable 'bump'
-        ];
-        let signer = Signer::from(&config_seeds);
+        Config::check_vault_received(accounts.vault_x, vault_x_before, x)?;
+        Config::check_vault_received(accounts.vault_y, vault_y_before, y)?;
+
+        // 7. 扣除存款手续费：用户按 data.amount 足额转入代币，但只铸造扣费后的 LP，
+        //    差额对应的代币留在池子里归现有 LP。首次存款没有其他 LP，不收取
+        let lp_amount = if first_deposit {
+            data.amount
+        } else {
+            config.lp_after_deposit_fee(data.amount)
+        };
+        if lp_amount == 0 {
+            return Err(AmmError::ZeroLpMinted.into());
+        }
+
+        // 8. 签署并执行 MintTo (LP 权限 PDA -> 用户，锁定期启用时 -> 用户的托管账户)
+        let lp_destination = match lp_lock {
+            Some((_, _, lp_escrow, escrow_bump, _)) => {
+                if let Some(bump) = escrow_bump {
+                    create_lp_escrow(lp_escrow, accounts, bump)?;
+                }
+                lp_escrow
+            }
+            None => accounts.user_lp_ata,
+        };
+
+        let lp_authority_bump = config.lp_authority_bump();
+        let signer_seeds = lp_authority_seeds(accounts.config.address(), &lp_authority_bump);
+        let signer = Signer::from(&signer_seeds);
 
         MintTo {
             mint: accounts.mint_lp,
-            account: accounts.user_lp_ata,
-            mint_authority: accounts.config,
-            amount: data.amount,
+            account: lp_destination,
+            mint_authority: accounts.lp_authority,
+            amount: lp_amount,
         }
         .invoke_signed(&[signer])?;
 
+        if let Some((user_deposit_state, record_bump, _, _, now)) = lp_lock {
+            record_deposit_time(
+                user_deposit_state,
+                accounts.config,
+                accounts.user,
+                record_bump,
+                now,
+            )?;
+        }
+
         Ok(())
     }
 }
+
+/// 校验用户的存款记录 PDA。尚未创建时返回创建所需的 bump，已存在时必须属于 `(config, user)`
+fn check_deposit_record(
+    user_deposit_state: &AccountView,
+    config: &AccountView,
+    user: &AccountView,
+) -> Result<Option<u8>, ProgramError> {
+    if user_deposit_state.data_len() == 0 {
+        let (expected, bump) = Address::find_program_address(
+            &[
+                USER_DEPOSIT_SEED,
+                config.address().as_ref(),
+                user.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if expected.ne(user_deposit_state.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        return Ok(Some(bump));
+    }
+
+    UserDepositState::check_address(user_deposit_state, config, user)?;
+    Ok(None)
+}
+
+/// 校验用户的 LP 托管账户 PDA。尚未创建时返回创建所需的 bump，
+/// 已存在时必须是 LP mint 的代币账户 (地址是本程序的 PDA，只能由 `Deposit` 创建)
+fn check_lp_escrow(
+    lp_escrow: &AccountView,
+    accounts: &DepositAccounts,
+    config: &Config,
+) -> Result<Option<u8>, ProgramError> {
+    let (expected, bump) = Address::find_program_address(
+        &[
+            LP_ESCROW_SEED,
+            accounts.config.address().as_ref(),
+            accounts.user.address().as_ref(),
+        ],
+        &crate::ID,
+    );
+    if expected.ne(lp_escrow.address()) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if lp_escrow.data_len() == 0 {
+        return Ok(Some(bump));
+    }
+
+    let escrow = TokenAccount::from_account_view(lp_escrow)?;
+    let lp_authority = config.lp_authority_address(accounts.config.address())?;
+    if escrow.mint().ne(accounts.mint_lp.address()) || escrow.owner().ne(&lp_authority) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(None)
+}
+
+/// 由用户出资创建 LP 托管代币账户，authority 为 LP 权限 PDA
+fn create_lp_escrow(
+    lp_escrow: &AccountView,
+    accounts: &DepositAccounts,
+    bump: u8,
+) -> ProgramResult {
+    let bump = [bump];
+    let seeds = lp_escrow_seeds(accounts.config.address(), accounts.user.address(), &bump);
+    // 地址可预测，可能已被他人预先转入 lamports
+    create_program_account(
+        lp_escrow,
+        accounts.user,
+        TokenAccount::LEN,
+        Rent::get()?.try_minimum_balance(TokenAccount::LEN)?,
+        &pinocchio_token::ID,
+        &[Signer::from(&seeds)],
+    )?;
+    InitializeAccount3 {
+        account: lp_escrow,
+        mint: accounts.mint_lp,
+        owner: accounts.lp_authority.address(),
+    }
+    .invoke()
+}
+
+/// 记录本次存款的时间；首次存款时先由用户出资创建记录 PDA。
+/// 每次存款都会刷新时间，追加存款会让托管账户中尚未领取的全部 LP 重新进入锁定期
+fn record_deposit_time(
+    user_deposit_state: &AccountView,
+    config: &AccountView,
+    user: &AccountView,
+    create_bump: Option<u8>,
+    now: i64,
+) -> ProgramResult {
+    if let Some(bump) = create_bump {
+        let bump = [bump];
+        let seeds = user_deposit_seeds(config.address(), user.address(), &bump);
+        // 地址可预测，可能已被他人预先转入 lamports
+        create_program_account(
+            user_deposit_state,
+            user,
+            UserDepositState::LEN,
+            Rent::get()?.try_minimum_balance(UserDepositState::LEN)?,
+            &crate::ID,
+            &[Signer::from(&seeds)],
+        )?;
+
+        UserDepositState::load_mut(user_deposit_state)?.set_bump(bump);
+    }
+
+    UserDepositState::load_mut(user_deposit_state)?.set_deposited_at(now);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "clock-free"))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, empty_account, token_account};
+
+    /// 以 `user` 的身份向 `pool` 存款
+    fn deposit(pool: &mut TestPool, data: DepositInstructionData) -> ProgramResult {
+        let user = Address::new_from_array([6; 32]);
+        let mut user_account = empty_account(user.clone()).signer().writable();
+        let mut user_x = token_account(Address::new_from_array([7; 32]), &pool.mint_x, &user, 0);
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &user, 0);
+        let mut user_lp = token_account(Address::new_from_array([10; 32]), &user, &user, 0);
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let accounts = [
+            user_account.view(),
+            pool.mint_lp.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+            user_x.view(),
+            user_y.view(),
+            user_lp.view(),
+            pool.config.view(),
+            token_program.view(),
+            pool.global_state.view(),
+            pool.lp_authority.view(),
+        ];
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                &data as *const DepositInstructionData as *const u8,
+                size_of::<DepositInstructionData>(),
+            )
+        };
+        Deposit::try_from((data, &accounts[..]))?.process()
+    }
+
+    fn data(amount: u64, max_x: u64, max_y: u64) -> DepositInstructionData {
+        DepositInstructionData {
+            amount,
+            max_x,
+            max_y,
+            expiration: i64::MAX,
+            ratio_tolerance_bps: 0,
+        }
+    }
+
+    #[test]
+    fn deposit_above_max_is_rejected_before_any_transfer() {
+        // 1_000 LP 对应 10_000 X / 20_000 Y，存入 100 LP 需要 1_000 X / 2_000 Y
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 1_999)),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            deposit(&mut pool, data(100, 999, 2_000)),
+            Err(AmmError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn deposit_within_max_passes_the_slippage_check() {
+        // 主机上的 Transfer 是空操作，金库余额不变，因此在转账后的到账校验处失败，
+        // 说明滑点检查与之前的所有校验都已通过
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn ratio_tolerance_compares_against_the_quote() {
+        // 1_000 LP 对应 1_000 X / 1_500 Y：1 LP 按取整需要 1 X / 2 Y，偏离储备比例约 1/3
+        let mut pool = TestPool::new(1_000, 1_500, 1_000);
+        // 0 (默认) 不检查比例
+        assert_eq!(
+            deposit(&mut pool, data(1, 1, 2)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        // 报价 10 X / 20 Y 与实际的 1 : 2 一致，容差再小也接受
+        let quoted = |max_x, max_y, ratio_tolerance_bps| DepositInstructionData {
+            ratio_tolerance_bps,
+            ..data(1, max_x, max_y)
+        };
+        assert_eq!(
+            deposit(&mut pool, quoted(10, 20, 1)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        // 报价 10 X / 15 Y 时实际比例偏离 2_500 bps
+        assert_eq!(
+            deposit(&mut pool, quoted(10, 15, 2_500)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            deposit(&mut pool, quoted(10, 15, 2_499)),
+            Err(AmmError::RatioOutOfTolerance.into())
+        );
+        assert_eq!(
+            deposit(&mut pool, quoted(10, 15, 10_001)),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn deposit_requires_the_pool_lp_mint() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.mint_lp = crate::test_utils::mint_account(Address::new_from_array([11; 32]), 1_000, 6);
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn first_deposit_must_mint_the_geometric_mean() {
+        let mut pool = TestPool::new(0, 0, 0);
+        // sqrt(10_000 * 40_000) = 20_000
+        for amount in [19_999, 20_001] {
+            assert_eq!(
+                deposit(&mut pool, data(amount, 10_000, 40_000)),
+                Err(ProgramError::InvalidArgument)
+            );
+        }
+        assert_eq!(
+            deposit(&mut pool, data(20_000, 10_000, 40_000)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn paused_deposits_are_rejected() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.update_config(|config| config.set_flags(crate::FLAG_DEPOSITS_PAUSED).unwrap());
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(AmmError::OperationPaused.into())
+        );
+        // 其他操作的暂停位不影响存款
+        pool.update_config(|config| {
+            config
+                .set_flags(crate::FLAG_SWAPS_PAUSED | crate::FLAG_WITHDRAWALS_PAUSED)
+                .unwrap()
+        });
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn global_pause_blocks_deposits() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.global_state = crate::test_utils::global_state_account(true);
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(AmmError::GloballyPaused.into())
+        );
+        pool.global_state = crate::test_utils::global_state_account(false);
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn vaults_must_be_owned_by_the_config() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.vault_y =
+            token_account([5; 32].into(), &pool.mint_y, &[11; 32].into(), 20_000).writable();
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
+
+    #[test]
+    fn unbounded_maximums_are_rejected_under_the_slippage_policy() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.update_config(|config| config.set_require_slippage(true));
+        assert_eq!(
+            deposit(&mut pool, data(100, u64::MAX, 2_000)),
+            Err(AmmError::SlippageProtectionRequired.into())
+        );
+        pool.update_config(|config| config.set_require_slippage(false));
+        assert_eq!(
+            deposit(&mut pool, data(100, u64::MAX, u64::MAX)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn deposits_do_not_wait_for_swaps_to_open() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.update_config(|config| config.set_swaps_enabled_at(i64::MAX));
+        assert_eq!(
+            deposit(&mut pool, data(100, 1_000, 2_000)),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    /// 以 `pool` 的账户与 `token_program` 运行 `DepositAccounts::validate`
+    fn validate(pool: &mut TestPool, token_program: Address) -> ProgramResult {
+        let mut other = empty_account([11; 32].into());
+        let mut token_program = TestAccount::new(token_program, Address::default(), &[]);
+        let (other, config, token_program) =
+            (other.view(), pool.config.view(), token_program.view());
+        let (mint_lp, vault_x, vault_y) = (
+            pool.mint_lp.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+        );
+        let (global_state, lp_authority) = (pool.global_state.view(), pool.lp_authority.view());
+        let accounts = DepositAccounts {
+            user: &other,
+            mint_lp: &mint_lp,
+            vault_x: &vault_x,
+            vault_y: &vault_y,
+            user_x_ata: &other,
+            user_y_ata: &other,
+            user_lp_ata: &other,
+            config: &config,
+            token_program: &token_program,
+            global_state: &global_state,
+            lp_authority: &lp_authority,
+            remaining: &[],
+        };
+        accounts.validate(&*Config::load(&config)?)
+    }
+
+    #[test]
+    fn validate_reports_each_mismatched_account() {
+        assert_eq!(
+            validate(&mut TestPool::new(0, 0, 0), pinocchio_token::ID),
+            Ok(())
+        );
+
+        assert_eq!(
+            validate(&mut TestPool::new(0, 0, 0), [12; 32].into()),
+            Err(ProgramError::IncorrectProgramId)
+        );
+
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.lp_authority = empty_account([12; 32].into());
+        assert_eq!(
+            validate(&mut pool, pinocchio_token::ID),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.mint_lp = crate::test_utils::mint_account([12; 32].into(), 0, crate::LP_DECIMALS);
+        assert_eq!(
+            validate(&mut pool, pinocchio_token::ID),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        let mut pool = TestPool::new(0, 0, 0);
+        let config = pool.config.view().address().clone();
+        pool.vault_x = token_account([4; 32].into(), &pool.mint_y, &config, 0);
+        assert_eq!(
+            validate(&mut pool, pinocchio_token::ID),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn a_pool_with_lp_but_an_empty_reserve_is_insolvent() {
+        for (reserve_x, reserve_y) in [(0, 20_000), (10_000, 0), (0, 0)] {
+            let mut pool = TestPool::new(reserve_x, reserve_y, 1_000);
+            assert_eq!(
+                deposit(&mut pool, data(100, u64::MAX, u64::MAX)),
+                Err(AmmError::PoolInsolvent.into()),
+                "reserves {reserve_x} / {reserve_y}"
+            );
+        }
+    }
+}
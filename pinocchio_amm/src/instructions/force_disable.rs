@@ -0,0 +1,69 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    events::{Event, ForcedDisable},
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员紧急停用池子：不经过 `Config::transition` 的迁移规则，从任何已初始化的状态直接进入
+/// `Disabled`，并以 `ForcedDisable` 事件记录停用前的状态。
+pub type ForceDisable<'a> = Admin<'a, ForceDisableInstructionData>;
+
+/// 没有参数
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct ForceDisableInstructionData {}
+
+unsafe impl AdminUpdate for ForceDisableInstructionData {
+    const DISCRIMINATOR: &'static u8 = &32;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        let previous_state = config.force_disable()?;
+
+        ForcedDisable { previous_state }.emit();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AmmState,
+        test_utils::{TestPool, empty_account},
+    };
+    use pinocchio::Address;
+    use pinocchio::error::ProgramError;
+
+    fn force_disable(pool: &mut TestPool, authority: Address) -> ProgramResult {
+        let mut authority = empty_account(authority).signer();
+        let accounts = [authority.view(), pool.config.view()];
+        ForceDisable::try_from((&[][..], &accounts[..]))?.process()
+    }
+
+    #[test]
+    fn authority_can_force_disable_a_withdraw_only_pool() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| config.transition(AmmState::WithdrawOnly).unwrap());
+        force_disable(&mut pool, TestPool::AUTHORITY).unwrap();
+        assert_eq!(
+            Config::load(&pool.config.view()).unwrap().state(),
+            AmmState::Disabled as u8
+        );
+    }
+
+    #[test]
+    fn only_the_authority_can_force_disable() {
+        let mut pool = TestPool::new(0, 0, 0);
+        assert_eq!(
+            force_disable(&mut pool, Address::new_from_array([8; 32])),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        assert_eq!(
+            Config::load(&pool.config.view()).unwrap().state(),
+            AmmState::Initialized as u8
+        );
+    }
+}
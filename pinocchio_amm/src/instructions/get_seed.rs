@@ -0,0 +1,128 @@
+use pinocchio::{AccountView, Address, ProgramResult, error::ProgramError};
+
+use crate::{
+    CONFIG_SEED, Config,
+    ret::{ReturnData, SeedInfo},
+};
+
+/*
+    只读查询：返回推导 Config PDA 所需的全部参数 `seed`、`mint_x`、`mint_y` 与 bump。
+
+    客户端丢失 `seed` 时可以只凭 Config 地址取回，结果格式见 `ret::SeedInfo`。
+    返回前会用这些参数重新推导一次地址，确认与传入的 Config 一致。
+*/
+pub struct GetSeedAccounts<'a> {
+    pub config: &'a AccountView,
+}
+
+impl GetSeedAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 1;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for GetSeedAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+pub struct GetSeed<'a> {
+    pub accounts: GetSeedAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for GetSeed<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = GetSeedAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> GetSeed<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &22;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.seed_info()?.set_return_data();
+
+        Ok(())
+    }
+
+    fn seed_info(&self) -> Result<SeedInfo, ProgramError> {
+        let accounts = &self.accounts;
+
+        let config = Config::load(accounts.config)?;
+        let seed = config.seed();
+        let bump = config.config_bump();
+
+        let expected = Address::create_program_address(
+            &[
+                CONFIG_SEED,
+                &seed.to_le_bytes(),
+                config.mint_x().as_ref(),
+                config.mint_y().as_ref(),
+                &bump,
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        if expected.ne(accounts.config.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        Ok(SeedInfo {
+            seed,
+            mint_x: config.mint_x().to_bytes(),
+            mint_y: config.mint_y().to_bytes(),
+            config_bump: bump[0],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::NewPool;
+
+    #[test]
+    fn returned_seeds_rederive_the_config() {
+        let mut pool = NewPool::new([2; 32], [3; 32]);
+        pool.initialize().unwrap();
+
+        let accounts = [pool.config.view()];
+        let info = GetSeed::try_from((&[][..], &accounts[..]))
+            .unwrap()
+            .seed_info()
+            .unwrap();
+        let (seed, mint_x, mint_y) = (info.seed, info.mint_x, info.mint_y);
+        assert_eq!((seed, mint_x, mint_y), (7, [2; 32], [3; 32]));
+        let (expected, bump) = Address::find_program_address(
+            &[CONFIG_SEED, &seed.to_le_bytes(), &mint_x, &mint_y],
+            &crate::ID,
+        );
+        assert_eq!((&expected, bump), (accounts[0].address(), info.config_bump));
+    }
+
+    #[test]
+    fn a_config_whose_fields_do_not_derive_it_is_rejected() {
+        // TestPool 的 Config 地址不是按其字段推导的 PDA
+        let mut pool = crate::test_utils::TestPool::new(0, 0, 0);
+        let accounts = [pool.config.view()];
+        assert_eq!(
+            GetSeed::try_from((&[][..], &accounts[..]))
+                .unwrap()
+                .process(),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+}
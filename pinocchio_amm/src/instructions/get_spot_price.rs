@@ -0,0 +1,110 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+
+use crate::{
+    Config, check_distinct_vaults,
+    curve::spot_price,
+    ret::{ReturnData, SpotPrice},
+};
+
+/*
+    只读查询：根据当前储备返回双向边际价格 (不含手续费)，用于行情展示。
+
+    结果以 Q64.64 定点数写入 return data，格式见 `ret::SpotPrice`。
+*/
+pub struct GetSpotPriceAccounts<'a> {
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+}
+
+impl GetSpotPriceAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 3;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for GetSpotPriceAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        let accounts = Self {
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        Ok(accounts)
+    }
+}
+
+pub struct GetSpotPrice<'a> {
+    pub accounts: GetSpotPriceAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for GetSpotPrice<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = GetSpotPriceAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> GetSpotPrice<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // 1. 加载 Config 并校验金库归属
+        let config = Config::load(accounts.config)?;
+        let (vault_x_amount, vault_y_amount) =
+            Config::read_reserves(accounts.config, accounts.vault_x, accounts.vault_y)?;
+
+        // 2. 只使用属于 LP 的储备计算价格
+        let (reserve_x, reserve_y) = config.lp_reserves(vault_x_amount, vault_y_amount)?;
+
+        SpotPrice {
+            x_in_y: spot_price(reserve_x, reserve_y)?,
+            y_in_x: spot_price(reserve_y, reserve_x)?,
+        }
+        .set_return_data();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AmmError, test_utils::TestPool};
+
+    fn get_spot_price(pool: &mut TestPool) -> ProgramResult {
+        let accounts = [pool.config.view(), pool.vault_x.view(), pool.vault_y.view()];
+        GetSpotPrice::try_from((&[][..], &accounts[..]))?.process()
+    }
+
+    #[test]
+    fn an_empty_side_has_no_price() {
+        assert_eq!(
+            get_spot_price(&mut TestPool::new(1_000, 2_000, 1_000)),
+            Ok(())
+        );
+        assert_eq!(
+            get_spot_price(&mut TestPool::new(0, 2_000, 1_000)),
+            Err(AmmError::InsufficientLiquidity.into())
+        );
+        // 金库里只剩协议手续费时，LP 储备同样为空
+        let mut pool = TestPool::new(1_000, 2_000, 1_000);
+        pool.update_config(|config| config.set_protocol_fees(1_000, 0));
+        assert_eq!(
+            get_spot_price(&mut pool),
+            Err(AmmError::InsufficientLiquidity.into())
+        );
+    }
+}
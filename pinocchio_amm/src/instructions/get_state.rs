@@ -0,0 +1,102 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+
+use crate::{
+    Config,
+    ret::{ReturnData, StateInfo},
+};
+
+/*
+    只读查询：只返回 Config 的 `state` 字节 (`AmmState`)，格式见 `ret::StateInfo`。
+
+    供监控程序轻量轮询池子状态，不需要拉取整个 Config 账户。
+*/
+pub struct GetStateAccounts<'a> {
+    pub config: &'a AccountView,
+}
+
+impl GetStateAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 1;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for GetStateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+pub struct GetState<'a> {
+    pub accounts: GetStateAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for GetState<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = GetStateAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> GetState<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &25;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.state_info()?.set_return_data();
+
+        Ok(())
+    }
+
+    fn state_info(&self) -> Result<StateInfo, ProgramError> {
+        let config = Config::load(self.accounts.config)?;
+        Ok(StateInfo {
+            state: config.state(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AmmState, test_utils::TestPool};
+
+    fn state_info(pool: &mut TestPool) -> Result<u8, ProgramError> {
+        let accounts = [pool.config.view()];
+        GetState::try_from((&[][..], &accounts[..]))?
+            .state_info()
+            .map(|info| info.state)
+    }
+
+    #[test]
+    fn returns_the_stored_state_byte() {
+        let mut pool = TestPool::new(0, 0, 0);
+        assert_eq!(state_info(&mut pool), Ok(AmmState::Initialized as u8));
+
+        pool.update_config(|config| config.transition(AmmState::WithdrawOnly).unwrap());
+        assert_eq!(state_info(&mut pool), Ok(AmmState::WithdrawOnly as u8));
+        pool.update_config(|config| config.transition(AmmState::Disabled).unwrap());
+        assert_eq!(state_info(&mut pool), Ok(AmmState::Disabled as u8));
+        pool.update_config(|config| config.set_state(AmmState::Uninitialized as u8).unwrap());
+        assert_eq!(state_info(&mut pool), Ok(AmmState::Uninitialized as u8));
+
+        // 不是 Config 的账户 (长度不符) 报错，而不是返回某个状态
+        let mut vault = TestPool::new(0, 0, 0).vault_x;
+        let accounts = [vault.view()];
+        assert_eq!(
+            GetState::try_from((&[][..], &accounts[..]))
+                .unwrap()
+                .process(),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
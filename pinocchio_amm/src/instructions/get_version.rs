@@ -0,0 +1,62 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+
+use crate::{
+    Config, PROGRAM_VERSION,
+    ret::{ReturnData, VersionInfo},
+};
+
+/*
+    只读查询：返回编译期确定的程序版本与 Config 账户长度，格式见 `ret::VersionInfo`。
+
+    不需要任何账户，运维工具可以借此在链上识别已部署的程序版本，
+    并判断现有 Config 账户是否需要迁移。
+*/
+pub struct GetVersion;
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for GetVersion {
+    type Error = ProgramError;
+
+    fn try_from((_data, _accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+impl GetVersion {
+    pub const DISCRIMINATOR: &'static u8 = &27;
+
+    pub fn process(&mut self) -> ProgramResult {
+        self.version_info().set_return_data();
+
+        Ok(())
+    }
+
+    fn version_info(&self) -> VersionInfo {
+        VersionInfo {
+            program_version: PROGRAM_VERSION,
+            config_len: Config::LEN as u16,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_cargo_version_and_config_len() {
+        // 不需要账户与数据，多传的也被忽略
+        let info = GetVersion::try_from((&[1, 2][..], &[][..]))
+            .unwrap()
+            .version_info();
+        let version: Vec<u32> = env!("CARGO_PKG_VERSION")
+            .split(['.', '-'])
+            .take(3)
+            .map(|part| part.parse().unwrap())
+            .collect();
+        assert_eq!(
+            { info.program_version },
+            version[0] << 16 | version[1] << 8 | version[2]
+        );
+        assert_eq!({ info.config_len } as usize, Config::LEN);
+    }
+}
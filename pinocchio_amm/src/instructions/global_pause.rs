@@ -0,0 +1,178 @@
+use pinocchio::{
+    AccountView, Address, ProgramResult,
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{Sysvar, rent::Rent},
+};
+
+use crate::{GLOBAL_ADMIN, GLOBAL_SEED, GlobalState, create_program_account, seeds::global_seeds};
+
+/*
+    程序级紧急开关：由编译期常量 GLOBAL_ADMIN 签名，写入单例 PDA ["global"]。
+
+    暂停期间所有池子的 Deposit / Swap 都会被拒绝，Withdraw 不受影响，保证 LP 随时可以撤出资金。
+    首次调用时由管理员出资创建该 PDA，地址上已有他人预先转入的 lamports 也不影响创建。
+*/
+pub struct GlobalPauseAccounts<'a> {
+    pub admin: &'a AccountView,
+    pub global_state: &'a AccountView,
+    pub system_program: &'a AccountView,
+}
+
+impl GlobalPauseAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 3;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for GlobalPauseAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            admin: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            global_state: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            system_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct GlobalPauseInstructionData {
+    /// 0: 恢复运行，其他值: 暂停
+    pub paused: u8,
+}
+
+impl<'a> TryFrom<&'a [u8]> for GlobalPauseInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { *(data.as_ptr() as *const Self) })
+    }
+}
+
+pub struct GlobalPause<'a> {
+    pub accounts: GlobalPauseAccounts<'a>,
+    pub instruction_data: GlobalPauseInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for GlobalPause<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = GlobalPauseAccounts::try_from(accounts)?;
+        let instruction_data = GlobalPauseInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> GlobalPause<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let data = &self.instruction_data;
+
+        // 1. 只有编译期指定的管理员可以操作
+        if !accounts.admin.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if accounts.admin.address().ne(&GLOBAL_ADMIN) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+
+        // 2. 首次调用时创建单例 PDA
+        if accounts.global_state.data_len() == 0 {
+            let (expected, bump) = Address::find_program_address(&[GLOBAL_SEED], &crate::ID);
+            if expected.ne(accounts.global_state.address()) {
+                return Err(ProgramError::InvalidSeeds);
+            }
+
+            let bump = [bump];
+            let signer_seeds = global_seeds(&bump);
+            // 地址固定且公开，可能已被他人预先转入 lamports
+            create_program_account(
+                accounts.global_state,
+                accounts.admin,
+                GlobalState::LEN,
+                Rent::get()?.try_minimum_balance(GlobalState::LEN)?,
+                &crate::ID,
+                &[Signer::from(&signer_seeds)],
+            )?;
+
+            GlobalState::load_mut(accounts.global_state)?.set_bump(bump);
+        }
+
+        // 3. 写入暂停状态
+        GlobalState::load_mut(accounts.global_state)?.set_paused(data.paused != 0);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, empty_account, global_state_account};
+
+    fn global_pause(
+        admin: &mut TestAccount,
+        global_state: &mut TestAccount,
+        paused: u8,
+    ) -> ProgramResult {
+        let mut system_program = empty_account(pinocchio_system::ID);
+        let accounts = [admin.view(), global_state.view(), system_program.view()];
+        GlobalPause::try_from((&[paused][..], &accounts[..]))?.process()
+    }
+
+    #[test]
+    fn only_the_global_admin_toggles_the_pause() {
+        let mut global_state = global_state_account(false);
+        let mut unsigned = empty_account(GLOBAL_ADMIN);
+        assert_eq!(
+            global_pause(&mut unsigned, &mut global_state, 1),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        let mut other = empty_account([9; 32].into()).signer();
+        assert_eq!(
+            global_pause(&mut other, &mut global_state, 1),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        assert_eq!(global_state.data()[0], 0);
+
+        let mut admin = empty_account(GLOBAL_ADMIN).signer().writable();
+        assert_eq!(global_pause(&mut admin, &mut global_state, 2), Ok(()));
+        assert_eq!(
+            GlobalState::check_not_paused(&global_state.view()),
+            Err(crate::AmmError::GloballyPaused.into())
+        );
+        assert_eq!(global_pause(&mut admin, &mut global_state, 0), Ok(()));
+        assert_eq!(GlobalState::check_not_paused(&global_state.view()), Ok(()));
+    }
+
+    #[test]
+    fn a_missing_singleton_must_be_the_global_pda() {
+        let mut admin = empty_account(GLOBAL_ADMIN).signer().writable();
+        let mut impostor = empty_account([8; 32].into()).writable();
+        assert_eq!(
+            global_pause(&mut admin, &mut impostor, 1),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            GlobalState::check_not_paused(&impostor.view()),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+}
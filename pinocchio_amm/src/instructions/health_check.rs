@@ -0,0 +1,196 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+use crate::{
+    AmmState, Config, check_distinct_vaults,
+    ret::{HealthReport, HealthStatus, ReturnData},
+};
+
+/*
+    只读监控：检查 Config、金库与 LP mint 之间的一致性。
+
+    发现不一致时不会报错，而是把 `HealthStatus` 写入 return data，方便监控程序轮询；
+    只有账户本身无法解析 (例如不是 Config / 代币账户) 时才返回错误。
+    储备不单独记录在 Config 中，因此以金库余额扣除协议手续费作为储备。
+*/
+pub struct HealthCheckAccounts<'a> {
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+}
+
+impl HealthCheckAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 4;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for HealthCheckAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        let accounts = Self {
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            mint_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        Ok(accounts)
+    }
+}
+
+pub struct HealthCheck<'a> {
+    pub accounts: HealthCheckAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for HealthCheck<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = HealthCheckAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> HealthCheck<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &17;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let status = self.check()?;
+        HealthReport {
+            status: status as u8,
+        }
+        .set_return_data();
+
+        Ok(())
+    }
+
+    fn check(&self) -> Result<HealthStatus, ProgramError> {
+        let accounts = &self.accounts;
+        let config = Config::load(accounts.config)?;
+        let mint_lp = Mint::from_account_view(accounts.mint_lp)?;
+        let vault_x = TokenAccount::from_account_view(accounts.vault_x)?;
+        let vault_y = TokenAccount::from_account_view(accounts.vault_y)?;
+
+        // 1. 状态必须合法且已初始化
+        match AmmState::try_from(config.state()) {
+            Ok(AmmState::Uninitialized) | Err(_) => return Ok(HealthStatus::InvalidState),
+            Ok(_) => {}
+        }
+
+        // 2. 金库归 Config PDA 所有且币种正确
+        if Config::check_vaults(accounts.config, &vault_x, &vault_y).is_err()
+            || vault_x.mint().ne(config.mint_x())
+            || vault_y.mint().ne(config.mint_y())
+        {
+            return Ok(HealthStatus::VaultMismatch);
+        }
+
+        // 3. 只有 LP 权限 PDA 能铸造 LP
+        let lp_authority = config.lp_authority_address(accounts.config.address())?;
+        if mint_lp.mint_authority() != Some(&lp_authority) {
+            return Ok(HealthStatus::LpMintMismatch);
+        }
+
+        // 4. 协议手续费不能超过金库余额
+        let Ok((reserve_x, reserve_y)) = config.lp_reserves(vault_x.amount(), vault_y.amount())
+        else {
+            return Ok(HealthStatus::ProtocolFeesExceedVaults);
+        };
+
+        // 5. 有 LP 流通时两侧储备都必须大于 0
+        if mint_lp.supply() > 0 && (reserve_x == 0 || reserve_y == 0) {
+            return Ok(HealthStatus::SupplyWithoutReserves);
+        }
+
+        Ok(HealthStatus::Healthy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, token_account};
+    use pinocchio::Address;
+
+    /// mint_authority 为 LP 权限 PDA 的 LP mint，即 Initialize 创建的样子
+    fn lp_mint(pool: &mut TestPool, authority: &Address, supply: u64) -> TestAccount {
+        let mut data = [0u8; size_of::<Mint>()];
+        data[0] = 1;
+        data[4..36].copy_from_slice(authority.as_ref());
+        data[36..44].copy_from_slice(&supply.to_le_bytes());
+        data[44] = crate::LP_DECIMALS;
+        data[45] = 1;
+        TestAccount::new(
+            pool.mint_lp.view().address().clone(),
+            pinocchio_token::ID,
+            &data,
+        )
+    }
+
+    fn healthy(reserve_x: u64, reserve_y: u64, lp_supply: u64) -> TestPool {
+        let mut pool = TestPool::new(reserve_x, reserve_y, lp_supply);
+        let lp_authority = pool.lp_authority.view().address().clone();
+        pool.mint_lp = lp_mint(&mut pool, &lp_authority, lp_supply);
+        pool
+    }
+
+    fn health_check(pool: &mut TestPool) -> Result<HealthStatus, ProgramError> {
+        let accounts = [
+            pool.config.view(),
+            pool.mint_lp.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+        ];
+        HealthCheck::try_from((&[][..], &accounts[..]))?.check()
+    }
+
+    #[test]
+    fn a_consistent_pool_is_healthy() {
+        assert_eq!(
+            health_check(&mut healthy(1_000, 2_000, 1_000)),
+            Ok(HealthStatus::Healthy)
+        );
+        // 没有流通 LP 时空池子也是健康的
+        assert_eq!(
+            health_check(&mut healthy(0, 0, 0)),
+            Ok(HealthStatus::Healthy)
+        );
+    }
+
+    #[test]
+    fn each_inconsistency_reports_its_own_status() {
+        let mut pool = healthy(1_000, 2_000, 1_000);
+        pool.update_config(|config| config.set_state(AmmState::Uninitialized as u8).unwrap());
+        assert_eq!(health_check(&mut pool), Ok(HealthStatus::InvalidState));
+
+        let mut pool = healthy(1_000, 2_000, 1_000);
+        pool.vault_y = token_account([5; 32].into(), &pool.mint_y, &[11; 32].into(), 2_000);
+        assert_eq!(health_check(&mut pool), Ok(HealthStatus::VaultMismatch));
+
+        let mut pool = healthy(1_000, 2_000, 1_000);
+        pool.mint_lp = lp_mint(&mut pool, &[11; 32].into(), 1_000);
+        assert_eq!(health_check(&mut pool), Ok(HealthStatus::LpMintMismatch));
+
+        let mut pool = healthy(1_000, 2_000, 1_000);
+        pool.update_config(|config| config.set_protocol_fees(1_001, 0));
+        assert_eq!(
+            health_check(&mut pool),
+            Ok(HealthStatus::ProtocolFeesExceedVaults)
+        );
+
+        let mut pool = healthy(1_000, 2_000, 1_000);
+        pool.update_config(|config| config.set_protocol_fees(1_000, 0));
+        assert_eq!(
+            health_check(&mut pool),
+            Ok(HealthStatus::SupplyWithoutReserves)
+        );
+    }
+}
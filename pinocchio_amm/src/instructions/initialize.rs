@@ -1,28 +1,74 @@
 use std::mem::MaybeUninit;
 
 use pinocchio::{
-    AccountView, ProgramResult,
-    cpi::{Seed, Signer},
+    AccountView, Address, ProgramResult,
+    cpi::Signer,
     error::ProgramError,
     sysvars::{Sysvar, rent::Rent},
 };
 use pinocchio_system::instructions::CreateAccount;
 use pinocchio_token::{instructions::InitializeMint2, state::Mint};
 
-use crate::Config;
+use crate::{
+    AmmError, CONFIG_SEED, Config, LP_AUTHORITY_SEED, LP_DECIMALS, MINT_LP_SEED, get_clock,
+    seeds::{ConfigSeeds, mint_lp_seeds},
+};
+#[cfg(feature = "creation-fee")]
+use crate::{POOL_CREATION_FEE, TREASURY};
+#[cfg(feature = "creation-fee")]
+use pinocchio_system::instructions::Transfer;
+
+/// `authority` 按原样写入：全零 (或省略) 表示放弃管理权，池子永久无管理员。
+pub const AUTHORITY_MODE_AS_GIVEN: u8 = 0;
+/// `authority` 全零 (或省略) 时以 initializer 作为管理员。
+pub const AUTHORITY_MODE_DEFAULT_INITIALIZER: u8 = 1;
+
+/// 未设置 `allow_high_fee` 时 Initialize 接受的最高费率 (bps)，用于拦截误填的费率。
+/// Config 本身允许的上限 (9999 bps) 不受影响。
+pub const MAX_INITIAL_FEE: u16 = 1_000;
+
+/// `max_decimals_diff` 取该值 (默认的 0) 时不检查 mint_x / mint_y 的小数位数差，也不需要传入两个 mint 账户。
+pub const DECIMALS_DIFF_UNCHECKED: u8 = 0;
 
 /// 初始化 Config 账户，并存储 AMM 正常运行所需的所有信息。
-/// 创建 mint_lp 铸币账户，并将 mint_authority 分配给 config 账户。
+/// 创建 mint_lp 铸币账户，并将 mint_authority 分配给独立的 LP 权限 PDA (`["lp_authority", config]`)。
 pub struct InitializeAccounts<'a> {
     pub initializer: &'a AccountView,
     pub mint_lp: &'a AccountView,
     pub config: &'a AccountView,
+    /// 建池费收款账户，必须是 `TREASURY`
+    #[cfg(feature = "creation-fee")]
+    pub treasury: &'a AccountView,
+    /// 可选账户：`max_decimals_diff` 启用时依次传入 mint_x 与 mint_y
+    pub remaining: &'a [AccountView],
+}
+
+impl InitializeAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 3 + cfg!(feature = "creation-fee") as usize;
+
+    /// initializer、config 与 mint_lp 必须是三个不同的账户：
+    /// 否则 CreateAccount 会从待创建的账户自身出资，或把两个 PDA 创建到同一个地址上
+    #[inline(always)]
+    pub fn check_distinct(&self) -> Result<(), ProgramError> {
+        let initializer = self.initializer.address();
+        let config = self.config.address();
+        let mint_lp = self.mint_lp.address();
+        if initializer.eq(config) || initializer.eq(mint_lp) || config.eq(mint_lp) {
+            return Err(AmmError::DuplicateAccount.into());
+        }
+        Ok(())
+    }
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
         let mut accounts_iter = accounts.iter();
 
         let initializer = accounts_iter
@@ -34,16 +80,24 @@ impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
         let config = accounts_iter
             .next()
             .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        #[cfg(feature = "creation-fee")]
+        let treasury = accounts_iter
+            .next()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
 
         Ok(Self {
             initializer,
             mint_lp,
             config,
+            #[cfg(feature = "creation-fee")]
+            treasury,
+            remaining: accounts_iter.as_slice(),
         })
     }
 }
 
 #[repr(C, packed)]
+#[derive(Clone, Copy)]
 pub struct InitializeInstructionData {
     pub seed: u64,
     pub fee: u16,
@@ -51,9 +105,29 @@ pub struct InitializeInstructionData {
     pub mint_y: [u8; 32],
     pub config_bump: [u8; 1],
     pub lp_bump: [u8; 1],
+    /// authority 缺省时的处理方式，见 `AUTHORITY_MODE_*`
+    pub authority_mode: u8,
+    /// 非 0 时允许 `fee` 超过 `MAX_INITIAL_FEE`
+    pub allow_high_fee: u8,
+    /// 创建后禁止交换的时长 (秒)，期间仍可存取款，用于防止开盘抢跑；0 表示立即开放
+    pub swap_delay: u32,
+    /// mint_x 与 mint_y 的小数位数最多相差多少，0 (`DECIMALS_DIFF_UNCHECKED`) 表示不检查，
+    /// 因此最严格的限制是相差 1，不能要求两者完全相同。
+    /// 小数位数相差过大的交易对价格缩放极端，小额交换容易被取整为 0
+    pub max_decimals_diff: u8,
     pub authority: [u8; 32],
 }
 
+// 省略 authority 的短格式依赖 authority 是最后一个字段：前缀原样拷贝，末尾 32 字节补零。
+// 新字段必须加在 authority 之前，否则短格式会把新字段当成 authority 的一部分清零
+const _: () = {
+    use core::mem::offset_of;
+    assert!(
+        offset_of!(InitializeInstructionData, authority) + size_of::<[u8; 32]>()
+            == size_of::<InitializeInstructionData>()
+    );
+};
+
 impl TryFrom<&[u8]> for InitializeInstructionData {
     type Error = ProgramError;
 
@@ -109,20 +183,110 @@ impl<'a> Initialize<'a> {
     pub const DISCRIMINATOR: &'a u8 = &0;
 
     pub fn process(&mut self) -> ProgramResult {
+        let rent = Rent::get()?;
+        self.process_with_rent(&rent)
+    }
+
+    /// 使用调用方读取的 Rent 执行初始化，主机测试由此绕过 Rent sysvar
+    pub(crate) fn process_with_rent(&mut self, rent: &Rent) -> ProgramResult {
         let instruction_data = &self.instruction_data;
         let accounts = &self.accounts;
-        let rent = Rent::get()?;
+        let seed_binding = instruction_data.seed.to_le_bytes();
 
-        // --- 1. 创建 Config 账户 ---
+        // InitializeMany / InitializeWithLiquidity 直接构造 InitializeAccounts，
+        // 因此重复账户检查放在这里而不是 try_from 中，所有入口都会经过
+        accounts.check_distinct()?;
+
+        // --- 0. 校验调用方提供的 bump ---
+        // bump 直接作为签名种子使用，错误的 bump 会让 CreateAccount 报出难以理解的错误，
+        // 因此在创建账户之前先确认它们确实能推导出传入的 config / mint_lp 地址。
+        let expected_config = Address::create_program_address(
+            &[
+                CONFIG_SEED,
+                &seed_binding,
+                &instruction_data.mint_x,
+                &instruction_data.mint_y,
+                &instruction_data.config_bump,
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        if expected_config.ne(accounts.config.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        verify_mint_lp_address(accounts.config, accounts.mint_lp, &instruction_data.lp_bump)?;
+
+        if instruction_data.fee > MAX_INITIAL_FEE && instruction_data.allow_high_fee == 0 {
+            return Err(AmmError::InvalidFee.into());
+        }
+
+        #[cfg(feature = "strict-mints")]
+        if instruction_data.mint_x == [0u8; 32] || instruction_data.mint_y == [0u8; 32] {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        check_not_own_lp_mint(
+            accounts.mint_lp.address(),
+            &instruction_data.mint_x,
+            &instruction_data.mint_y,
+        )?;
+
+        check_decimals_diff(
+            accounts.remaining,
+            &instruction_data.mint_x,
+            &instruction_data.mint_y,
+            instruction_data.max_decimals_diff,
+        )?;
+
+        // 省略 authority 时是放弃管理权还是交给 initializer，必须由调用方显式选择
+        let authority: Address = match instruction_data.authority_mode {
+            AUTHORITY_MODE_DEFAULT_INITIALIZER if instruction_data.authority == [0u8; 32] => {
+                accounts.initializer.address().clone()
+            }
+            AUTHORITY_MODE_AS_GIVEN | AUTHORITY_MODE_DEFAULT_INITIALIZER => {
+                instruction_data.authority.into()
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        // initializer 需要支付 Config 与 Mint LP 两个账户的租金，余额不足时提前给出明确的错误，
+        // 而不是让 CreateAccount CPI 失败
         let config_lamports = rent.try_minimum_balance(Config::LEN)?; // 动态计算
-        let seed_binding = instruction_data.seed.to_le_bytes();
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(seed_binding.as_ref()),
-            Seed::from(instruction_data.mint_x.as_ref()),
-            Seed::from(instruction_data.mint_y.as_ref()),
-            Seed::from(&instruction_data.config_bump),
-        ];
+        let mint_lamports = rent.try_minimum_balance(size_of::<Mint>())?;
+        let required_lamports = config_lamports
+            .checked_add(mint_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        #[cfg(feature = "creation-fee")]
+        let required_lamports = required_lamports
+            .checked_add(POOL_CREATION_FEE)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if accounts.initializer.lamports() < required_lamports {
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        // 建池费在创建任何账户之前转给协议金库
+        #[cfg(feature = "creation-fee")]
+        {
+            if accounts.treasury.address().ne(&TREASURY) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            Transfer {
+                from: accounts.initializer,
+                to: accounts.treasury,
+                lamports: POOL_CREATION_FEE,
+            }
+            .invoke()?;
+        }
+
+        // --- 1. 创建 Config 账户 ---
+        let config_seeds = ConfigSeeds::new(
+            instruction_data.seed,
+            instruction_data.mint_x.into(),
+            instruction_data.mint_y.into(),
+            instruction_data.config_bump,
+        );
+        let config_seeds = config_seeds.as_seeds();
         let config_signer = Signer::from(&config_seeds);
         // 计算 Config 账户所需的租金空间 (使用我们在 state.rs 定义的 LEN)
         CreateAccount {
@@ -134,46 +298,453 @@ impl<'a> Initialize<'a> {
         }
         .invoke_signed(&[config_signer])?;
 
+        // LP mint 的铸币权限交给独立的 PDA，与金库的 authority (Config PDA) 分离
+        let (lp_authority, lp_authority_bump) = Address::find_program_address(
+            &[LP_AUTHORITY_SEED, accounts.config.address().as_ref()],
+            &crate::ID,
+        );
+
         // --- 2. 初始化 Config 数据 ---
         // 使用之前实现的 load_mut_unchecked 来获取可变引用
         let config_account = unsafe { Config::load_mut_unchecked(accounts.config)? };
         config_account.set_inner(
             instruction_data.seed,
-            instruction_data.authority.into(), // 将 [u8;32] 转为 Pubkey
+            authority,
             instruction_data.mint_x.into(),
             instruction_data.mint_y.into(),
             instruction_data.fee,
             instruction_data.config_bump,
+            [lp_authority_bump],
+            instruction_data.lp_bump,
         )?;
+        if instruction_data.swap_delay > 0 {
+            let now = get_clock()?
+                .ok_or(ProgramError::UnsupportedSysvar)?
+                .unix_timestamp;
+            config_account.set_swaps_enabled_at(
+                now.checked_add(instruction_data.swap_delay as i64)
+                    .ok_or(ProgramError::ArithmeticOverflow)?,
+            );
+        }
 
-        // --- 3. 创建 Mint LP 账户 ---
-        let mint_space = size_of::<Mint>();
-        let mint_lamports = rent.try_minimum_balance(mint_space)?;
-        let mint_lp_seeds = [
-            Seed::from(b"mint_lp"),
-            Seed::from(accounts.config.address().as_ref()),
-            Seed::from(&instruction_data.lp_bump),
-        ];
+        // --- 3. 创建并初始化 Mint LP 账户 ---
+        create_mint_lp(
+            accounts.initializer,
+            accounts.mint_lp,
+            accounts.config,
+            &lp_authority,
+            &instruction_data.lp_bump,
+            rent,
+        )?;
 
-        // Mint 账户固定大小为 82 字节
-        CreateAccount {
-            from: accounts.initializer,
-            to: accounts.mint_lp,
-            lamports: mint_lamports, // 同样应根据 Rent 计算
-            space: mint_space as u64,
-            owner: &pinocchio_token::ID, // 注意所有者是 Token Program
+        Ok(())
+    }
+}
+
+/// X / Y 可以是其他池子的 LP 代币 (嵌套 LP)，但不能是本池子自己的 LP mint。
+/// mint_lp 由 Config 地址推导、Config 地址又由两个 mint 推导，正常情况下不会相等，
+/// 这里仍显式拒绝，不依赖该推导关系
+fn check_not_own_lp_mint(mint_lp: &Address, mint_x: &[u8; 32], mint_y: &[u8; 32]) -> ProgramResult {
+    if mint_lp.as_ref() == mint_x.as_slice() || mint_lp.as_ref() == mint_y.as_slice() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+/// 校验 `mints` 中的前两个账户就是 mint_x / mint_y，且两者的小数位数最多相差 `max_diff`
+fn check_decimals_diff(
+    mints: &[AccountView],
+    mint_x: &[u8; 32],
+    mint_y: &[u8; 32],
+    max_diff: u8,
+) -> ProgramResult {
+    if max_diff == DECIMALS_DIFF_UNCHECKED {
+        return Ok(());
+    }
+    let [mint_x_view, mint_y_view, ..] = mints else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if mint_x_view.address().as_ref() != mint_x.as_slice()
+        || mint_y_view.address().as_ref() != mint_y.as_slice()
+    {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let decimals_x = Mint::from_account_view(mint_x_view)?.decimals();
+    let decimals_y = Mint::from_account_view(mint_y_view)?.decimals();
+    if decimals_x.abs_diff(decimals_y) > max_diff {
+        return Err(AmmError::DecimalsGapTooLarge.into());
+    }
+    Ok(())
+}
+
+/// 校验 `lp_bump` 能由 `["mint_lp", config]` 推导出传入的 `mint_lp` 地址
+pub(crate) fn verify_mint_lp_address(
+    config: &AccountView,
+    mint_lp: &AccountView,
+    lp_bump: &[u8; 1],
+) -> ProgramResult {
+    let expected_mint_lp = Address::create_program_address(
+        &[MINT_LP_SEED, config.address().as_ref(), lp_bump],
+        &crate::ID,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+    if expected_mint_lp.ne(mint_lp.address()) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+/// 创建 Mint LP 账户并将 mint_authority 交给 LP 权限 PDA。
+/// 由 `Initialize` 和 `CompleteInitialize` 共用。
+pub(crate) fn create_mint_lp(
+    initializer: &AccountView,
+    mint_lp: &AccountView,
+    config: &AccountView,
+    mint_authority: &Address,
+    lp_bump: &[u8; 1],
+    rent: &Rent,
+) -> ProgramResult {
+    // mint_lp 必须是尚未创建的空账户 (归 System Program 所有且没有数据)，
+    // 否则 CreateAccount / InitializeMint2 会给出难以定位的错误
+    if !mint_lp.owned_by(&pinocchio_system::ID) || mint_lp.data_len() != 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let mint_space = size_of::<Mint>();
+    let mint_lamports = rent.try_minimum_balance(mint_space)?;
+    let signer_seeds = mint_lp_seeds(config.address(), lp_bump);
+
+    // Mint 账户固定大小为 82 字节
+    CreateAccount {
+        from: initializer,
+        to: mint_lp,
+        lamports: mint_lamports, // 同样应根据 Rent 计算
+        space: mint_space as u64,
+        owner: &pinocchio_token::ID, // 注意所有者是 Token Program
+    }
+    .invoke_signed(&[Signer::from(&signer_seeds)])?;
+
+    // 初始化 Mint LP (设置 Mint Authority)
+    InitializeMint2 {
+        mint: mint_lp,
+        decimals: LP_DECIMALS, // 通常 LP 代币使用 6 位小数
+        mint_authority,        // 权限交给 LP 权限 PDA
+        freeze_authority: None,
+    }
+    .invoke()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{NewPool, TestAccount, mint_account, rent};
+
+    fn pool() -> NewPool {
+        NewPool::new([2; 32], [3; 32])
+    }
+
+    #[test]
+    fn bumps_must_derive_the_passed_addresses() {
+        let mut pool = pool();
+        let config_bump = pool.data.config_bump[0];
+        pool.data.config_bump = [config_bump.wrapping_sub(1)];
+        assert_eq!(pool.initialize(), Err(ProgramError::InvalidSeeds));
+
+        let mut pool = self::pool();
+        let lp_bump = pool.data.lp_bump[0];
+        pool.data.lp_bump = [lp_bump.wrapping_sub(1)];
+        assert_eq!(pool.initialize(), Err(ProgramError::InvalidSeeds));
+
+        let mut pool = self::pool();
+        assert_eq!(pool.initialize(), Ok(()));
+        assert_eq!(
+            pool.config(|config| (config.config_bump(), config.mint_lp_bump())),
+            ([config_bump], [lp_bump])
+        );
+    }
+
+    /// 每个字段都是不同的非 0 值，authority 为 `[0xAA; 32]`
+    fn long_form() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+        data.extend_from_slice(&30u16.to_le_bytes());
+        data.extend_from_slice(&[2; 32]);
+        data.extend_from_slice(&[3; 32]);
+        data.extend_from_slice(&[254, 253, 1, 1]);
+        data.extend_from_slice(&3_600u32.to_le_bytes());
+        data.push(9);
+        data.extend_from_slice(&[0xAA; 32]);
+        assert_eq!(data.len(), size_of::<InitializeInstructionData>());
+        data
+    }
+
+    fn assert_fields(data: &InitializeInstructionData) {
+        let InitializeInstructionData {
+            seed,
+            fee,
+            mint_x,
+            mint_y,
+            config_bump,
+            lp_bump,
+            authority_mode,
+            allow_high_fee,
+            swap_delay,
+            max_decimals_diff,
+            ..
+        } = *data;
+        assert_eq!((seed, fee), (0x0102_0304_0506_0708, 30));
+        assert_eq!((mint_x, mint_y), ([2; 32], [3; 32]));
+        assert_eq!((config_bump, lp_bump), ([254], [253]));
+        assert_eq!((authority_mode, allow_high_fee), (1, 1));
+        assert_eq!((swap_delay, max_decimals_diff), (3_600, 9));
+    }
+
+    #[test]
+    fn long_form_keeps_the_authority() {
+        let data = InitializeInstructionData::try_from(&long_form()[..]).unwrap();
+        assert_fields(&data);
+        assert_eq!({ data.authority }, [0xAA; 32]);
+    }
+
+    #[test]
+    fn short_form_zero_fills_the_authority() {
+        let long = long_form();
+        let data = InitializeInstructionData::try_from(&long[..long.len() - 32]).unwrap();
+        assert_fields(&data);
+        assert_eq!({ data.authority }, [0; 32]);
+    }
+
+    fn decimals_diff(decimals_x: u8, decimals_y: u8, max_diff: u8) -> ProgramResult {
+        let mut mint_x = mint_account(Address::new_from_array([2; 32]), 0, decimals_x);
+        let mut mint_y = mint_account(Address::new_from_array([3; 32]), 0, decimals_y);
+        let mints = [mint_x.view(), mint_y.view()];
+        check_decimals_diff(&mints, &[2; 32], &[3; 32], max_diff)
+    }
+
+    #[test]
+    fn decimals_gap_is_checked_only_when_enabled() {
+        assert_eq!(decimals_diff(6, 9, 3), Ok(()));
+        assert_eq!(
+            decimals_diff(0, 9, 3),
+            Err(AmmError::DecimalsGapTooLarge.into())
+        );
+        // 0 表示不检查，也不需要传入 mint 账户
+        assert_eq!(decimals_diff(0, 9, DECIMALS_DIFF_UNCHECKED), Ok(()));
+        assert_eq!(
+            check_decimals_diff(&[], &[2; 32], &[3; 32], DECIMALS_DIFF_UNCHECKED),
+            Ok(())
+        );
+        assert_eq!(
+            check_decimals_diff(&[], &[2; 32], &[3; 32], 1),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+    }
+
+    #[test]
+    fn other_lengths_are_rejected() {
+        let long = long_form();
+        for len in [0, long.len() - 33, long.len() - 31, long.len() + 1] {
+            let mut data = long.clone();
+            data.resize(len, 0);
+            assert_eq!(
+                InitializeInstructionData::try_from(&data[..]).err(),
+                Some(ProgramError::InvalidInstructionData),
+                "len {len}"
+            );
+        }
+    }
+
+    #[test]
+    fn another_pools_lp_mint_can_be_paired_but_not_our_own() {
+        // 以池子 A 的 LP mint 作为池子 B 的 mint_x
+        let mut inner = pool();
+        let inner_lp = inner.mint_lp.view().address().clone();
+        let mut nested = NewPool::new(inner_lp.to_bytes(), [3; 32]);
+        assert_eq!(nested.initialize(), Ok(()));
+        assert_eq!(nested.config(|config| config.mint_x().clone()), inner_lp);
+
+        let mint_lp = nested.mint_lp.view().address().clone();
+        let own = mint_lp.to_bytes();
+        assert_eq!(
+            check_not_own_lp_mint(&mint_lp, &inner_lp.to_bytes(), &[3; 32]),
+            Ok(())
+        );
+        for (mint_x, mint_y) in [(own, [3; 32]), ([2; 32], own)] {
+            assert_eq!(
+                check_not_own_lp_mint(&mint_lp, &mint_x, &mint_y),
+                Err(ProgramError::InvalidArgument)
+            );
         }
-        .invoke_signed(&[Signer::from(&mint_lp_seeds)])?;
-
-        // --- 4. 初始化 Mint LP (设置 Mint Authority) ---
-        InitializeMint2 {
-            mint: accounts.mint_lp,
-            decimals: 6,                               // 通常 LP 代币使用 6 位小数
-            mint_authority: accounts.config.address(), // 权限交给 Config PDA
-            freeze_authority: None,
+    }
+
+    #[test]
+    fn authority_mode_decides_what_a_zero_authority_means() {
+        let with = |authority_mode, authority| {
+            let mut pool = pool();
+            pool.data.authority_mode = authority_mode;
+            pool.data.authority = authority;
+            pool.initialize()
+                .map(|()| pool.config(|config| config.has_authority()))
+        };
+
+        assert_eq!(
+            with(AUTHORITY_MODE_DEFAULT_INITIALIZER, [0; 32]),
+            Ok(Some(NewPool::INITIALIZER))
+        );
+        // 显式给出的管理员在两种模式下都原样保留
+        for mode in [AUTHORITY_MODE_AS_GIVEN, AUTHORITY_MODE_DEFAULT_INITIALIZER] {
+            assert_eq!(with(mode, [0xAA; 32]), Ok(Some([0xAA; 32].into())));
         }
-        .invoke()?;
+        // 有意放弃管理权
+        assert_eq!(with(AUTHORITY_MODE_AS_GIVEN, [0; 32]), Ok(None));
+        assert_eq!(with(2, [0; 32]), Err(ProgramError::InvalidInstructionData));
+    }
 
-        Ok(())
+    #[test]
+    fn initializer_must_cover_both_rents() {
+        let rent = rent();
+        let required = rent.try_minimum_balance(Config::LEN).unwrap()
+            + rent.try_minimum_balance(size_of::<Mint>()).unwrap();
+        #[cfg(feature = "creation-fee")]
+        let required = required + POOL_CREATION_FEE;
+        let funded = |lamports| {
+            let mut pool = pool();
+            pool.initializer = crate::test_utils::empty_account(NewPool::INITIALIZER)
+                .signer()
+                .writable()
+                .lamports(lamports);
+            pool.initialize()
+        };
+        assert_eq!(funded(required - 1), Err(ProgramError::InsufficientFunds));
+        assert_eq!(funded(required), Ok(()));
+    }
+
+    #[test]
+    fn the_lp_mint_is_funded_for_the_82_byte_token_mint() {
+        // 主网参数下 82 字节账户的免租金额度：(128 + 82) * 3480 * 2
+        const MINT_RENT: u64 = 1_461_600;
+        assert_eq!(rent().try_minimum_balance(82), Ok(MINT_RENT));
+        let required = rent().try_minimum_balance(Config::LEN).unwrap() + MINT_RENT;
+        #[cfg(feature = "creation-fee")]
+        let required = required + POOL_CREATION_FEE;
+        for (lamports, expected) in [
+            (required - 1, Err(ProgramError::InsufficientFunds)),
+            (required, Ok(())),
+        ] {
+            let mut pool = pool();
+            pool.initializer = crate::test_utils::empty_account(NewPool::INITIALIZER)
+                .signer()
+                .writable()
+                .lamports(lamports);
+            assert_eq!(pool.initialize(), expected);
+        }
+    }
+
+    #[cfg(feature = "creation-fee")]
+    #[test]
+    fn creation_fee_goes_only_to_the_treasury() {
+        let with_treasury = |treasury| {
+            let mut pool = pool();
+            let mut treasury = crate::test_utils::empty_account(treasury).writable();
+            let accounts = [
+                pool.initializer.view(),
+                pool.mint_lp.view(),
+                pool.config.view(),
+                treasury.view(),
+            ];
+            Initialize {
+                accounts: InitializeAccounts::try_from(&accounts[..])?,
+                instruction_data: pool.data,
+            }
+            .process_with_rent(&rent())?;
+            Ok::<_, ProgramError>(pool.config(|config| config.fee()))
+        };
+        assert_eq!(
+            with_treasury([11; 32].into()),
+            Err(ProgramError::InvalidAccountData)
+        );
+        // 主机上的 Transfer 是空操作，金库实际到账需在链上验证
+        assert_eq!(with_treasury(TREASURY), Ok(30));
+    }
+
+    #[test]
+    fn an_existing_mint_lp_is_rejected() {
+        let mut pool = pool();
+        let mint_lp = pool.mint_lp.view().address().clone();
+        pool.mint_lp = mint_account(mint_lp.clone(), 0, crate::LP_DECIMALS).writable();
+        assert_eq!(
+            pool.initialize(),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+        // 仍归系统程序所有、但已有数据的账户同样拒绝
+        pool.mint_lp = TestAccount::new(mint_lp, Address::default(), &[0; 8]).writable();
+        assert_eq!(
+            pool.initialize(),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn high_initial_fees_need_an_explicit_override() {
+        let with_fee = |fee, allow_high_fee| {
+            let mut pool = pool();
+            pool.data.fee = fee;
+            pool.data.allow_high_fee = allow_high_fee;
+            pool.initialize()
+        };
+        assert_eq!(with_fee(500, 0), Ok(()));
+        assert_eq!(with_fee(MAX_INITIAL_FEE, 0), Ok(()));
+        assert_eq!(
+            with_fee(MAX_INITIAL_FEE + 1, 0),
+            Err(AmmError::InvalidFee.into())
+        );
+        assert_eq!(with_fee(5_000, 0), Err(AmmError::InvalidFee.into()));
+        assert_eq!(with_fee(5_000, 1), Ok(()));
+    }
+
+    #[test]
+    fn all_zero_mints_are_rejected_only_with_strict_mints() {
+        for (mint_x, mint_y) in [([0; 32], [3; 32]), ([2; 32], [0; 32])] {
+            let expected = if cfg!(feature = "strict-mints") {
+                Err(ProgramError::InvalidArgument)
+            } else {
+                Ok(())
+            };
+            assert_eq!(NewPool::new(mint_x, mint_y).initialize(), expected);
+        }
+    }
+
+    #[test]
+    fn the_three_fixed_accounts_must_be_distinct() {
+        let funded = |address| {
+            crate::test_utils::empty_account(address)
+                .signer()
+                .writable()
+                .lamports(u64::MAX)
+        };
+        let addresses = |pool: &mut NewPool| {
+            (
+                pool.mint_lp.view().address().clone(),
+                pool.config.view().address().clone(),
+            )
+        };
+
+        let mut pool = pool();
+        let (_, config) = addresses(&mut pool);
+        pool.initializer = funded(config);
+        assert_eq!(pool.initialize(), Err(AmmError::DuplicateAccount.into()));
+
+        let mut pool = self::pool();
+        let (mint_lp, _) = addresses(&mut pool);
+        pool.initializer = funded(mint_lp);
+        assert_eq!(pool.initialize(), Err(AmmError::DuplicateAccount.into()));
+
+        let mut pool = self::pool();
+        let (_, config) = addresses(&mut pool);
+        pool.mint_lp = TestAccount::new(config, crate::ID, &[0; Config::LEN]).writable();
+        assert_eq!(pool.initialize(), Err(AmmError::DuplicateAccount.into()));
     }
 }
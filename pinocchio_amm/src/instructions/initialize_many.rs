@@ -0,0 +1,168 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    error::ProgramError,
+    sysvars::{Sysvar, rent::Rent},
+};
+
+use crate::{Initialize, InitializeAccounts, InitializeInstructionData};
+
+/// 单条指令内最多创建的池子数量，限制计算单元消耗。
+pub const MAX_POOLS_PER_INITIALIZE_MANY: usize = 4;
+
+/// 批量初始化：在一条指令里创建多个池子，任意一个失败则整条指令回滚。
+///
+/// 账户布局：`[initializer, treasury (仅 creation-fee), (mint_lp, config) * count]`，每个池子各收一次建池费
+/// 数据布局：`[count: u8, InitializeInstructionData (含 authority 的完整格式) * count]`
+pub struct InitializeMany<'a> {
+    pub initializer: &'a AccountView,
+    #[cfg(feature = "creation-fee")]
+    pub treasury: &'a AccountView,
+    pub pools: &'a [AccountView],
+    pub descriptors: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for InitializeMany<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        const DESCRIPTOR_LEN: usize = size_of::<InitializeInstructionData>();
+
+        let (count, descriptors) = data
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let count = *count as usize;
+        if count == 0 || count > MAX_POOLS_PER_INITIALIZE_MANY {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if descriptors.len() != count * DESCRIPTOR_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let (initializer, pools) = accounts
+            .split_first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        #[cfg(feature = "creation-fee")]
+        let (treasury, pools) = pools
+            .split_first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if pools.len() < count * 2 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        Ok(Self {
+            initializer,
+            #[cfg(feature = "creation-fee")]
+            treasury,
+            pools: &pools[..count * 2],
+            descriptors,
+        })
+    }
+}
+
+impl<'a> InitializeMany<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 各池子共用同一次读取的 Rent
+        let rent = Rent::get()?;
+        self.process_with_rent(&rent)
+    }
+
+    pub(crate) fn process_with_rent(&mut self, rent: &Rent) -> ProgramResult {
+        const DESCRIPTOR_LEN: usize = size_of::<InitializeInstructionData>();
+
+        for (pool, descriptor) in self
+            .pools
+            .chunks_exact(2)
+            .zip(self.descriptors.chunks_exact(DESCRIPTOR_LEN))
+        {
+            Initialize {
+                accounts: InitializeAccounts {
+                    initializer: self.initializer,
+                    mint_lp: &pool[0],
+                    config: &pool[1],
+                    #[cfg(feature = "creation-fee")]
+                    treasury: self.treasury,
+                    // 批量创建不传 mint 账户，max_decimals_diff 只能取 DECIMALS_DIFF_UNCHECKED
+                    remaining: &[],
+                },
+                instruction_data: InitializeInstructionData::try_from(descriptor)?,
+            }
+            .process_with_rent(rent)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{NewPool, rent};
+
+    /// `pools` 中每个池子的完整指令数据，前面加上数量
+    fn descriptors(pools: &[&NewPool]) -> Vec<u8> {
+        let mut data = vec![pools.len() as u8];
+        for pool in pools {
+            let bytes = unsafe {
+                core::slice::from_raw_parts(
+                    &pool.data as *const InitializeInstructionData as *const u8,
+                    size_of::<InitializeInstructionData>(),
+                )
+            };
+            data.extend_from_slice(bytes);
+        }
+        data
+    }
+
+    fn initialize_many(data: &[u8], a: &mut NewPool, b: &mut NewPool) -> ProgramResult {
+        #[cfg(feature = "creation-fee")]
+        let mut treasury = crate::test_utils::empty_account(crate::TREASURY).writable();
+        let mut accounts = vec![a.initializer.view()];
+        #[cfg(feature = "creation-fee")]
+        accounts.push(treasury.view());
+        accounts.extend([a.mint_lp.view(), a.config.view()]);
+        accounts.extend([b.mint_lp.view(), b.config.view()]);
+        InitializeMany::try_from((data, &accounts[..]))?.process_with_rent(&rent())
+    }
+
+    #[test]
+    fn every_pool_in_the_batch_is_initialized() {
+        let mut a = NewPool::new([2; 32], [3; 32]);
+        let mut b = NewPool::new([2; 32], [4; 32]);
+        b.data.fee = 50;
+        let data = descriptors(&[&a, &b]);
+        assert_eq!(initialize_many(&data, &mut a, &mut b), Ok(()));
+        assert_eq!(a.config(|config| config.fee()), 30);
+        assert_eq!(
+            b.config(|config| (config.fee(), config.mint_y().clone())),
+            (50, [4; 32].into())
+        );
+    }
+
+    #[test]
+    fn count_and_payload_length_must_agree() {
+        let mut a = NewPool::new([2; 32], [3; 32]);
+        let mut b = NewPool::new([2; 32], [4; 32]);
+        let mut data = descriptors(&[&a, &b]);
+        for count in [0, 1, 3, MAX_POOLS_PER_INITIALIZE_MANY as u8 + 1] {
+            data[0] = count;
+            assert_eq!(
+                initialize_many(&data, &mut a, &mut b),
+                Err(ProgramError::InvalidInstructionData),
+                "count {count}"
+            );
+        }
+        // 省略 authority 的短格式不能用于批量创建
+        let mut data = descriptors(&[&a, &b]);
+        data.truncate(data.len() - 32);
+        assert_eq!(
+            initialize_many(&data, &mut a, &mut b),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            initialize_many(&[], &mut a, &mut b),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use pinocchio::{
+    AccountView, Address, ProgramResult,
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{Sysvar, rent::Rent},
+};
+use pinocchio_system::instructions::CreateAccount;
+use pinocchio_token::{
+    instructions::{InitializeAccount3, MintTo, Transfer},
+    state::TokenAccount,
+};
+
+use crate::{
+    Config, Initialize, InitializeAccounts, InitializeInstructionData, check_distinct_vaults,
+    first_deposit_lp, seeds::lp_authority_seeds,
+};
+
+/*
+    在同一条指令里完成 Initialize 与首次存款，避免池子创建后、首笔流动性注入前被他人抢先存款定价。
+
+    vault_x / vault_y 需由客户端预先创建为 Config PDA 的 ATA (ATA 可以在 owner 账户存在之前创建)。
+    LP mint 在本指令中才创建，因此初始化者的 LP 代币账户无法提前准备：
+    `initializer_lp` 是一个新的签名账户，由本指令创建并初始化为归属 initializer 的代币账户。
+
+    数据布局：`[InitializeInstructionData (两种格式均可), max_x: u64, max_y: u64]`
+*/
+pub struct InitializeWithLiquidityAccounts<'a> {
+    pub initializer: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub initializer_x_ata: &'a AccountView,
+    pub initializer_y_ata: &'a AccountView,
+    pub initializer_lp: &'a AccountView,
+    pub token_program: &'a AccountView,
+    pub system_program: &'a AccountView,
+    /// LP mint 权限 PDA (`["lp_authority", config]`)
+    pub lp_authority: &'a AccountView,
+    /// 建池费收款账户 (仅 creation-fee)
+    #[cfg(feature = "creation-fee")]
+    pub treasury: &'a AccountView,
+}
+
+impl InitializeWithLiquidityAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 11 + cfg!(feature = "creation-fee") as usize;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for InitializeWithLiquidityAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        let accounts = Self {
+            initializer: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            mint_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            initializer_x_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            initializer_y_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            initializer_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            token_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            system_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            lp_authority: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            #[cfg(feature = "creation-fee")]
+            treasury: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        Ok(accounts)
+    }
+}
+
+/// 追加在 `InitializeInstructionData` 之后的初始流动性
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct InitialLiquidityData {
+    pub max_x: u64,
+    pub max_y: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for InitialLiquidityData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+pub struct InitializeWithLiquidity<'a> {
+    pub accounts: InitializeWithLiquidityAccounts<'a>,
+    pub initialize_data: InitializeInstructionData,
+    pub liquidity_data: InitialLiquidityData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for InitializeWithLiquidity<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = InitializeWithLiquidityAccounts::try_from(accounts)?;
+
+        // 初始流动性固定在末尾，前面的部分交给 Initialize 的解析逻辑 (兼容省略 authority 的格式)
+        let split = data
+            .len()
+            .checked_sub(size_of::<InitialLiquidityData>())
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let (initialize_data, liquidity_data) = data.split_at(split);
+
+        Ok(Self {
+            accounts,
+            initialize_data: InitializeInstructionData::try_from(initialize_data)?,
+            liquidity_data: InitialLiquidityData::try_from(liquidity_data)?,
+        })
+    }
+}
+
+impl<'a> InitializeWithLiquidity<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // Initialize 与 LP 代币账户共用同一次读取的 Rent
+        let rent = Rent::get()?;
+        self.process_with_rent(&rent)
+    }
+
+    pub(crate) fn process_with_rent(&mut self, rent: &Rent) -> ProgramResult {
+        let accounts = &self.accounts;
+        let liquidity = self.liquidity_data;
+
+        // 1. 首次存款的 LP 数量与 Deposit 保持一致：sqrt(x * y)
+        let lp_amount = first_deposit_lp(liquidity.max_x, liquidity.max_y);
+        if lp_amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        // 2. 创建 Config 与 LP mint
+        let mut initialize = Initialize {
+            accounts: InitializeAccounts {
+                initializer: accounts.initializer,
+                mint_lp: accounts.mint_lp,
+                config: accounts.config,
+                #[cfg(feature = "creation-fee")]
+                treasury: accounts.treasury,
+                // 不传 mint 账户，max_decimals_diff 只能取 DECIMALS_DIFF_UNCHECKED
+                remaining: &[],
+            },
+            instruction_data: self.initialize_data,
+        };
+        initialize.process_with_rent(rent)?;
+        let init_data = &initialize.instruction_data;
+
+        // 3. 金库必须归新创建的 Config PDA 所有，lp_authority 必须是该池子的 LP 权限 PDA
+        let lp_authority_bump = {
+            let config = Config::load(accounts.config)?;
+            config.check_lp_authority(accounts.config, accounts.lp_authority)?;
+            config.lp_authority_bump()
+        };
+        {
+            let vault_x = TokenAccount::from_account_view(accounts.vault_x)?;
+            let vault_y = TokenAccount::from_account_view(accounts.vault_y)?;
+            Config::check_vaults(accounts.config, &vault_x, &vault_y)?;
+            if vault_x.mint().ne(&Address::from(init_data.mint_x))
+                || vault_y.mint().ne(&Address::from(init_data.mint_y))
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        // 4. 创建初始化者的 LP 代币账户
+        let token_account_space = size_of::<TokenAccount>();
+        CreateAccount {
+            from: accounts.initializer,
+            to: accounts.initializer_lp,
+            lamports: rent.try_minimum_balance(token_account_space)?,
+            space: token_account_space as u64,
+            owner: &pinocchio_token::ID,
+        }
+        .invoke()?;
+
+        InitializeAccount3 {
+            account: accounts.initializer_lp,
+            mint: accounts.mint_lp,
+            owner: accounts.initializer.address(),
+        }
+        .invoke()?;
+
+        // 5. 注入初始流动性 (初始化者 -> 金库)
+        Transfer {
+            from: accounts.initializer_x_ata,
+            to: accounts.vault_x,
+            authority: accounts.initializer,
+            amount: liquidity.max_x,
+        }
+        .invoke()?;
+
+        Transfer {
+            from: accounts.initializer_y_ata,
+            to: accounts.vault_y,
+            authority: accounts.initializer,
+            amount: liquidity.max_y,
+        }
+        .invoke()?;
+
+        // 6. LP 权限 PDA 签名铸造 LP
+        let signer_seeds = lp_authority_seeds(accounts.config.address(), &lp_authority_bump);
+
+        MintTo {
+            mint: accounts.mint_lp,
+            account: accounts.initializer_lp,
+            mint_authority: accounts.lp_authority,
+            amount: lp_amount,
+        }
+        .invoke_signed(&[Signer::from(&signer_seeds)])?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{NewPool, TestAccount, empty_account, rent, token_account};
+
+    /// 初始化者持有 `max_x` / `max_y` 并以此建池；`vault_owner` 为两个金库的 owner
+    fn initialize_with_liquidity(
+        pool: &mut NewPool,
+        vault_owner: &Address,
+        max_x: u64,
+        max_y: u64,
+    ) -> ProgramResult {
+        let (mint_x, mint_y) = (
+            Address::from(pool.data.mint_x),
+            Address::from(pool.data.mint_y),
+        );
+        let config = pool.config.view().address().clone();
+        let (lp_authority, _) =
+            Address::find_program_address(&[crate::LP_AUTHORITY_SEED, config.as_ref()], &crate::ID);
+        let mut vault_x = token_account([4; 32].into(), &mint_x, vault_owner, 0).writable();
+        let mut vault_y = token_account([5; 32].into(), &mint_y, vault_owner, 0).writable();
+        let initializer = NewPool::INITIALIZER;
+        let mut initializer_x = token_account([7; 32].into(), &mint_x, &initializer, max_x);
+        let mut initializer_y = token_account([8; 32].into(), &mint_y, &initializer, max_y);
+        let mut initializer_lp = empty_account([10; 32].into()).signer().writable();
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let mut system_program = empty_account(pinocchio_system::ID);
+        let mut lp_authority = empty_account(lp_authority);
+        #[cfg(feature = "creation-fee")]
+        let mut treasury = empty_account(crate::TREASURY).writable();
+        let accounts = [
+            pool.initializer.view(),
+            pool.mint_lp.view(),
+            pool.config.view(),
+            vault_x.view(),
+            vault_y.view(),
+            initializer_x.view(),
+            initializer_y.view(),
+            initializer_lp.view(),
+            token_program.view(),
+            system_program.view(),
+            lp_authority.view(),
+            #[cfg(feature = "creation-fee")]
+            treasury.view(),
+        ];
+
+        let mut data = unsafe {
+            core::slice::from_raw_parts(
+                &pool.data as *const InitializeInstructionData as *const u8,
+                size_of::<InitializeInstructionData>(),
+            )
+        }
+        .to_vec();
+        data.extend_from_slice(&max_x.to_le_bytes());
+        data.extend_from_slice(&max_y.to_le_bytes());
+        InitializeWithLiquidity::try_from((&data[..], &accounts[..]))?.process_with_rent(&rent())
+    }
+
+    #[test]
+    fn one_call_initializes_the_pool_around_its_own_vaults() {
+        let mut pool = NewPool::new([2; 32], [3; 32]);
+        let config = pool.config.view().address().clone();
+        assert_eq!(
+            initialize_with_liquidity(&mut pool, &config, 1_000_000, 4_000_000),
+            Ok(())
+        );
+        assert_eq!(
+            pool.config(|config| (config.fee(), config.mint_x().clone())),
+            (30, [2; 32].into())
+        );
+        // 铸给初始化者的 LP 与首次 Deposit 相同
+        assert_eq!(first_deposit_lp(1_000_000, 4_000_000), 2_000_000);
+    }
+
+    #[test]
+    fn vaults_must_belong_to_the_new_config_and_liquidity_must_be_positive() {
+        let mut pool = NewPool::new([2; 32], [3; 32]);
+        assert_eq!(
+            initialize_with_liquidity(&mut pool, &[11; 32].into(), 1_000, 1_000),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+
+        let mut pool = NewPool::new([2; 32], [3; 32]);
+        let config = pool.config.view().address().clone();
+        assert_eq!(
+            initialize_with_liquidity(&mut pool, &config, 0, 1_000),
+            Err(ProgramError::InvalidArgument)
+        );
+        // 未写入任何状态
+        assert!(pool.config.data().iter().all(|&byte| byte == 0));
+    }
+}
@@ -0,0 +1,204 @@
+use pinocchio::{
+    AccountView, ProgramResult,
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{Sysvar, rent::Rent},
+};
+use pinocchio_token::{
+    instructions::{AuthorityType, SetAuthority},
+    state::Mint,
+};
+
+use crate::{Config, seeds::ConfigSeeds};
+
+/*
+    管理员在 Config 新增字段之后同步可以从其他账户推导出的数据。
+
+    旧版本程序创建的 Config 短于当前的 `Config::LEN` (最短为 `Config::MIN_MIGRATABLE_LEN`)，
+    先扩容到当前长度，免租金差额由管理员补足，新增字段为 0 (功能未启用)。
+    之后读取 LP mint 实际的小数位数并写入 `Config.lp_decimals`，
+    修正早期以硬编码 6 位小数创建、之后才记录该字段的池子。
+    第一个版本的池子以 Config 作为 LP mint 的 mint_authority，迁移时转交给 LP 权限 PDA
+    (`["lp_authority", config]`)，之后的 Deposit 由它签名铸造 LP；这一步需要 Token Program 出现在交易中。
+*/
+pub struct MigrateAccounts<'a> {
+    /// 池子管理员，Config 需要扩容时同时支付补足的租金，因此须可写
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub mint_lp: &'a AccountView,
+}
+
+impl MigrateAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 3;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for MigrateAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        Ok(Self {
+            authority: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            mint_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        })
+    }
+}
+
+pub struct Migrate<'a> {
+    pub accounts: MigrateAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Migrate<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = MigrateAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> Migrate<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &20;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // 扩容发生在管理员校验之前：非管理员调用时随后的校验失败，扩容与补足的租金一并回滚
+        if accounts.config.data_len() != Config::LEN {
+            let minimum_balance = Rent::get()?.try_minimum_balance(Config::LEN)?;
+            Config::realloc_legacy(accounts.config, accounts.authority, minimum_balance)?;
+        }
+
+        let mut config = Config::load_mut(accounts.config)?;
+        config.check_authority(accounts.authority)?;
+        config.check_mint_lp(accounts.config, accounts.mint_lp)?;
+
+        // 以 LP mint 上记录的实际值为准，而不是沿用默认的 LP_DECIMALS
+        let (decimals, minted_by_config) = {
+            let mint = Mint::from_account_view(accounts.mint_lp)?;
+            (
+                mint.decimals(),
+                mint.mint_authority() == Some(accounts.config.address()),
+            )
+        };
+        config.set_lp_decimals(decimals);
+        if !minted_by_config {
+            return Ok(());
+        }
+
+        // 第一个版本的 LP mint：把 mint_authority 从 Config 转交给 LP 权限 PDA (Config PDA 签名)
+        let lp_authority = config.lp_authority_address(accounts.config.address())?;
+        let config_seeds = ConfigSeeds::from_config(&config);
+        // CPI 会借用 Config 账户 (作为 authority)，先释放对其数据的可变借用
+        drop(config);
+        let config_seeds = config_seeds.as_seeds();
+        SetAuthority {
+            account: accounts.mint_lp,
+            authority: accounts.config,
+            authority_type: AuthorityType::MintTokens,
+            new_authority: Some(&lp_authority),
+        }
+        .invoke_signed(&[Signer::from(&config_seeds)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, empty_account, mint_account};
+    use pinocchio::Address;
+
+    /// 把 `pool` 的 Config 截断为旧版本的 `len` 字节
+    fn legacy_config(pool: &TestPool, len: usize) -> TestAccount {
+        let data = pool.config.data()[..len].to_vec();
+        TestAccount::new(Address::new_from_array([1; 32]), crate::ID, &data)
+            .writable()
+            .lamports(1)
+    }
+
+    fn migrate(config: &mut TestAccount, mint_lp: &mut TestAccount) -> ProgramResult {
+        let mut authority = empty_account(TestPool::AUTHORITY).signer().writable();
+        let accounts = [authority.view(), config.view(), mint_lp.view()];
+        Migrate::try_from((&[][..], &accounts[..]))?.process()
+    }
+
+    #[test]
+    fn first_version_config_is_extended_with_the_new_pool_defaults() {
+        // 第一个版本的 Config 只有 state 到 config_bump
+        let mut pool = TestPool::new(0, 0, 0);
+        let mut config = legacy_config(&pool, Config::MIN_MIGRATABLE_LEN);
+        let mut payer = empty_account(TestPool::AUTHORITY).signer().writable();
+
+        Config::realloc_legacy(&config.view(), &payer.view(), 1_000).unwrap();
+        // 新增字段与 set_inner 创建的池子相同：重新推导的 bump、LP_DECIMALS、DEFAULT_MAX_SWAP_BPS
+        assert_eq!(config.data(), pool.config.data());
+        let view = config.view();
+        let migrated = Config::load(&view).unwrap();
+        assert_eq!(migrated.max_swap_bps(), crate::DEFAULT_MAX_SWAP_BPS);
+        migrated.check_mint_lp(&view, &pool.mint_lp.view()).unwrap();
+    }
+
+    #[test]
+    fn later_fields_keep_their_values_when_extending() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            config.set_fee(30).unwrap();
+            config.set_max_swap_bps(500).unwrap();
+            config.set_lock_duration(3_600);
+        });
+        // 新增 deposit_fee 之前的布局 (max_swap_bps 已存在)
+        let len = 244;
+        let mut config = legacy_config(&pool, len);
+        let mut payer = empty_account(TestPool::AUTHORITY).signer().writable();
+
+        Config::realloc_legacy(&config.view(), &payer.view(), 1_000).unwrap();
+        let data = config.data();
+        assert_eq!(&data[..len], &pool.config.data()[..len]);
+        let view = config.view();
+        let migrated = Config::load(&view).unwrap();
+        assert_eq!(
+            (
+                migrated.fee(),
+                migrated.max_swap_bps(),
+                migrated.lock_duration()
+            ),
+            (30, 500, 0)
+        );
+    }
+
+    #[test]
+    fn configs_older_than_the_migratable_layout_are_rejected() {
+        let pool = TestPool::new(0, 0, 0);
+        let mut config = legacy_config(&pool, Config::MIN_MIGRATABLE_LEN - 1);
+        assert_eq!(
+            Config::realloc_legacy(&config.view(), &config.view(), 1_000),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn migrate_records_the_mint_decimals() {
+        let mut pool = TestPool::new(0, 0, 0);
+        let mint_lp = pool.mint_lp.view().address().clone();
+        let mut mint_lp = mint_account(mint_lp, 0, 9);
+        migrate(&mut pool.config, &mut mint_lp).unwrap();
+        assert_eq!(Config::load(&pool.config.view()).unwrap().lp_decimals(), 9);
+    }
+
+    #[test]
+    fn migrate_requires_the_pool_authority() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| config.set_authority(Address::new_from_array([8; 32])));
+        assert_eq!(
+            migrate(&mut pool.config, &mut pool.mint_lp),
+            Err(ProgramError::IncorrectAuthority)
+        );
+    }
+}
@@ -1,9 +1,71 @@
+pub mod admin;
+pub mod claim_locked_lp;
+pub mod complete_initialize;
 pub mod deposit;
+pub mod force_disable;
+pub mod get_seed;
+pub mod get_spot_price;
+pub mod get_state;
+pub mod get_version;
+pub mod global_pause;
+pub mod health_check;
 pub mod initialize;
+pub mod initialize_many;
+pub mod initialize_with_liquidity;
+pub mod migrate;
+pub mod set_base_fee;
+pub mod set_cooldown;
+pub mod set_deposit_fee;
+pub mod set_direction_fees;
+pub mod set_fee_mode;
+pub mod set_flags;
+pub mod set_gate;
+pub mod set_lock_duration;
+pub mod set_lp_holder_fee;
+pub mod set_max_abs_fee;
+pub mod set_max_swap_bps;
+pub mod set_min_slippage_protection;
+pub mod set_state;
+pub mod set_swap_tip;
+pub mod set_twap_interval;
+pub mod set_volatility_fee;
 pub mod swap;
+pub mod swap_exact_in_with_path;
 pub mod withdraw;
+pub mod withdraw_protocol_fees;
 
+pub use admin::*;
+pub use claim_locked_lp::*;
+pub use complete_initialize::*;
 pub use deposit::*;
+pub use force_disable::*;
+pub use get_seed::*;
+pub use get_spot_price::*;
+pub use get_state::*;
+pub use get_version::*;
+pub use global_pause::*;
+pub use health_check::*;
 pub use initialize::*;
+pub use initialize_many::*;
+pub use initialize_with_liquidity::*;
+pub use migrate::*;
+pub use set_base_fee::*;
+pub use set_cooldown::*;
+pub use set_deposit_fee::*;
+pub use set_direction_fees::*;
+pub use set_fee_mode::*;
+pub use set_flags::*;
+pub use set_gate::*;
+pub use set_lock_duration::*;
+pub use set_lp_holder_fee::*;
+pub use set_max_abs_fee::*;
+pub use set_max_swap_bps::*;
+pub use set_min_slippage_protection::*;
+pub use set_state::*;
+pub use set_swap_tip::*;
+pub use set_twap_interval::*;
+pub use set_volatility_fee::*;
 pub use swap::*;
+pub use swap_exact_in_with_path::*;
 pub use withdraw::*;
+pub use withdraw_protocol_fees::*;
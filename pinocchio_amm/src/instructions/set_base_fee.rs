@@ -0,0 +1,26 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员调整基础手续费。交换实际收取 `fee + volatility_fee`，两者之和必须小于 10_000 bps。
+pub type SetBaseFee<'a> = Admin<'a, SetBaseFeeInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetBaseFeeInstructionData {
+    /// 新的基础手续费 (bps)
+    pub fee: u16,
+}
+
+unsafe impl AdminUpdate for SetBaseFeeInstructionData {
+    const DISCRIMINATOR: &'static u8 = &10;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_fee(self.fee)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置交换冷却 (slot)。启用后每个用户在同一池子内每 `cooldown_slots` 个 slot 只能交换一次，
+/// 用于抑制高频套利机器人。
+pub type SetCooldown<'a> = Admin<'a, SetCooldownInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetCooldownInstructionData {
+    /// 同一用户两次交换之间至少间隔的 slot 数，0 表示关闭冷却
+    pub cooldown_slots: u64,
+}
+
+unsafe impl AdminUpdate for SetCooldownInstructionData {
+    const DISCRIMINATOR: &'static u8 = &14;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_cooldown_slots(self.cooldown_slots);
+
+        Ok(())
+    }
+}
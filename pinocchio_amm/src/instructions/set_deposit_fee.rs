@@ -0,0 +1,27 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置存款手续费：非首次存款时按该比例少铸造 LP，存入的代币全部留在池子里，
+/// 相当于把这部分价值分给现有 LP。适合新发行的池子抑制短期进出。
+pub type SetDepositFee<'a> = Admin<'a, SetDepositFeeInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetDepositFeeInstructionData {
+    /// 存款手续费 (bps)，必须小于 10_000，0 表示不收取
+    pub deposit_fee: u16,
+}
+
+unsafe impl AdminUpdate for SetDepositFeeInstructionData {
+    const DISCRIMINATOR: &'static u8 = &26;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_deposit_fee(self.deposit_fee)?;
+
+        Ok(())
+    }
+}
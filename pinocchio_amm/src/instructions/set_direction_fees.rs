@@ -0,0 +1,29 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员为两个交换方向分别设置基础费率 (非对称手续费)。`volatility_fee` 仍叠加在所选方向的费率上，
+/// 两者之和必须小于 10_000。
+pub type SetDirectionFees<'a> = Admin<'a, SetDirectionFeesInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetDirectionFeesInstructionData {
+    /// X -> Y 方向的基础费率 (bps)，0 表示沿用 `fee`
+    pub fee_x_to_y: u16,
+    /// Y -> X 方向的基础费率 (bps)，0 表示沿用 `fee`
+    pub fee_y_to_x: u16,
+}
+
+unsafe impl AdminUpdate for SetDirectionFeesInstructionData {
+    const DISCRIMINATOR: &'static u8 = &30;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_direction_fees(self.fee_x_to_y, self.fee_y_to_x)?;
+
+        Ok(())
+    }
+}
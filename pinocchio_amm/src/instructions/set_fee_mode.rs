@@ -0,0 +1,62 @@
+use pinocchio::{ProgramResult, error::ProgramError};
+
+use crate::{
+    Config, FeeMode,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员切换手续费模式：手续费留给 LP (`FeeMode::Lp`)、直接转给指定的 `fee_recipient`
+/// (`FeeMode::Recipient`)，或记为协议收入待提取 (`FeeMode::Protocol`)。
+pub type SetFeeMode<'a> = Admin<'a, SetFeeModeInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetFeeModeInstructionData {
+    pub fee_mode: u8,
+    /// 接收手续费的钱包地址 (其 ATA 作为 Swap 的手续费收款账户)
+    pub fee_recipient: [u8; 32],
+}
+
+unsafe impl AdminUpdate for SetFeeModeInstructionData {
+    const DISCRIMINATOR: &'static u8 = &4;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        // Recipient / Protocol 模式必须指定有效的收款地址
+        if self.fee_mode != FeeMode::Lp as u8 && self.fee_recipient == [0u8; 32] {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        config.set_fee_mode(self.fee_mode)?;
+        config.set_fee_recipient(self.fee_recipient.into());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestPool;
+
+    #[test]
+    fn non_lp_modes_require_a_recipient() {
+        let mut pool = TestPool::new(0, 0, 0);
+        let update = |fee_mode: FeeMode, fee_recipient| SetFeeModeInstructionData {
+            fee_mode: fee_mode as u8,
+            fee_recipient,
+        };
+        pool.update_config(|config| {
+            for mode in [FeeMode::Recipient, FeeMode::Protocol] {
+                assert_eq!(
+                    update(mode, [0; 32]).apply(config),
+                    Err(ProgramError::InvalidInstructionData)
+                );
+            }
+            assert_eq!(update(FeeMode::Lp, [0; 32]).apply(config), Ok(()));
+
+            assert_eq!(update(FeeMode::Recipient, [12; 32]).apply(config), Ok(()));
+            assert_eq!(config.fee_mode(), FeeMode::Recipient as u8);
+            assert_eq!(config.fee_recipient().as_ref(), &[12; 32]);
+        });
+    }
+}
@@ -0,0 +1,57 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置细粒度暂停位 (`FLAG_DEPOSITS_PAUSED` / `FLAG_SWAPS_PAUSED` / `FLAG_WITHDRAWALS_PAUSED`)，
+/// 每一位只影响对应的操作，例如可以暂停存款但仍允许交换。
+pub type SetFlags<'a> = Admin<'a, SetFlagsInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetFlagsInstructionData {
+    /// 按位组合的 `FLAG_*`，未定义的位会被拒绝
+    pub flags: u8,
+}
+
+unsafe impl AdminUpdate for SetFlagsInstructionData {
+    const DISCRIMINATOR: &'static u8 = &7;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_flags(self.flags)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FLAG_DEPOSITS_PAUSED, FLAGS_ALL, test_utils::TestPool};
+    use pinocchio::error::ProgramError;
+
+    #[test]
+    fn only_defined_pause_bits_can_be_set() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            assert_eq!(
+                SetFlagsInstructionData { flags: 1 << 3 }.apply(config),
+                Err(ProgramError::InvalidAccountData)
+            );
+            assert_eq!(
+                SetFlagsInstructionData { flags: FLAGS_ALL }.apply(config),
+                Ok(())
+            );
+            assert_eq!(
+                SetFlagsInstructionData {
+                    flags: FLAG_DEPOSITS_PAUSED
+                }
+                .apply(config),
+                Ok(())
+            );
+            assert_eq!(config.flags(), FLAG_DEPOSITS_PAUSED);
+        });
+    }
+}
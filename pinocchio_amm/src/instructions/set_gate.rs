@@ -0,0 +1,27 @@
+use pinocchio::{Address, ProgramResult};
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置交换的准入代币 (例如 KYC 凭证)：启用后交换者必须提供自己持有该代币、
+/// 余额大于 0 的代币账户。Deposit / Withdraw 不受影响。
+pub type SetGate<'a> = Admin<'a, SetGateInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetGateInstructionData {
+    /// 准入代币的 mint，全 0 表示关闭准入限制
+    pub gate_mint: [u8; 32],
+}
+
+unsafe impl AdminUpdate for SetGateInstructionData {
+    const DISCRIMINATOR: &'static u8 = &29;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_gate_mint(Address::new_from_array(self.gate_mint));
+
+        Ok(())
+    }
+}
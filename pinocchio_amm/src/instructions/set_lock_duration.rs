@@ -0,0 +1,28 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置 LP 锁定期 (秒)。启用后存款铸造的 LP 先进入用户的托管账户，最近一次存款之后
+/// 满 `lock_duration` 秒才能通过 `ClaimLockedLp` 领取，锁定期内既不能取款也不能转给其他钱包。
+/// 用于抑制在大额交换前后存入、取出流动性以套取手续费的短期 LP。
+pub type SetLockDuration<'a> = Admin<'a, SetLockDurationInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetLockDurationInstructionData {
+    /// 存款之后的 LP 锁定时长 (秒)，0 表示关闭锁定
+    pub lock_duration: u64,
+}
+
+unsafe impl AdminUpdate for SetLockDurationInstructionData {
+    const DISCRIMINATOR: &'static u8 = &31;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_lock_duration(self.lock_duration);
+
+        Ok(())
+    }
+}
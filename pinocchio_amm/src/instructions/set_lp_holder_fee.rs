@@ -0,0 +1,29 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置 LP 持有者的交换手续费优惠。交换时与常规费率一样叠加 `volatility_fee`，
+/// 实际使用的手续费不会高于常规手续费。
+pub type SetLpHolderFee<'a> = Admin<'a, SetLpHolderFeeInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetLpHolderFeeInstructionData {
+    /// LP 持有者交换时使用的手续费 (bps)，范围与 `SetBaseFee` 相同：加上 `volatility_fee` 后小于 10_000
+    pub lp_holder_fee: u16,
+    /// 享受优惠所需的最少 LP 数量，0 表示关闭优惠
+    pub lp_holder_min: u64,
+}
+
+unsafe impl AdminUpdate for SetLpHolderFeeInstructionData {
+    const DISCRIMINATOR: &'static u8 = &19;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_lp_holder_fee(self.lp_holder_fee, self.lp_holder_min)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置每笔交换手续费的绝对上限。上限生效时 exact-in 交换多出的部分计入输出，
+/// exact-out 交换则少收对应的输入。
+pub type SetMaxAbsFee<'a> = Admin<'a, SetMaxAbsFeeInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetMaxAbsFeeInstructionData {
+    /// 每笔交换手续费的绝对上限 (以输入代币计)，0 表示不设上限
+    pub max_abs_fee: u64,
+}
+
+unsafe impl AdminUpdate for SetMaxAbsFeeInstructionData {
+    const DISCRIMINATOR: &'static u8 = &18;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_max_abs_fee(self.max_abs_fee);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置单笔交换的规模上限：输入 (含手续费) 不能超过输入方储备的 `max_swap_bps`。
+/// 新建池子默认为 `DEFAULT_MAX_SWAP_BPS`。
+pub type SetMaxSwapBps<'a> = Admin<'a, SetMaxSwapBpsInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetMaxSwapBpsInstructionData {
+    /// 单笔交换输入占输入方储备的上限 (bps)，不超过 10_000，0 表示不限制
+    pub max_swap_bps: u16,
+}
+
+unsafe impl AdminUpdate for SetMaxSwapBpsInstructionData {
+    const DISCRIMINATOR: &'static u8 = &24;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_max_swap_bps(self.max_swap_bps)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 开关池子的滑点保护策略：开启后，Deposit 的 `max_x` / `max_y` 与 exact-out 交换的最大输入
+/// 不能为 `u64::MAX`，Withdraw 的 `min_x` / `min_y` 与 exact-in 交换的最少输出不能为 0。
+pub type SetMinSlippageProtection<'a> = Admin<'a, SetMinSlippageProtectionInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetMinSlippageProtectionInstructionData {
+    /// 非 0 时要求 Deposit / Withdraw / Swap 提供有效的滑点参数，0 关闭该策略
+    pub required: u8,
+}
+
+unsafe impl AdminUpdate for SetMinSlippageProtectionInstructionData {
+    const DISCRIMINATOR: &'static u8 = &21;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_require_slippage(self.required != 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    AmmState, Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员切换池子状态 (启用 / 停用 / 只允许提取)，只接受 `Config::transition` 允许的迁移。
+pub type SetState<'a> = Admin<'a, SetStateInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetStateInstructionData {
+    /// 目标状态，见 `AmmState`
+    pub state: u8,
+}
+
+unsafe impl AdminUpdate for SetStateInstructionData {
+    const DISCRIMINATOR: &'static u8 = &16;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.transition(AmmState::try_from(self.state)?)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置交换 tip：每笔交换由用户向程序级 tip 金库 PDA (`["swap_tip_treasury"]`) 额外转入
+/// 固定数量的 lamports，与代币手续费分开计算。tip 金库尚未创建时，首笔 tip 必须不低于
+/// 空账户的免租金额度，否则转账会被运行时拒绝。
+pub type SetSwapTip<'a> = Admin<'a, SetSwapTipInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetSwapTipInstructionData {
+    /// 每笔交换额外收取的 lamports，0 表示不收取
+    pub swap_tip: u64,
+}
+
+unsafe impl AdminUpdate for SetSwapTipInstructionData {
+    const DISCRIMINATOR: &'static u8 = &28;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_swap_tip(self.swap_tip);
+
+        Ok(())
+    }
+}
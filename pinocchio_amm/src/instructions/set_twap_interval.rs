@@ -0,0 +1,26 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员设置 TWAP 累加器的最小更新间隔，减少高频交换时的计算开销。
+pub type SetTwapInterval<'a> = Admin<'a, SetTwapIntervalInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetTwapIntervalInstructionData {
+    /// 两次 TWAP 累加器更新之间的最小间隔 (秒)，0 表示每笔交换都更新
+    pub twap_min_interval: u32,
+}
+
+unsafe impl AdminUpdate for SetTwapIntervalInstructionData {
+    const DISCRIMINATOR: &'static u8 = &15;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_twap_min_interval(self.twap_min_interval);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use pinocchio::ProgramResult;
+
+use crate::{
+    Config,
+    instructions::{Admin, AdminUpdate},
+};
+
+/// 管理员调整随波动率变化的附加手续费。交换实际收取 `fee + volatility_fee`，两者之和必须小于 10_000 bps。
+pub type SetVolatilityFee<'a> = Admin<'a, SetVolatilityFeeInstructionData>;
+
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SetVolatilityFeeInstructionData {
+    /// 新的附加手续费 (bps)
+    pub volatility_fee: u16,
+}
+
+unsafe impl AdminUpdate for SetVolatilityFeeInstructionData {
+    const DISCRIMINATOR: &'static u8 = &11;
+
+    fn apply(&self, config: &mut Config) -> ProgramResult {
+        config.set_volatility_fee(self.volatility_fee)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SetBaseFeeInstructionData, test_utils::TestPool};
+    use pinocchio::error::ProgramError;
+
+    #[test]
+    fn base_and_volatility_fees_together_stay_below_100_percent() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            let base = |fee| SetBaseFeeInstructionData { fee };
+            let volatility = |volatility_fee| SetVolatilityFeeInstructionData { volatility_fee };
+
+            assert_eq!(base(9_000).apply(config), Ok(()));
+            assert_eq!(volatility(999).apply(config), Ok(()));
+            assert_eq!(config.effective_fee(), 9_999);
+            assert_eq!(
+                volatility(1_000).apply(config),
+                Err(ProgramError::InvalidAccountData)
+            );
+            assert_eq!(
+                base(9_001).apply(config),
+                Err(ProgramError::InvalidAccountData)
+            );
+
+            assert_eq!(volatility(0).apply(config), Ok(()));
+            assert_eq!(base(9_999).apply(config), Ok(()));
+            assert_eq!(config.effective_fee(), 9_999);
+        });
+    }
+}
@@ -1,13 +1,25 @@
-use constant_product_curve::{ConstantProduct, LiquidityPair};
+use core::mem::offset_of;
+
 use pinocchio::{
-    AccountView, ProgramResult,
-    cpi::{Seed, Signer},
+    AccountView, Address, ProgramResult,
+    cpi::{Signer, invoke_with_slice},
     error::ProgramError,
-    sysvars::{Sysvar, clock::Clock},
+    instruction::{InstructionAccount, InstructionView},
+    sysvars::{Sysvar, rent::Rent},
 };
+use pinocchio_system::instructions::Transfer as SystemTransfer;
 use pinocchio_token::{instructions::Transfer, state::TokenAccount};
 
-use crate::Config;
+use crate::{
+    AmmError, Config, FLAG_SWAPS_PAUSED, FeeMode, GlobalState, SWAP_TIP_TREASURY_SEED,
+    USER_SWAP_SEED, UserSwapState, check_distinct_vaults, check_expiration, check_token_program,
+    create_program_account,
+    curve::{FlashSettlement, SwapPool},
+    events::{Event, FeeAccrued},
+    get_clock,
+    ret::{ReturnData, SwapQuote},
+    seeds::{ConfigSeeds, user_swap_seeds},
+};
 
 /*
     计算通过将一定数量的 mint_y 发送到 AMM（或反之）后，能够接收到的 mint_x 的数量，包括手续费。
@@ -15,6 +27,7 @@ use crate::Config;
     将 from 代币转移到金库，并将 to 代币转移到用户的代币账户。
 */
 pub struct SwapAccounts<'a> {
+    /// 交换者，必须签名；`user_x_ata` / `user_y_ata` 必须属于它 (`to_recipient` 时的输出 ATA 除外)
     pub user: &'a AccountView,
     pub user_x_ata: &'a AccountView,
     pub user_y_ata: &'a AccountView,
@@ -22,14 +35,54 @@ pub struct SwapAccounts<'a> {
     pub vault_y: &'a AccountView,
     pub config: &'a AccountView,
     pub token_program: &'a AccountView,
+    /// 全局暂停单例 PDA (`["global"]`)，尚未创建时传入其地址即可
+    pub global_state: &'a AccountView,
+    /// 可选账户，按以下顺序排列，仅在对应功能启用时才需要提供：
+    /// 0. `user_lp_ata` 与 `mint_lp`：用户的 LP 代币账户及本池子的 LP mint，`lp_discount` 时需要
+    /// 1. `secondary_recipient`：第二收款 ATA (输出代币，属于 Token 程序)，`split_bps > 0` 时需要
+    /// 2. `fee_recipient_ata`：手续费收款 ATA (输入代币)，`FeeMode::Recipient` 时需要
+    /// 3. `user_swap_state` 与 `system_program`：用户的交换记录 PDA
+    ///    (`["user_swap", config, user]`) 及用于首次创建它的 System Program，`cooldown_slots > 0` 时需要
+    /// 4. `recipient_ata`：接收主输出的第三方 ATA (输出代币)，`to_recipient` 时需要
+    /// 5. `tip_treasury`：程序级 tip 金库 PDA (`["swap_tip_treasury"]`)，`Config.swap_tip > 0` 时需要；
+    ///    System Program 需出现在交易的账户列表中
+    /// 6. `gate_ata`：交换者持有准入代币的代币账户，`Config.gate_mint` 非全 0 时需要
+    /// 7. `callback_program` 及其后的全部账户：闪电交换的回调程序与回调所需的账户，`flash` 时需要。
+    ///    这些账户以原有的可写权限、但一律不带签名权限转交给回调；不能包含 Config
+    pub remaining: &'a [AccountView],
+}
+
+impl SwapAccounts<'_> {
+    /// 指令所需的固定账户数量，之后的账户按功能需要作为可选账户 (见 `remaining`)。
+    pub const LEN: usize = 8;
+
+    /// 一次性校验固定账户中与池子绑定的部分，返回遇到的第一个错误：
+    /// Token 程序、金库币种、用户 ATA 币种。金库的 authority 在读取储备时校验，
+    /// 可选账户 (`remaining`) 在用到时各自校验。
+    pub fn validate(&self, config: &Config) -> ProgramResult {
+        check_token_program(self.token_program)?;
+        config.check_vault_mints(self.vault_x, self.vault_y)?;
+
+        // 用户 ATA 的币种必须与声明的方向一致，防止客户端传错 is_x / ATA 顺序时静默执行错误的交换
+        let user_x_ata = unsafe { TokenAccount::from_account_view_unchecked(self.user_x_ata)? };
+        let user_y_ata = unsafe { TokenAccount::from_account_view_unchecked(self.user_y_ata)? };
+        if user_x_ata.mint().ne(config.mint_x()) || user_y_ata.mint().ne(config.mint_y()) {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
         let mut iter = accounts.iter();
-        Ok(Self {
+        let accounts = Self {
             user: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             user_x_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             user_y_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
@@ -37,7 +90,24 @@ impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
             vault_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             token_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
-        })
+            global_state: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            remaining: iter.as_slice(),
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        // process 在 Transfer CPI 期间持有 Config 的共享借用 (`Config::load`)。
+        // Config 出现在任何一个代币账户位置上都会让 CPI 以借用冲突失败，这里提前给出明确的错误
+        let config = accounts.config.address();
+        for token_account in [
+            accounts.user_x_ata,
+            accounts.user_y_ata,
+            accounts.vault_x,
+            accounts.vault_y,
+        ] {
+            if token_account.address().eq(config) {
+                return Err(AmmError::DuplicateAccount.into());
+            }
+        }
+        Ok(accounts)
     }
 }
 
@@ -47,7 +117,104 @@ pub struct SwapInstructionData {
     pub is_x: bool,
     pub amount: u64,
     pub min: u64,
+    /// 订单的最后有效时间 (unix 秒，包含边界)：与 Clock sysvar 的 `unix_timestamp` 比较，
+    /// `unix_timestamp == expiration` 时仍可执行，大于时拒绝；`clock-free` 构建中被忽略
     pub expiration: i64,
+    /// 输出中分给 `secondary_recipient` 的比例 (bps)，其余归用户；0 表示不拆分
+    pub split_bps: u16,
+    /// false: exact-in，`amount` 为输入数量，`min` 为最少输出；
+    /// true: exact-out，`amount` 为期望输出数量，`min` 为愿意支付的最大输入，
+    /// 实际收取的输入通过 return data (`SwapQuote`) 返回。
+    /// 取整策略见 `crate::curve`。
+    pub exact_out: bool,
+    /// true 时执行全部校验与曲线计算 (包括账户检查)，通过 return data 返回 `SwapQuote`，
+    /// 但不发起任何转账，也不修改 Config
+    pub dry_run: bool,
+    /// true 时 `min` 表示客户端看到的报价 (exact-in 为报价输出，exact-out 为报价输入)，
+    /// 实际的滑点限制由 `min_bps` 从报价推导；false 时 `min` 为绝对限制，`min_bps` 被忽略
+    pub min_is_bps: bool,
+    /// 相对报价允许的滑点 (bps)，仅在 `min_is_bps` 时生效
+    pub min_bps: u16,
+    /// true 时用户以 LP 持有者身份使用 `Config.lp_holder_fee` (同样叠加 `volatility_fee`)，
+    /// 需提供用户自己的 LP 代币账户
+    pub lp_discount: bool,
+    /// true 时为闪电交换：先把输出转给用户，再 CPI 调用 `callback_program`，
+    /// 回调结束后输入方金库必须至少收到应付的输入 (含手续费)，否则整笔交易回滚。
+    /// 用户的签名不会转交给回调，偿还的资金须由回调程序自己 (例如其 PDA) 签名转出。
+    /// 仅支持 exact-out，且不支持 `FeeMode::Recipient`
+    pub flash: bool,
+    /// true 时主输出转入 `recipient_ata` 而不是用户自己的 ATA (例如直接付款给第三方)，
+    /// 输入仍由签名用户支付
+    pub to_recipient: bool,
+    /// 仅对 exact-out 生效：false (默认) 时池子无法提供完整的 `amount` 则整笔回滚；
+    /// true 时按池子当前最多能换出的数量 (同时受储备下限与 `max_swap_bps` 限制) 部分成交，
+    /// 实际输出通过 return data (`SwapQuote`) 返回
+    pub allow_partial: bool,
+    /// 仅对 exact-out 生效的买单模式："买入 `amount` 个输出代币，最多花掉全部余额"。
+    /// 所需输入超过用户输入 ATA 的余额时，改为以全部余额做 exact-in，实际成交通过
+    /// return data (`SwapQuote`) 返回。这时的最少输出为 `amount * (10_000 - min_bps) / 10_000`，
+    /// 因此需要 `min_is_bps`，否则余额不足时拒绝。不支持 `flash`
+    pub clamp_to_balance: bool,
+}
+
+/// 闪电交换时传给回调程序的指令数据
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct FlashCallbackData {
+    /// 已转给用户的输出数量
+    pub amount_out: u64,
+    /// 回调结束前需要转入输入方金库的数量 (含手续费)
+    pub amount_owed: u64,
+    /// 与 `SwapInstructionData::is_x` 相同：true 表示需偿还 X
+    pub is_x: bool,
+}
+
+impl SwapInstructionData {
+    /// 实际使用的滑点限制：exact-in 为最少输出，exact-out 为最大输入
+    pub fn slippage_limit(&self) -> Result<u64, ProgramError> {
+        if !self.min_is_bps {
+            return Ok(self.min);
+        }
+        if self.min_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let quote = self.min as u128;
+        let limit = if self.exact_out {
+            quote * (10_000 + self.min_bps as u128) / 10_000
+        } else {
+            quote * (10_000 - self.min_bps as u128) / 10_000
+        };
+        u64::try_from(limit).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// 买单模式按余额部分成交时的最少输出：期望输出 `amount` 扣除 `min_bps`。
+    /// 未使用 `min_is_bps` 时没有可用的下限，返回 `SlippageExceeded`
+    pub fn clamped_min_output(&self) -> Result<u64, ProgramError> {
+        if !self.min_is_bps {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+        if self.min_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        // 结果不超过 amount
+        Ok((self.amount as u128 * (10_000 - self.min_bps as u128) / 10_000) as u64)
+    }
+}
+
+impl SwapInstructionData {
+    /// 各 `bool` 字段在指令数据中的偏移
+    const BOOL_OFFSETS: [usize; 9] = [
+        offset_of!(Self, is_x),
+        offset_of!(Self, exact_out),
+        offset_of!(Self, dry_run),
+        offset_of!(Self, min_is_bps),
+        offset_of!(Self, lp_discount),
+        offset_of!(Self, flash),
+        offset_of!(Self, to_recipient),
+        offset_of!(Self, allow_partial),
+        offset_of!(Self, clamp_to_balance),
+    ];
 }
 
 impl<'a> TryFrom<&'a [u8]> for SwapInstructionData {
@@ -57,7 +224,11 @@ impl<'a> TryFrom<&'a [u8]> for SwapInstructionData {
         if data.len() < size_of::<Self>() {
             return Err(ProgramError::InvalidInstructionData);
         }
-        Ok(unsafe { *(data.as_ptr() as *const Self) })
+        // bool 只能是 0 或 1，其他取值直接转换为 bool 是未定义行为
+        if Self::BOOL_OFFSETS.iter().any(|&offset| data[offset] > 1) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
     }
 }
 
@@ -87,11 +258,28 @@ impl<'a> Swap<'a> {
         let accounts = &self.accounts;
         let data = &self.instruction_data;
 
+        // 用户必须签名，任何模式都不例外：闪电交换中用户不转出任何代币，Token 程序不会替我们检查签名，
+        // 而准入代币、LP 持有者优惠与交换冷却都以 `user` 的身份为准
+        if !accounts.user.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
         // 1. 验证过期时间
-        let clock = Clock::get()?;
-        if clock.unix_timestamp > data.expiration {
-            return Err(ProgramError::InvalidArgument);
+        let clock = get_clock()?;
+        check_expiration(clock.as_ref(), data.expiration)?;
+
+        let slippage_limit = data.slippage_limit()?;
+        // 闪电交换的应付金额只有在输出确定时才有意义
+        if data.flash && !data.exact_out {
+            return Err(ProgramError::InvalidInstructionData);
         }
+        // 闪电交换的输入由回调偿还，与用户当前余额无关
+        if data.clamp_to_balance && (!data.exact_out || data.flash) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        // 全局紧急暂停时拒绝交换
+        GlobalState::check_not_paused(accounts.global_state)?;
 
         // 2. 加载配置和状态
         let config = Config::load(accounts.config)?;
@@ -99,81 +287,1677 @@ impl<'a> Swap<'a> {
             // 必须是 Initialized
             return Err(ProgramError::InvalidAccountData);
         }
+        if config.is_paused(FLAG_SWAPS_PAUSED) {
+            return Err(AmmError::OperationPaused.into());
+        }
+        config.check_swaps_enabled(clock.as_ref())?;
+        // exact-in 的滑点参数是最少输出，exact-out 是最多输入
+        if data.exact_out {
+            config.check_slippage_max(slippage_limit)?;
+        } else {
+            config.check_slippage_min(slippage_limit)?;
+        }
+
+        accounts.validate(&config)?;
+
+        // LP 持有者优惠：签名用户持有至少 lp_holder_min 的 LP 时使用 lp_holder_fee + volatility_fee
+        // (不高于常规手续费)
+        let mut remaining = accounts.remaining.iter();
+        let fee_bps = if data.lp_discount {
+            let user_lp_ata = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let mint_lp = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            config.check_mint_lp(accounts.config, mint_lp)?;
+
+            let user_lp = TokenAccount::from_account_view(user_lp_ata)?;
+            if user_lp.owner().ne(accounts.user.address()) || user_lp.mint().ne(mint_lp.address()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            // 未启用优惠 (lp_holder_min == 0) 或持仓不足时拒绝，而不是静默按常规手续费成交
+            if config.lp_holder_min() == 0 || user_lp.amount() < config.lp_holder_min() {
+                return Err(ProgramError::InvalidArgument);
+            }
+            config.lp_holder_fee_for(data.is_x)
+        } else {
+            config.effective_fee_for(data.is_x)
+        };
 
         // 3. 获取金库当前余额并计算交换
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_y)? };
-
-        let mut curve = ConstantProduct::init(
-            vault_x.amount(),
-            vault_y.amount(),
-            vault_x.amount(), // 这里 supply 通常用于初始价格，交换中主要看储备
-            config.fee(),
-            None,
-        )
-        .map_err(|_| ProgramError::ArithmeticOverflow)?;
+        //    同一交易中的其他指令只会在本指令之前或之后整体执行，不会插入到读取与转账之间，
+        //    且储备每次都从金库现读，不使用任何缓存。从这里到金库转账之间不发起其他 CPI
+        //    (交换记录与 tip 在转账之后处理)，只有 Recipient 模式下从用户账户转出手续费的
+        //    Token 转账，它不会动用金库，金库也只能由 Config PDA 签名转出。
+        //    因此曲线使用的储备就是转账时的真实储备。
+        //    闪电交换的回调发生在输出转出之后，回调结束后会按金库的实际余额重新校验
+        let (vault_x_amount, vault_y_amount) = unsafe {
+            Config::read_reserves_unchecked(accounts.config, accounts.vault_x, accounts.vault_y)?
+        };
+        let (reserve_x, reserve_y) = config.lp_reserves(vault_x_amount, vault_y_amount)?;
+
+        let (reserve_in, reserve_out) = if data.is_x {
+            (reserve_x, reserve_y)
+        } else {
+            (reserve_y, reserve_x)
+        };
 
-        let pair = if data.is_x {
-            LiquidityPair::X
+        // 曲线计算 (含手续费上限与各项不变量) 与报价共用 SwapPool::preview_swap
+        let pool = SwapPool {
+            reserve_x,
+            reserve_y,
+            fee_bps,
+            max_abs_fee: config.max_abs_fee(),
+        };
+        // 部分成交：期望输出超过可用流动性或 max_swap_bps 允许的规模时截断到最多能换出的数量，
+        // 滑点限制仍按截断后的输入检查
+        let amount = if data.exact_out && data.allow_partial {
+            data.amount
+                .min(pool.max_partial_output(data.is_x, config.max_swap_input(reserve_in))?)
         } else {
-            LiquidityPair::Y
+            data.amount
+        };
+        let swap_result = pool.preview_swap(data.is_x, data.exact_out, amount)?;
+        // 买单模式：余额不足以买到 amount 时，用全部余额按 exact-in 重新计算
+        let swap_result = if data.clamp_to_balance {
+            let user_in = if data.is_x {
+                accounts.user_x_ata
+            } else {
+                accounts.user_y_ata
+            };
+            let balance = crate::vault_amount(user_in)?;
+            if swap_result.deposit > balance {
+                // 部分成交的输入上限由余额保证，输出同样需要下限
+                let clamped = pool.preview_swap(data.is_x, false, balance)?;
+                if clamped.withdraw < data.clamped_min_output()? {
+                    return Err(AmmError::SlippageExceeded.into());
+                }
+                clamped
+            } else {
+                swap_result
+            }
+        } else {
+            swap_result
         };
-        let swap_result = curve
-            .swap(pair, data.amount, data.min)
-            .map_err(|_| ProgramError::InvalidArgument)?;
+
+        // 滑点检查针对实际成交的数量 (手续费上限生效之后)，在任何 CPI 之前只检查这一次
+        let slippage_exceeded = if data.exact_out {
+            swap_result.deposit > slippage_limit
+        } else {
+            swap_result.withdraw < slippage_limit
+        };
+        if slippage_exceeded {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+        // 限制单笔交换相对储备的规模，按含手续费的实际输入计算
+        config.check_max_swap(swap_result.deposit, reserve_in)?;
+        let remaining_out = reserve_out - swap_result.withdraw;
 
         // 4. 准备签名种子 (用于从金库转出)
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x_key = config.mint_x();
-        let mint_y_key = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x_key.as_ref()),
-            Seed::from(mint_y_key.as_ref()),
-            Seed::from(&bump),
-        ];
+        let config_seeds = ConfigSeeds::from_config(&config);
+        let config_seeds = config_seeds.as_seeds();
         let signer = Signer::from(&config_seeds);
 
-        // 5. 执行原子转账
-        if data.is_x {
-            // X -> Y: 用户发送 X 到 vault_x，金库发送 Y 到 user_y_ata
-            Transfer {
-                from: accounts.user_x_ata,
-                to: accounts.vault_x,
-                authority: accounts.user,
-                amount: swap_result.deposit,
+        // 5. 计算输出拆分：secondary + primary 恒等于 withdraw，即两者合计 100%
+        if data.split_bps > 10_000 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let secondary_amount =
+            (swap_result.withdraw as u128 * data.split_bps as u128 / 10_000) as u64;
+        let primary_amount = swap_result.withdraw - secondary_amount;
+
+        // 6. 执行原子转账
+        // X -> Y: 用户发送 X 到 vault_x，金库发送 Y 到 user_y_ata
+        // Y -> X: 用户发送 Y 到 vault_y，金库发送 X 到 user_x_ata
+        let (user_in, vault_in, vault_out, user_out) = if data.is_x {
+            (
+                accounts.user_x_ata,
+                accounts.vault_x,
+                accounts.vault_y,
+                accounts.user_y_ata,
+            )
+        } else {
+            (
+                accounts.user_y_ata,
+                accounts.vault_y,
+                accounts.vault_x,
+                accounts.user_x_ata,
+            )
+        };
+
+        // 用户的输入 ATA 与 (未指定第三方收款时的) 输出 ATA 必须属于签名用户，
+        // 否则闪电交换可以把别人的 ATA 当作 user_in / user_out
+        let user_owns = |ata: &AccountView| -> ProgramResult {
+            let token_account = unsafe { TokenAccount::from_account_view_unchecked(ata)? };
+            if token_account.owner().ne(accounts.user.address()) {
+                return Err(ProgramError::InvalidAccountData);
             }
-            .invoke()?;
+            Ok(())
+        };
+        user_owns(user_in)?;
+        if !data.to_recipient {
+            user_owns(user_out)?;
+        }
 
-            Transfer {
-                from: accounts.vault_y,
-                to: accounts.user_y_ata,
-                authority: accounts.config,
-                amount: swap_result.withdraw,
+        // 曲线算出的输出不能超过金库的实际余额 (例如储备数据过期或曲线出错)，
+        // 否则 Transfer CPI 会以难以理解的错误失败
+        let (vault_in_before, vault_out_amount) = if data.is_x {
+            (vault_x_amount, vault_y_amount)
+        } else {
+            (vault_y_amount, vault_x_amount)
+        };
+        Config::check_vault_covers(vault_out_amount, swap_result.withdraw)?;
+
+        // 主输出与拆分输出的收款账户都必须是输出代币的代币账户
+        let mint_out = if data.is_x {
+            config.mint_y()
+        } else {
+            config.mint_x()
+        };
+        let secondary_recipient = if secondary_amount > 0 {
+            let secondary_recipient = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if TokenAccount::from_account_view(secondary_recipient)?
+                .mint()
+                .ne(mint_out)
+            {
+                return Err(ProgramError::InvalidAccountData);
             }
-            .invoke_signed(&[signer])?;
+            Some(secondary_recipient)
+        } else {
+            None
+        };
+
+        // 手续费去向：LP 模式下全部输入留在金库；Recipient 模式下手续费部分直接转给 fee_recipient
+        let fee_to_recipient = config.fee_mode() == FeeMode::Recipient as u8 && swap_result.fee > 0;
+        if fee_to_recipient && data.flash {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let fee_recipient_ata = if fee_to_recipient {
+            let fee_recipient_ata = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            {
+                let fee_ata = TokenAccount::from_account_view(fee_recipient_ata)?;
+                let mint_in = if data.is_x {
+                    config.mint_x()
+                } else {
+                    config.mint_y()
+                };
+                if fee_ata.owner().ne(config.fee_recipient()) || fee_ata.mint().ne(mint_in) {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+            Some(fee_recipient_ata)
+        } else {
+            None
+        };
+
+        // 交换冷却：同一用户在 cooldown_slots 内只能交换一次
+        let cooldown_slots = config.cooldown_slots();
+        let user_swap_state = if cooldown_slots > 0 {
+            let slot = clock
+                .as_ref()
+                .map(|clock| clock.slot)
+                .ok_or(ProgramError::UnsupportedSysvar)?;
+            let user_swap_state = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let _system_program = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let create_bump = check_swap_cooldown(
+                user_swap_state,
+                accounts.config,
+                accounts.user,
+                slot,
+                cooldown_slots,
+            )?;
+            Some((user_swap_state, create_bump, slot))
+        } else {
+            None
+        };
+
+        // 第三方收款：只要求币种与输出代币一致，不限制 ATA 的 owner
+        let user_out = if data.to_recipient {
+            let recipient_ata = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let recipient = TokenAccount::from_account_view(recipient_ata)?;
+            if recipient.mint().ne(mint_out) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            recipient_ata
+        } else {
+            user_out
+        };
+
+        // 交换 tip：lamports 直接转入程序级 tip 金库 PDA，地址每次重新推导校验
+        let swap_tip = config.swap_tip();
+        let tip_treasury = if swap_tip > 0 {
+            let tip_treasury = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let (expected, _) =
+                Address::find_program_address(&[SWAP_TIP_TREASURY_SEED], &crate::ID);
+            if expected.ne(tip_treasury.address()) {
+                return Err(ProgramError::InvalidSeeds);
+            }
+            Some(tip_treasury)
         } else {
-            // Y -> X: 用户发送 Y 到 vault_y，金库发送 X 到 user_x_ata
+            None
+        };
+
+        // 准入代币：交换者必须持有 gate_mint 且余额大于 0。
+        // `user` 已在入口校验签名，因此不能借用其他持有者的身份通过准入
+        if config.gate_mint().ne(&Address::default()) {
+            let gate_ata = remaining.next().ok_or(AmmError::GateTokenRequired)?;
+            let gate = TokenAccount::from_account_view(gate_ata)?;
+            if gate.owner().ne(accounts.user.address())
+                || gate.mint().ne(config.gate_mint())
+                || gate.amount() == 0
+            {
+                return Err(AmmError::GateTokenRequired.into());
+            }
+        }
+
+        // 闪电交换：回调程序之后的剩余账户全部转交给回调。
+        // 整个 process 期间都持有 Config 的共享借用，Config 不能转交给回调
+        let flash_callback = if data.flash {
+            let callback_program = remaining.next().ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let callback_accounts = remaining.as_slice();
+            if callback_accounts
+                .iter()
+                .any(|account| account.address().eq(accounts.config.address()))
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+            Some((callback_program, callback_accounts))
+        } else {
+            None
+        };
+
+        // dry-run：校验已全部通过，返回计算结果后直接结束，不产生任何状态变化
+        if data.dry_run {
+            SwapQuote {
+                deposit: swap_result.deposit,
+                withdraw: swap_result.withdraw,
+                fee: swap_result.fee,
+            }
+            .set_return_data();
+            return Ok(());
+        }
+
+        // Protocol 模式下手续费留在金库，但记入协议收入，交换结束后再累加到 Config
+        let accrue_protocol_fee = config.fee_mode() == FeeMode::Protocol as u8;
+
+        // 普通交换先收输入再转出；闪电交换的输入由回调在输出转出之后偿还
+        if flash_callback.is_none() {
+            let vault_in_amount = if let Some(fee_recipient_ata) = fee_recipient_ata {
+                Transfer {
+                    from: user_in,
+                    to: fee_recipient_ata,
+                    authority: accounts.user,
+                    amount: swap_result.fee,
+                }
+                .invoke()?;
+                swap_result.deposit - swap_result.fee
+            } else {
+                swap_result.deposit
+            };
+
             Transfer {
-                from: accounts.user_y_ata,
-                to: accounts.vault_y,
+                from: user_in,
+                to: vault_in,
                 authority: accounts.user,
-                amount: swap_result.deposit,
+                amount: vault_in_amount,
             }
             .invoke()?;
+            Config::check_vault_received(vault_in, vault_in_before, vault_in_amount)?;
+        }
+
+        Transfer {
+            from: vault_out,
+            to: user_out,
+            authority: accounts.config,
+            amount: primary_amount,
+        }
+        .invoke_signed(std::slice::from_ref(&signer))?;
 
+        if let Some(secondary_recipient) = secondary_recipient {
             Transfer {
-                from: accounts.vault_x,
-                to: accounts.user_x_ata,
+                from: vault_out,
+                to: secondary_recipient,
                 authority: accounts.config,
-                amount: swap_result.withdraw,
+                amount: secondary_amount,
             }
             .invoke_signed(&[signer])?;
         }
 
+        if let Some((callback_program, callback_accounts)) = flash_callback {
+            invoke_flash_callback(
+                callback_program,
+                callback_accounts,
+                &FlashCallbackData {
+                    amount_out: swap_result.withdraw,
+                    amount_owed: swap_result.deposit,
+                    is_x: data.is_x,
+                },
+            )?;
+
+            // 回调必须已把应付的输入 (含手续费) 转入金库，且按金库的实际余额计算的恒定乘积没有减少
+            FlashSettlement {
+                vault_in_before,
+                vault_out_before: vault_out_amount,
+                reserve_in,
+                reserve_out,
+                amount_owed: swap_result.deposit,
+                protocol_fee: if accrue_protocol_fee {
+                    swap_result.fee
+                } else {
+                    0
+                },
+            }
+            .check(
+                crate::vault_amount(vault_in)?,
+                crate::vault_amount(vault_out)?,
+            )?;
+        }
+
+        // 与金库无关的 CPI 放在金库转账之后，读取储备到金库转账之间不插入任何其他 CPI。
+        // 须在写入 return data 之前完成，CPI 会清空 return data
+        if let Some((user_swap_state, create_bump, slot)) = user_swap_state {
+            record_swap_slot(
+                user_swap_state,
+                accounts.config,
+                accounts.user,
+                create_bump,
+                slot,
+            )?;
+        }
+
+        if let Some(tip_treasury) = tip_treasury {
+            SystemTransfer {
+                from: accounts.user,
+                to: tip_treasury,
+                lamports: swap_tip,
+            }
+            .invoke()?;
+        }
+
+        // exact-out：只转入了计算出的输入，max 与实际输入之间的差额从未离开用户账户
+        // (滑点已在任何 CPI 之前检查)。通过 return data 报告实际收取的输入，方便 UI 对账
+        if data.exact_out {
+            SwapQuote {
+                deposit: swap_result.deposit,
+                withdraw: swap_result.withdraw,
+                fee: swap_result.fee,
+            }
+            .set_return_data();
+        }
+
+        // 记录本次实现的手续费与交换之后属于 LP 的储备。
+        // 非 Lp 模式下手续费不计入 LP 储备 (Recipient 已转出，Protocol 记为协议收入)
+        if swap_result.fee > 0 {
+            let fee_to_lp = config.fee_mode() == FeeMode::Lp as u8;
+            let reserve_in_after = reserve_in
+                .checked_add(swap_result.deposit)
+                .and_then(|r| r.checked_sub(if fee_to_lp { 0 } else { swap_result.fee }))
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            let (reserve_x, reserve_y) = if data.is_x {
+                (reserve_in_after, remaining_out)
+            } else {
+                (remaining_out, reserve_in_after)
+            };
+            let (fee_x, fee_y) = if data.is_x {
+                (swap_result.fee, 0)
+            } else {
+                (0, swap_result.fee)
+            };
+            FeeAccrued {
+                fee_x,
+                fee_y,
+                fee_mode: config.fee_mode(),
+                reserve_x,
+                reserve_y,
+            }
+            .emit();
+        }
+
+        #[cfg(feature = "reserve-log")]
+        crate::events::ReservesUpdated {
+            vault_x: crate::vault_amount(accounts.vault_x)?,
+            vault_y: crate::vault_amount(accounts.vault_y)?,
+        }
+        .emit();
+
+        // CPI 期间 Config 只能处于共享借用状态，因此在全部转账完成后再以可变方式重新加载
+        drop(config);
+        let mut config = Config::load_mut(accounts.config)?;
+        if accrue_protocol_fee {
+            config.accrue_protocol_fee(data.is_x, swap_result.fee)?;
+        }
+        // TWAP 按交换前的储备累加 (即这段时间内池子实际的价格)
+        if let Some(clock) = &clock {
+            config.update_twap(clock.unix_timestamp, reserve_x, reserve_y)?;
+        }
+
         Ok(())
     }
 }
+
+/// 闪电交换回调：以 `FlashCallbackData` 为指令数据调用回调程序。
+///
+/// 账户按传入的顺序与可写权限转交，但全部去掉签名权限：回调程序由调用方任意指定，
+/// 转交用户的签名等于让它以用户身份动用用户的全部代币账户与 lamports。
+fn invoke_flash_callback(
+    callback_program: &AccountView,
+    callback_accounts: &[AccountView],
+    data: &FlashCallbackData,
+) -> ProgramResult {
+    let data = unsafe {
+        core::slice::from_raw_parts(
+            data as *const FlashCallbackData as *const u8,
+            size_of::<FlashCallbackData>(),
+        )
+    };
+    let metas: Vec<InstructionAccount> = callback_accounts
+        .iter()
+        .map(|account| InstructionAccount::new(account.address(), account.is_writable(), false))
+        .collect();
+    let account_refs: Vec<&AccountView> = callback_accounts.iter().collect();
+
+    invoke_with_slice(
+        &InstructionView {
+            program_id: callback_program.address(),
+            accounts: &metas,
+            data,
+        },
+        &account_refs,
+    )
+}
+
+/// 校验交换冷却。记录 PDA 尚未创建时校验其地址并返回创建所需的 bump，已存在时返回 `None`。
+fn check_swap_cooldown(
+    user_swap_state: &AccountView,
+    config: &AccountView,
+    user: &AccountView,
+    slot: u64,
+    cooldown_slots: u64,
+) -> Result<Option<u8>, ProgramError> {
+    if user_swap_state.data_len() == 0 {
+        let (expected, bump) = Address::find_program_address(
+            &[
+                USER_SWAP_SEED,
+                config.address().as_ref(),
+                user.address().as_ref(),
+            ],
+            &crate::ID,
+        );
+        if expected.ne(user_swap_state.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        return Ok(Some(bump));
+    }
+
+    let state = UserSwapState::load(user_swap_state)?;
+    // 属于本程序且长度正确并不能说明是这个用户的记录，必须用保存的 bump 重新推导地址
+    let expected = Address::create_program_address(
+        &[
+            USER_SWAP_SEED,
+            config.address().as_ref(),
+            user.address().as_ref(),
+            &state.bump(),
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+    if expected.ne(user_swap_state.address()) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if slot.saturating_sub(state.last_swap_slot()) < cooldown_slots {
+        return Err(AmmError::SwapCooldown.into());
+    }
+    Ok(None)
+}
+
+/// 记录本次交换的 slot；首次交换时先由 (已签名的) 用户出资创建记录 PDA
+fn record_swap_slot(
+    user_swap_state: &AccountView,
+    config: &AccountView,
+    user: &AccountView,
+    create_bump: Option<u8>,
+    slot: u64,
+) -> ProgramResult {
+    if let Some(bump) = create_bump {
+        let bump = [bump];
+        let seeds = user_swap_seeds(config.address(), user.address(), &bump);
+        // 地址可预测，可能已被他人预先转入 lamports
+        create_program_account(
+            user_swap_state,
+            user,
+            UserSwapState::LEN,
+            Rent::get()?.try_minimum_balance(UserSwapState::LEN)?,
+            &crate::ID,
+            &[Signer::from(&seeds)],
+        )?;
+
+        UserSwapState::load_mut(user_swap_state)?.set_bump(bump);
+    }
+
+    UserSwapState::load_mut(user_swap_state)?.set_last_swap_slot(slot);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "clock-free"))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, empty_account, mint_account, token_account};
+    use constant_product_curve::SwapResult;
+
+    const USER: Address = Address::new_from_array([6; 32]);
+
+    /// 用户 (已签名) 持有 `balance_x` X 与 `balance_y` Y，按 `data` 在 `pool` 中交换
+    fn swap(
+        pool: &mut TestPool,
+        data: SwapInstructionData,
+        balance_x: u64,
+        balance_y: u64,
+        remaining: &mut [TestAccount],
+    ) -> ProgramResult {
+        let mut user_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &USER,
+            balance_x,
+        );
+        let mut user_y = token_account(
+            Address::new_from_array([8; 32]),
+            &pool.mint_y,
+            &USER,
+            balance_y,
+        );
+        swap_with(
+            pool,
+            empty_account(USER).signer().writable(),
+            &mut user_x,
+            &mut user_y,
+            data,
+            remaining,
+        )
+    }
+
+    /// 以任意的 `user` 账户与用户 ATA 交换
+    fn swap_with(
+        pool: &mut TestPool,
+        mut user: TestAccount,
+        user_x: &mut TestAccount,
+        user_y: &mut TestAccount,
+        data: SwapInstructionData,
+        remaining: &mut [TestAccount],
+    ) -> ProgramResult {
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let mut accounts = vec![
+            user.view(),
+            user_x.view(),
+            user_y.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+            pool.config.view(),
+            token_program.view(),
+            pool.global_state.view(),
+        ];
+        accounts.extend(remaining.iter_mut().map(TestAccount::view));
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                &data as *const SwapInstructionData as *const u8,
+                size_of::<SwapInstructionData>(),
+            )
+        };
+        Swap::try_from((data, &accounts[..]))?.process()
+    }
+
+    fn data(is_x: bool, exact_out: bool, amount: u64, min: u64) -> SwapInstructionData {
+        SwapInstructionData {
+            is_x,
+            amount,
+            min,
+            expiration: i64::MAX,
+            split_bps: 0,
+            exact_out,
+            dry_run: false,
+            min_is_bps: false,
+            min_bps: 0,
+            lp_discount: false,
+            flash: false,
+            to_recipient: false,
+            allow_partial: false,
+            clamp_to_balance: false,
+        }
+    }
+
+    fn pool() -> TestPool {
+        let mut pool = TestPool::new(1_000_000, 2_000_000, 1_000_000);
+        pool.update_config(|config| config.set_fee(30).unwrap());
+        pool
+    }
+
+    fn preview(is_x: bool, exact_out: bool, amount: u64) -> SwapResult {
+        SwapPool {
+            reserve_x: 1_000_000,
+            reserve_y: 2_000_000,
+            fee_bps: 30,
+            max_abs_fee: 0,
+        }
+        .preview_swap(is_x, exact_out, amount)
+        .unwrap()
+    }
+
+    // 主机上的 Transfer 是空操作，金库余额不变，因此能通过滑点检查的交换都会在
+    // 转入金库后的到账校验处以 TransferNotReceived 结束
+
+    #[test]
+    fn exact_in_swap_pays_out_exactly_the_preview() {
+        let expected = preview(true, false, 10_000).withdraw;
+        assert_eq!(
+            swap(
+                &mut pool(),
+                data(true, false, 10_000, expected),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool(),
+                data(true, false, 10_000, expected + 1),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+    }
+
+    /// 买入 10_000 X，但用户只有 15_000 Y (不够买满)
+    fn clamped(min_is_bps: bool, min_bps: u16) -> SwapInstructionData {
+        SwapInstructionData {
+            clamp_to_balance: true,
+            min_is_bps,
+            min_bps,
+            ..data(false, true, 10_000, 15_000)
+        }
+    }
+
+    #[test]
+    fn clamped_swap_enforces_a_minimum_output() {
+        let out = preview(false, false, 15_000).withdraw;
+        assert!(out < 10_000);
+        // 最少输出 10_000 * (10_000 - min_bps) / 10_000 = 10_000 - min_bps
+        let min_bps = (10_000 - out) as u16;
+        assert_eq!(
+            swap(&mut pool(), clamped(true, min_bps), 0, 15_000, &mut []),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(&mut pool(), clamped(true, min_bps - 1), 0, 15_000, &mut []),
+            Err(AmmError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn clamped_swap_without_a_minimum_is_rejected() {
+        assert_eq!(
+            swap(&mut pool(), clamped(false, 0), 0, 15_000, &mut []),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        // 余额足够时不会部分成交，也就不需要下限
+        let deposit = preview(false, true, 10_000).deposit;
+        assert_eq!(
+            swap(
+                &mut pool(),
+                SwapInstructionData {
+                    min: deposit,
+                    ..clamped(false, 0)
+                },
+                0,
+                deposit,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn secondary_recipient_must_hold_the_output_mint() {
+        let mut pool = pool();
+        let split = SwapInstructionData {
+            split_bps: 5_000,
+            ..data(true, false, 10_000, 0)
+        };
+        let owner = Address::new_from_array([12; 32]);
+        let mut wrong_mint = [token_account(
+            Address::new_from_array([13; 32]),
+            &pool.mint_x,
+            &owner,
+            0,
+        )];
+        assert_eq!(
+            swap(&mut pool, split, 10_000, 0, &mut wrong_mint),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut not_a_token_account = [TestAccount::new(
+            Address::new_from_array([13; 32]),
+            crate::ID,
+            wrong_mint[0].data(),
+        )];
+        assert_eq!(
+            swap(&mut pool, split, 10_000, 0, &mut not_a_token_account),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut output_mint = [token_account(
+            Address::new_from_array([13; 32]),
+            &pool.mint_y,
+            &owner,
+            0,
+        )];
+        assert_eq!(
+            swap(&mut pool, split, 10_000, 0, &mut output_mint),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn swap_uses_the_vault_balances_at_execution() {
+        // 同一交易中靠前的指令改变了储备：Y 金库从 2_000_000 变为 1_500_000
+        let mut pool = pool();
+        let stale = preview(true, false, 10_000).withdraw;
+        let vault_y = pool.vault_y.view().address().clone();
+        let config = pool.config.view().address().clone();
+        pool.vault_y = token_account(vault_y, &pool.mint_y, &config, 1_500_000).writable();
+
+        let fresh = SwapPool {
+            reserve_x: 1_000_000,
+            reserve_y: 1_500_000,
+            fee_bps: 30,
+            max_abs_fee: 0,
+        }
+        .preview_swap(true, false, 10_000)
+        .unwrap()
+        .withdraw;
+        assert!(fresh < stale);
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, stale),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, fresh),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn exact_out_swap_charges_exactly_the_preview() {
+        let expected = preview(false, true, 10_000).deposit;
+        assert_eq!(
+            swap(
+                &mut pool(),
+                data(false, true, 10_000, expected),
+                0,
+                expected,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool(),
+                data(false, true, 10_000, expected - 1),
+                0,
+                expected,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn bool_fields_other_than_zero_or_one_are_rejected() {
+        let data = data(true, false, 10_000, 0);
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                &data as *const SwapInstructionData as *const u8,
+                size_of::<SwapInstructionData>(),
+            )
+        };
+        assert!(SwapInstructionData::try_from(bytes).is_ok());
+        for offset in SwapInstructionData::BOOL_OFFSETS {
+            let mut bytes = bytes.to_vec();
+            bytes[offset] = 2;
+            assert_eq!(
+                SwapInstructionData::try_from(&bytes[..]).err(),
+                Some(ProgramError::InvalidInstructionData),
+                "offset {offset}"
+            );
+        }
+    }
+
+    #[test]
+    fn partial_fill_stops_at_the_max_swap_size() {
+        // 默认 max_swap_bps = 30%：买入几乎全部 Y 时最多只能投入 300_000 X
+        let buy_all = SwapInstructionData {
+            allow_partial: true,
+            ..data(true, true, 1_900_000, u64::MAX)
+        };
+        let filled = preview(true, false, 300_000).withdraw;
+        assert_eq!(
+            preview(true, true, filled).deposit,
+            300_000,
+            "截断后的输出恰好需要 max_swap_bps 允许的输入"
+        );
+        assert_eq!(
+            swap(&mut pool(), buy_all, u64::MAX, 0, &mut []),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool(),
+                data(true, true, filled + 1, u64::MAX),
+                u64::MAX,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SwapTooLarge.into())
+        );
+    }
+
+    #[test]
+    fn unsigned_flash_swap_is_rejected() {
+        // 受害者 USER 没有签名：攻击者以 USER 的名义闪电交换，输出转入自己的 ATA 再由回调偿还
+        let mut pool = pool();
+        let flash = SwapInstructionData {
+            flash: true,
+            to_recipient: true,
+            ..data(true, true, 10_000, u64::MAX)
+        };
+        let attacker = Address::new_from_array([12; 32]);
+        let mut user_x = token_account(Address::new_from_array([7; 32]), &pool.mint_x, &USER, 0);
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &USER, 0);
+        let mut remaining = [
+            token_account(
+                Address::new_from_array([13; 32]),
+                &pool.mint_y,
+                &attacker,
+                0,
+            ),
+            empty_account(Address::new_from_array([14; 32])),
+        ];
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                empty_account(USER).writable(),
+                &mut user_x,
+                &mut user_y,
+                flash,
+                &mut remaining
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn user_atas_must_belong_to_the_user() {
+        let mut pool = pool();
+        let other = Address::new_from_array([12; 32]);
+        let mut others_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &other,
+            10_000,
+        );
+        let mut users_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &USER,
+            10_000,
+        );
+        let mut others_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &other, 0);
+        let mut users_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &USER, 0);
+        let user = || empty_account(USER).signer().writable();
+
+        // 输入 ATA 属于别人
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                user(),
+                &mut others_x,
+                &mut users_y,
+                data(true, false, 10_000, 0),
+                &mut []
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        // 输出 ATA 属于别人
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                user(),
+                &mut users_x,
+                &mut others_y,
+                data(true, false, 10_000, 0),
+                &mut []
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        // 指定第三方收款时固定位置的输出 ATA 不接收输出，不要求属于用户
+        let to_recipient = SwapInstructionData {
+            to_recipient: true,
+            ..data(true, false, 10_000, 0)
+        };
+        let mut recipient = [token_account(
+            Address::new_from_array([13; 32]),
+            &pool.mint_y,
+            &other,
+            0,
+        )];
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                user(),
+                &mut users_x,
+                &mut others_y,
+                to_recipient,
+                &mut recipient
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn gate_requires_the_holder_to_sign() {
+        let mut pool = pool();
+        let gate_mint = Address::new_from_array([20; 32]);
+        pool.update_config(|config| config.set_gate_mint(gate_mint.clone()));
+        let gate_ata = || {
+            [token_account(
+                Address::new_from_array([21; 32]),
+                &gate_mint,
+                &USER,
+                1,
+            )]
+        };
+
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, 0),
+                10_000,
+                0,
+                &mut gate_ata()
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+
+        // 没有签名的 `user` 即使持有准入代币也不能交换
+        let mut user_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &USER,
+            10_000,
+        );
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &USER, 0);
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                empty_account(USER).writable(),
+                &mut user_x,
+                &mut user_y,
+                data(true, false, 10_000, 0),
+                &mut gate_ata()
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn lp_discount_adds_the_volatility_fee_and_requires_the_holder_to_sign() {
+        let mut pool = pool();
+        pool.update_config(|config| {
+            config.set_volatility_fee(10).unwrap();
+            config.set_lp_holder_fee(0, 1).unwrap();
+        });
+        let mint_lp = pool.mint_lp.view().address().clone();
+        let lp_accounts = || {
+            [
+                token_account(Address::new_from_array([22; 32]), &mint_lp, &USER, 1),
+                mint_account(mint_lp.clone(), 1_000_000, crate::LP_DECIMALS),
+            ]
+        };
+        let discounted = |fee_bps| {
+            let min = SwapPool {
+                reserve_x: 1_000_000,
+                reserve_y: 2_000_000,
+                fee_bps,
+                max_abs_fee: 0,
+            }
+            .preview_swap(true, false, 10_000)
+            .unwrap()
+            .withdraw;
+            SwapInstructionData {
+                lp_discount: true,
+                ..data(true, false, 10_000, min)
+            }
+        };
+
+        // 优惠费率为 lp_holder_fee + volatility_fee = 10，而不是 0
+        assert_eq!(
+            swap(&mut pool, discounted(10), 10_000, 0, &mut lp_accounts()),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(&mut pool, discounted(0), 10_000, 0, &mut lp_accounts()),
+            Err(AmmError::SlippageExceeded.into())
+        );
+
+        // 借用 LP 持有者的身份 (不签名) 不能获得优惠
+        let mut user_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &USER,
+            10_000,
+        );
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &USER, 0);
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                empty_account(USER).writable(),
+                &mut user_x,
+                &mut user_y,
+                discounted(10),
+                &mut lp_accounts()
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn strangers_cannot_start_a_victims_cooldown() {
+        // 冷却记录以 `user` 为键：不签名就以受害者的名义交换会刷新它的 last_swap_slot
+        let mut pool = pool();
+        pool.update_config(|config| config.set_cooldown_slots(100));
+        let mut user_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &USER,
+            10_000,
+        );
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &USER, 0);
+        let (user_swap_state, _) = Address::find_program_address(
+            &[
+                USER_SWAP_SEED,
+                pool.config.view().address().as_ref(),
+                USER.as_ref(),
+            ],
+            &crate::ID,
+        );
+        let mut remaining = [
+            empty_account(user_swap_state).writable().lamports(1),
+            TestAccount::new(pinocchio_system::ID, Address::default(), &[]),
+        ];
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                empty_account(USER).writable(),
+                &mut user_x,
+                &mut user_y,
+                data(true, false, 10_000, 0),
+                &mut remaining
+            ),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn swap_cannot_drain_the_output_reserve() {
+        // 关闭 max_swap_bps 后由储备下限兜底：Y 金库至少保留交换前的 1% (20_000)
+        let mut pool = pool();
+        pool.update_config(|config| config.set_max_swap_bps(0).unwrap());
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 200_000_000, 0),
+                200_000_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::ReserveBelowMinimum.into())
+        );
+        assert!(2_000_000 - preview(true, false, 98_000_000).withdraw >= 20_000);
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 98_000_000, 0),
+                98_000_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn recipient_mode_sends_the_fee_to_the_recipients_input_ata() {
+        let mut pool = pool();
+        let recipient = Address::new_from_array([12; 32]);
+        pool.update_config(|config| {
+            config.set_fee_mode(FeeMode::Recipient as u8).unwrap();
+            config.set_fee_recipient(recipient.clone());
+        });
+        let fee_ata = |mint: &Address, owner: &Address| {
+            [token_account(
+                Address::new_from_array([13; 32]),
+                mint,
+                owner,
+                0,
+            )]
+        };
+        let swap_x = data(true, false, 10_000, 0);
+
+        assert_eq!(
+            swap(&mut pool, swap_x, 10_000, 0, &mut []),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        // 收款 ATA 必须属于 fee_recipient，且是输入代币 (X) 的账户
+        let mint_x = pool.mint_x.clone();
+        let mint_y = pool.mint_y.clone();
+        assert_eq!(
+            swap(&mut pool, swap_x, 10_000, 0, &mut fee_ata(&mint_x, &USER)),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                swap_x,
+                10_000,
+                0,
+                &mut fee_ata(&mint_y, &recipient)
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                swap_x,
+                10_000,
+                0,
+                &mut fee_ata(&mint_x, &recipient)
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn user_atas_must_match_the_pool_mints() {
+        // 客户端把 X / Y 两个 ATA 的顺序传反 (或 is_x 与 ATA 不一致) 时拒绝，而不是反向成交
+        let mut pool = pool();
+        let mut user_x = token_account(
+            Address::new_from_array([7; 32]),
+            &pool.mint_x,
+            &USER,
+            10_000,
+        );
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &USER, 0);
+        assert_eq!(
+            swap_with(
+                &mut pool,
+                empty_account(USER).signer().writable(),
+                &mut user_y,
+                &mut user_x,
+                data(true, false, 10_000, 0),
+                &mut []
+            ),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+
+    #[test]
+    fn pausing_deposits_leaves_swaps_open() {
+        let mut pool = pool();
+        pool.update_config(|config| config.set_flags(crate::FLAG_DEPOSITS_PAUSED).unwrap());
+        assert_eq!(
+            swap(&mut pool, data(true, false, 10_000, 0), 10_000, 0, &mut []),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        pool.update_config(|config| config.set_flags(FLAG_SWAPS_PAUSED).unwrap());
+        assert_eq!(
+            swap(&mut pool, data(true, false, 10_000, 0), 10_000, 0, &mut []),
+            Err(AmmError::OperationPaused.into())
+        );
+    }
+
+    #[test]
+    fn global_pause_blocks_swaps() {
+        let mut pool = pool();
+        pool.global_state = crate::test_utils::global_state_account(true);
+        assert_eq!(
+            swap(&mut pool, data(true, false, 10_000, 0), 10_000, 0, &mut []),
+            Err(AmmError::GloballyPaused.into())
+        );
+    }
+
+    #[test]
+    fn volatility_fee_is_added_to_the_swap_fee() {
+        let with_fee = |fee_bps| {
+            SwapPool {
+                reserve_x: 1_000_000,
+                reserve_y: 2_000_000,
+                fee_bps,
+                max_abs_fee: 0,
+            }
+            .preview_swap(true, false, 10_000)
+            .unwrap()
+            .withdraw
+        };
+        let mut pool = pool();
+        pool.update_config(|config| config.set_volatility_fee(20).unwrap());
+        // 实际费率为 30 + 20 = 50，按 30 预期的输出已经拿不到
+        assert!(with_fee(50) < with_fee(30));
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, with_fee(30)),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, with_fee(50)),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn dry_run_validates_but_changes_nothing() {
+        let expected = preview(true, false, 10_000).withdraw;
+        let dry_run = |min| SwapInstructionData {
+            dry_run: true,
+            ..data(true, false, 10_000, min)
+        };
+        let mut pool = pool();
+        let config = pool.config.data().to_vec();
+        let (vault_x, vault_y) = (pool.vault_x.data().to_vec(), pool.vault_y.data().to_vec());
+
+        // 与真实交换相同的滑点校验
+        assert_eq!(
+            swap(&mut pool, dry_run(expected + 1), 10_000, 0, &mut []),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        // 通过校验后在转账之前返回
+        assert_eq!(
+            swap(&mut pool, dry_run(expected), 10_000, 0, &mut []),
+            Ok(())
+        );
+        assert_eq!(pool.config.data(), &config[..]);
+        assert_eq!(pool.vault_x.data(), &vault_x[..]);
+        assert_eq!(pool.vault_y.data(), &vault_y[..]);
+    }
+
+    #[test]
+    fn vaults_must_be_owned_by_the_config() {
+        let mut pool = pool();
+        pool.vault_x = token_account([4; 32].into(), &pool.mint_x, &USER, 1_000_000).writable();
+        assert_eq!(
+            swap(&mut pool, data(true, false, 10_000, 0), 10_000, 0, &mut []),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
+
+    #[test]
+    fn a_swap_eaten_by_the_fee_reports_zero_output() {
+        let mut pool = pool();
+        pool.update_config(|config| config.set_fee(9_999).unwrap());
+        assert_eq!(
+            swap(&mut pool, data(true, false, 100, 0), 100, 0, &mut []),
+            Err(AmmError::ZeroOutput.into())
+        );
+        // 输入足够大时即使 99.99% 的费率也有输出
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 100_000, 0),
+                100_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn bps_slippage_is_taken_from_the_clients_quote() {
+        let bps = |exact_out, amount, quote, min_bps| SwapInstructionData {
+            min_is_bps: true,
+            min_bps,
+            ..data(true, exact_out, amount, quote)
+        };
+        // exact-in：报价 2 * out，允许 50% 滑点时最少输出恰好为 out
+        let out = preview(true, false, 10_000).withdraw;
+        assert_eq!(
+            swap(
+                &mut pool(),
+                bps(false, 10_000, 2 * out, 5_000),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool(),
+                bps(false, 10_000, 2 * out, 4_999),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+
+        // exact-out：最大输入 = 报价 * (1 + min_bps)
+        let input = preview(true, true, 10_000).deposit;
+        assert_eq!(
+            swap(&mut pool(), bps(true, 10_000, input, 0), input, 0, &mut []),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool(),
+                bps(true, 10_000, input - 1, 0),
+                input,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool(),
+                bps(true, 10_000, input - 1, 100),
+                input,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+
+        assert_eq!(
+            swap(
+                &mut pool(),
+                bps(false, 10_000, out, 10_001),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn swaps_without_a_limit_are_rejected_under_the_slippage_policy() {
+        let mut pool = pool();
+        pool.update_config(|config| config.set_require_slippage(true));
+        assert_eq!(
+            swap(&mut pool, data(true, false, 10_000, 0), 10_000, 0, &mut []),
+            Err(AmmError::SlippageProtectionRequired.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, true, 10_000, u64::MAX),
+                u64::MAX,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageProtectionRequired.into())
+        );
+        assert_eq!(
+            swap(&mut pool, data(true, false, 10_000, 1), 10_000, 0, &mut []),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn exact_in_swaps_are_capped_at_max_swap_bps_of_the_input_reserve() {
+        use crate::{SetMaxSwapBpsInstructionData, instructions::AdminUpdate};
+        let sell = |pool: &mut TestPool, amount| {
+            swap(pool, data(true, false, amount, 0), amount, 0, &mut [])
+        };
+        // 默认 30%：输入储备 1_000_000 X 时最多投入 300_000
+        let mut pool = pool();
+        assert_eq!(
+            sell(&mut pool, 300_000),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(sell(&mut pool, 300_001), Err(AmmError::SwapTooLarge.into()));
+
+        let limit = |max_swap_bps| SetMaxSwapBpsInstructionData { max_swap_bps };
+        pool.update_config(|config| limit(1_000).apply(config).unwrap());
+        assert_eq!(
+            sell(&mut pool, 100_000),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(sell(&mut pool, 100_001), Err(AmmError::SwapTooLarge.into()));
+
+        pool.update_config(|config| {
+            assert_eq!(
+                limit(10_001).apply(config),
+                Err(ProgramError::InvalidAccountData)
+            );
+            // 0 表示不限制
+            limit(0).apply(config).unwrap()
+        });
+        assert_eq!(
+            sell(&mut pool, 500_000),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn recipient_ata_must_hold_the_output_mint() {
+        let to_recipient = SwapInstructionData {
+            to_recipient: true,
+            ..data(true, false, 10_000, 0)
+        };
+        let recipient =
+            |mint: &Address| token_account([13; 32].into(), mint, &[12; 32].into(), 0).writable();
+        let mut pool = pool();
+        assert_eq!(
+            swap(&mut pool, to_recipient, 10_000, 0, &mut []),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        let mint_x = pool.mint_x.clone();
+        assert_eq!(
+            swap(
+                &mut pool,
+                to_recipient,
+                10_000,
+                0,
+                &mut [recipient(&mint_x)]
+            ),
+            Err(ProgramError::InvalidAccountData)
+        );
+        let mint_y = pool.mint_y.clone();
+        assert_eq!(
+            swap(
+                &mut pool,
+                to_recipient,
+                10_000,
+                0,
+                &mut [recipient(&mint_y)]
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn a_swap_tip_must_go_to_the_tip_treasury_pda() {
+        use crate::{SWAP_TIP_TREASURY_SEED, SetSwapTipInstructionData, instructions::AdminUpdate};
+        let (treasury, _) = Address::find_program_address(&[SWAP_TIP_TREASURY_SEED], &crate::ID);
+        let mut pool = pool();
+        pool.update_config(|config| {
+            SetSwapTipInstructionData { swap_tip: 5_000 }
+                .apply(config)
+                .unwrap()
+        });
+        let data = data(true, false, 10_000, 0);
+        assert_eq!(
+            swap(&mut pool, data, 10_000, 0, &mut []),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data,
+                10_000,
+                0,
+                &mut [empty_account([12; 32].into()).writable()]
+            ),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data,
+                10_000,
+                0,
+                &mut [empty_account(treasury).writable()]
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+    }
+
+    #[test]
+    fn gate_rejects_a_missing_empty_foreign_or_wrong_mint_account() {
+        let mut pool = pool();
+        let gate_mint = Address::new_from_array([20; 32]);
+        pool.update_config(|config| config.set_gate_mint(gate_mint.clone()));
+        let gate_ata = |mint: &Address, owner: &Address, amount| {
+            token_account(Address::new_from_array([21; 32]), mint, owner, amount)
+        };
+        let data = data(true, false, 10_000, 0);
+        assert_eq!(
+            swap(&mut pool, data, 10_000, 0, &mut []),
+            Err(AmmError::GateTokenRequired.into())
+        );
+        for mut gate in [
+            gate_ata(&gate_mint, &USER, 0),
+            gate_ata(&gate_mint, &Address::new_from_array([12; 32]), 1),
+            gate_ata(&pool.mint_x.clone(), &USER, 1),
+        ] {
+            assert_eq!(
+                swap(&mut pool, data, 10_000, 0, core::slice::from_mut(&mut gate)),
+                Err(AmmError::GateTokenRequired.into())
+            );
+        }
+    }
+
+    #[test]
+    fn each_direction_pays_its_own_base_fee() {
+        let with_fee = |is_x, fee_bps| {
+            SwapPool {
+                reserve_x: 1_000_000,
+                reserve_y: 2_000_000,
+                fee_bps,
+                max_abs_fee: 0,
+            }
+            .preview_swap(is_x, false, 10_000)
+            .unwrap()
+            .withdraw
+        };
+        let mut pool = pool();
+        pool.update_config(|config| config.set_direction_fees(100, 0).unwrap());
+        // X -> Y 按 100 bps 收费，按基础费率 30 预期的输出拿不到
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, with_fee(true, 30)),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(true, false, 10_000, with_fee(true, 100)),
+                10_000,
+                0,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        // Y -> X 未单独设置，仍按 30 bps
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(false, false, 10_000, with_fee(false, 30)),
+                0,
+                10_000,
+                &mut []
+            ),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap(
+                &mut pool,
+                data(false, false, 10_000, with_fee(false, 30) + 1),
+                0,
+                10_000,
+                &mut []
+            ),
+            Err(AmmError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn config_in_a_token_account_slot_is_a_duplicate() {
+        for slot in 0..4 {
+            let mut pool = pool();
+            let config = pool.config.view().address().clone();
+            let as_config = |mint: &Address, owner: &Address| {
+                token_account(config.clone(), mint, owner, 10_000).writable()
+            };
+            let mut user_x = token_account([7; 32].into(), &pool.mint_x, &USER, 10_000);
+            let mut user_y = token_account([8; 32].into(), &pool.mint_y, &USER, 0);
+            match slot {
+                0 => user_x = as_config(&pool.mint_x, &USER),
+                1 => user_y = as_config(&pool.mint_y, &USER),
+                2 => pool.vault_x = as_config(&pool.mint_x, &config),
+                _ => pool.vault_y = as_config(&pool.mint_y, &config),
+            }
+            assert_eq!(
+                swap_with(
+                    &mut pool,
+                    empty_account(USER).signer().writable(),
+                    &mut user_x,
+                    &mut user_y,
+                    data(true, false, 10_000, 0),
+                    &mut []
+                ),
+                Err(AmmError::DuplicateAccount.into()),
+                "slot {slot}"
+            );
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use pinocchio::{AccountView, ProgramResult, error::ProgramError};
+
+use crate::{AmmError, Config, Swap, SwapAccounts, SwapInstructionData};
+
+/// 路径中最多可以包含的 mint 数量 (即最多 `MAX_SWAP_PATH_LEN - 1` 跳)。
+/// 数据布局按该上限固定，路由上线后客户端无需改变指令格式。
+pub const MAX_SWAP_PATH_LEN: usize = 4;
+
+/*
+    按 mint 路径描述的 exact-in 交换，为后续的多池路由预留稳定的客户端 ABI。
+
+    目前只支持单跳：`path = [mint_in, mint_out]`，两端必须分别是本池子的两种代币，
+    交换方向由路径推导，之后与 Swap 的 exact-in 完全相同。多跳路径返回 `UnsupportedRoute`。
+    账户布局与 Swap 相同。
+*/
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SwapExactInWithPathInstructionData {
+    pub amount: u64,
+    /// 最少输出
+    pub min: u64,
+    /// 订单的最后有效时间 (unix 秒，包含边界)，与 Swap 相同
+    pub expiration: i64,
+    /// `path` 中有效的 mint 数量，其余位置被忽略
+    pub path_len: u8,
+    pub path: [[u8; 32]; MAX_SWAP_PATH_LEN],
+}
+
+impl<'a> TryFrom<&'a [u8]> for SwapExactInWithPathInstructionData {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+pub struct SwapExactInWithPath<'a> {
+    pub accounts: SwapAccounts<'a>,
+    pub instruction_data: SwapExactInWithPathInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SwapExactInWithPath<'a> {
+    type Error = ProgramError;
+
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SwapAccounts::try_from(accounts)?;
+        let instruction_data = SwapExactInWithPathInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SwapExactInWithPath<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &23;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let data = self.instruction_data;
+
+        // 1. 路径长度：至少包含输入与输出两个 mint，超过两个即为多跳
+        let path_len = data.path_len as usize;
+        if !(2..=MAX_SWAP_PATH_LEN).contains(&path_len) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        if path_len > 2 {
+            return Err(AmmError::UnsupportedRoute.into());
+        }
+
+        // 2. 路径两端必须是本池子的两种代币，由此确定交换方向
+        let [mint_in, mint_out, ..] = data.path;
+        let is_x = {
+            let config = Config::load(self.accounts.config)?;
+            let (mint_x, mint_y) = (config.mint_x().as_ref(), config.mint_y().as_ref());
+            if mint_in == mint_x && mint_out == mint_y {
+                true
+            } else if mint_in == mint_y && mint_out == mint_x {
+                false
+            } else {
+                return Err(ProgramError::InvalidArgument);
+            }
+        };
+
+        // 3. 单跳交换与 Swap 的 exact-in 相同
+        let accounts = &self.accounts;
+        Swap {
+            accounts: SwapAccounts {
+                user: accounts.user,
+                user_x_ata: accounts.user_x_ata,
+                user_y_ata: accounts.user_y_ata,
+                vault_x: accounts.vault_x,
+                vault_y: accounts.vault_y,
+                config: accounts.config,
+                token_program: accounts.token_program,
+                global_state: accounts.global_state,
+                remaining: accounts.remaining,
+            },
+            instruction_data: SwapInstructionData {
+                is_x,
+                amount: data.amount,
+                min: data.min,
+                expiration: data.expiration,
+                split_bps: 0,
+                exact_out: false,
+                dry_run: false,
+                min_is_bps: false,
+                min_bps: 0,
+                lp_discount: false,
+                flash: false,
+                to_recipient: false,
+                allow_partial: false,
+                clamp_to_balance: false,
+            },
+        }
+        .process()
+    }
+}
+
+#[cfg(all(test, feature = "clock-free"))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, empty_account, token_account};
+    use pinocchio::Address;
+
+    /// 持有足够 X 与 Y 的用户按 `path` 交换 10_000，最少输出 `min`
+    fn swap_path(path: &[[u8; 32]], min: u64) -> ProgramResult {
+        let mut pool = TestPool::new(1_000_000, 2_000_000, 1_000_000);
+        let user = Address::new_from_array([6; 32]);
+        let mut user_account = empty_account(user.clone()).signer().writable();
+        let mut user_x = token_account([7; 32].into(), &pool.mint_x, &user, 10_000);
+        let mut user_y = token_account([8; 32].into(), &pool.mint_y, &user, 10_000);
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let accounts = [
+            user_account.view(),
+            user_x.view(),
+            user_y.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+            pool.config.view(),
+            token_program.view(),
+            pool.global_state.view(),
+        ];
+
+        let mut data = SwapExactInWithPathInstructionData {
+            amount: 10_000,
+            min,
+            expiration: i64::MAX,
+            path_len: path.len() as u8,
+            path: [[0; 32]; MAX_SWAP_PATH_LEN],
+        };
+        // 超长的路径只写入能放下的部分，path_len 仍是完整长度
+        let len = path.len().min(MAX_SWAP_PATH_LEN);
+        data.path[..len].copy_from_slice(&path[..len]);
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                &data as *const SwapExactInWithPathInstructionData as *const u8,
+                size_of::<SwapExactInWithPathInstructionData>(),
+            )
+        };
+        SwapExactInWithPath::try_from((data, &accounts[..]))?.process()
+    }
+
+    const X: [u8; 32] = [2; 32];
+    const Y: [u8; 32] = [3; 32];
+
+    #[test]
+    fn single_hop_path_picks_the_direction_from_its_endpoints() {
+        // 10_000 X 约换 19_700 Y，10_000 Y 只能换约 4_950 X
+        assert_eq!(
+            swap_path(&[X, Y], 19_000),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            swap_path(&[Y, X], 19_000),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        assert_eq!(
+            swap_path(&[Y, X], 4_900),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        for path in [[X, X], [X, [4; 32]], [[4; 32], Y]] {
+            assert_eq!(swap_path(&path, 0), Err(ProgramError::InvalidArgument));
+        }
+    }
+
+    #[test]
+    fn multi_hop_and_malformed_paths_are_rejected() {
+        assert_eq!(
+            swap_path(&[X, [4; 32], Y], 0),
+            Err(AmmError::UnsupportedRoute.into())
+        );
+        assert_eq!(
+            swap_path(&[X, Y, X, Y], 0),
+            Err(AmmError::UnsupportedRoute.into())
+        );
+        for path in [&[X][..], &[X, Y, X, Y, X][..]] {
+            assert_eq!(
+                swap_path(path, 0),
+                Err(ProgramError::InvalidInstructionData)
+            );
+        }
+    }
+}
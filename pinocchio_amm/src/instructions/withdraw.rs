@@ -1,16 +1,13 @@
-use constant_product_curve::ConstantProduct;
-use pinocchio::{
-    AccountView, ProgramResult,
-    cpi::{Seed, Signer},
-    error::ProgramError,
-    sysvars::{Sysvar, clock::Clock},
-};
+use pinocchio::{AccountView, ProgramResult, cpi::Signer, error::ProgramError};
 use pinocchio_token::{
     instructions::{Burn, Transfer},
     state::{Mint, TokenAccount},
 };
 
-use crate::Config;
+use crate::{
+    AmmError, AmmState, Config, FLAG_WITHDRAWALS_PAUSED, check_distinct_vaults, check_expiration,
+    check_token_program, curve::withdraw_amounts, get_clock, seeds::ConfigSeeds,
+};
 
 /*
     根据用户希望 burn 的 LP 数量，提取 mint_x 和 mint_y 代币。
@@ -18,6 +15,9 @@ use crate::Config;
     计算提取金额，并检查金额是否不低于用户指定的 mint_x 和 mint_y。
 
     从用户的 ata 中销毁相应数量的 mint_lp。
+
+    LP 锁定期 (Config.lock_duration) 不在这里检查：锁定期内的 LP 留在托管账户中 (见 UserDepositState)，
+    用户手里的 LP 都可以直接取款。
 */
 
 pub struct WithdrawAccounts<'a> {
@@ -32,12 +32,29 @@ pub struct WithdrawAccounts<'a> {
     pub token_program: &'a AccountView,
 }
 
+impl WithdrawAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 9;
+
+    /// 一次性校验与池子绑定的账户，返回遇到的第一个错误：
+    /// Token 程序、LP mint、金库币种。金库的 authority 在读取储备时校验。
+    pub fn validate(&self, config: &Config) -> ProgramResult {
+        check_token_program(self.token_program)?;
+        config.check_mint_lp(self.config, self.mint_lp)?;
+        config.check_vault_mints(self.vault_x, self.vault_y)
+    }
+}
+
 impl<'a> TryFrom<&'a [AccountView]> for WithdrawAccounts<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
         let mut iter = accounts.iter();
-        Ok(Self {
+        let accounts = Self {
             user: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             mint_lp: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             vault_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
@@ -47,7 +64,9 @@ impl<'a> TryFrom<&'a [AccountView]> for WithdrawAccounts<'a> {
             user_lp_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
             token_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
-        })
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        Ok(accounts)
     }
 }
 
@@ -57,6 +76,8 @@ pub struct WithdrawInstructionData {
     pub amount: u64,
     pub min_x: u64,
     pub min_y: u64,
+    /// 订单的最后有效时间 (unix 秒，包含边界)：与 Clock sysvar 的 `unix_timestamp` 比较，
+    /// `unix_timestamp == expiration` 时仍可执行，大于时拒绝；`clock-free` 构建中被忽略
     pub expiration: i64,
 }
 
@@ -99,42 +120,50 @@ impl<'a> Withdraw<'a> {
         let data = &self.instruction_data;
 
         // 1. 过期检查
-        let clock = Clock::get()?;
-        if clock.unix_timestamp > data.expiration {
-            return Err(ProgramError::InvalidArgument);
-        }
+        let clock = get_clock()?;
+        check_expiration(clock.as_ref(), data.expiration)?;
 
-        // 2. 加载状态并检查 (Withdraw 要求非 Disabled)
+        // 2. 加载状态并检查 (Withdraw 允许 Initialized 与 WithdrawOnly)
+        // 账户已由本程序创建但尚未 set_inner 时数据全为 0，state 读出为 Uninitialized，同样拒绝
         let config = Config::load(accounts.config)?;
-        // 假设 0: Uninitialized, 1: Initialized, 2: Disabled
-        if config.state() == 2 {
+        if config.state() != AmmState::Initialized as u8
+            && config.state() != AmmState::WithdrawOnly as u8
+        {
             return Err(ProgramError::InvalidAccountData);
         }
+        if config.is_paused(FLAG_WITHDRAWALS_PAUSED) {
+            return Err(AmmError::OperationPaused.into());
+        }
+        accounts.validate(&config)?;
+        config.check_slippage_min(data.min_x)?;
+        config.check_slippage_min(data.min_y)?;
+
+        // 用户持有的 LP 必须足够，否则 Burn 会以 Token 程序的错误失败，难以定位原因
+        if TokenAccount::from_account_view(accounts.user_lp_ata)?.amount() < data.amount {
+            solana_program_log::log("Withdraw: LP balance is lower than the requested amount");
+            return Err(ProgramError::InsufficientFunds);
+        }
 
         // 3. 反序列化代币信息
         let mint_lp = unsafe { Mint::from_account_view_unchecked(accounts.mint_lp)? };
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(accounts.vault_y)? };
+        let (vault_x_amount, vault_y_amount) = unsafe {
+            Config::read_reserves_unchecked(accounts.config, accounts.vault_x, accounts.vault_y)?
+        };
+        let (reserve_x, reserve_y) = config.lp_reserves(vault_x_amount, vault_y_amount)?;
 
         // 4. 计算应退还的 X, Y 数量
         let (x, y) = if mint_lp.supply() == data.amount {
-            // 全额提取：直接取走所有余额，防止舍入误差留下“尘埃”
-            (vault_x.amount(), vault_y.amount())
+            // 全额提取：直接取走属于 LP 的全部储备，而不是按比例计算，防止舍入误差留下“尘埃”。
+            // 储备由金库余额减去协议手续费得到 (包括直接转入金库的代币)，
+            // 因此提取之后金库中恰好只剩尚未提取的协议手续费，由 WithdrawProtocolFees 清空
+            (reserve_x, reserve_y)
         } else {
-            let amounts = ConstantProduct::xy_withdraw_amounts_from_l(
-                vault_x.amount(),
-                vault_y.amount(),
-                mint_lp.supply(),
-                data.amount,
-                6, // LP decimals
-            )
-            .map_err(|_| ProgramError::ArithmeticOverflow)?;
-            (amounts.x, amounts.y)
+            withdraw_amounts(reserve_x, reserve_y, mint_lp.supply(), data.amount)?
         };
 
         // 5. 滑点检查
         if x < data.min_x || y < data.min_y {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::SlippageExceeded.into());
         }
 
         // 6. 销毁用户的 LP 代币 (用户签名)
@@ -147,18 +176,8 @@ impl<'a> Withdraw<'a> {
         .invoke()?;
 
         // 7. 构造 Config PDA 签名以从金库转账
-        let seed_binding = config.seed().to_le_bytes();
-        let mint_x_key = config.mint_x();
-        let mint_y_key = config.mint_y();
-        let bump = config.config_bump();
-
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_binding),
-            Seed::from(mint_x_key.as_ref()),
-            Seed::from(mint_y_key.as_ref()),
-            Seed::from(&bump),
-        ];
+        let config_seeds = ConfigSeeds::from_config(&config);
+        let config_seeds = config_seeds.as_seeds();
         let signer = Signer::from(&config_seeds);
 
         // 8. 转移 Token X 和 Y (Config PDA 签名)
@@ -182,3 +201,177 @@ impl<'a> Withdraw<'a> {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "clock-free"))]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, empty_account, token_account};
+    use pinocchio::Address;
+
+    /// 持有 `lp_balance` LP 的用户按 `data` 从 `pool` 取款
+    pub(crate) fn withdraw(
+        pool: &mut TestPool,
+        data: WithdrawInstructionData,
+        lp_balance: u64,
+    ) -> ProgramResult {
+        let user = Address::new_from_array([6; 32]);
+        let mint_lp = pool.mint_lp.view().address().clone();
+        let mut user_account = empty_account(user.clone()).signer();
+        let mut user_x = token_account(Address::new_from_array([7; 32]), &pool.mint_x, &user, 0);
+        let mut user_y = token_account(Address::new_from_array([8; 32]), &pool.mint_y, &user, 0);
+        let mut user_lp = token_account(
+            Address::new_from_array([10; 32]),
+            &mint_lp,
+            &user,
+            lp_balance,
+        );
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let accounts = [
+            user_account.view(),
+            pool.mint_lp.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+            user_x.view(),
+            user_y.view(),
+            user_lp.view(),
+            pool.config.view(),
+            token_program.view(),
+        ];
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                &data as *const WithdrawInstructionData as *const u8,
+                size_of::<WithdrawInstructionData>(),
+            )
+        };
+        Withdraw::try_from((data, &accounts[..]))?.process()
+    }
+
+    fn data(amount: u64, min_x: u64, min_y: u64) -> WithdrawInstructionData {
+        WithdrawInstructionData {
+            amount,
+            min_x,
+            min_y,
+            expiration: i64::MAX,
+        }
+    }
+
+    #[test]
+    fn lp_in_the_users_hands_is_never_locked() {
+        // 锁定期内的 LP 在托管账户里，Withdraw 不再需要存款记录，也不检查锁定期
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.update_config(|config| config.set_lock_duration(3_600));
+        assert_eq!(withdraw(&mut pool, data(100, 1_000, 2_000), 100), Ok(()));
+    }
+
+    #[test]
+    fn paused_withdrawals_are_rejected() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.update_config(|config| config.set_flags(FLAG_WITHDRAWALS_PAUSED).unwrap());
+        assert_eq!(
+            withdraw(&mut pool, data(100, 1_000, 2_000), 100),
+            Err(AmmError::OperationPaused.into())
+        );
+    }
+
+    #[test]
+    fn vaults_must_be_owned_by_the_config() {
+        let attacker = Address::new_from_array([11; 32]);
+        for replace_x in [true, false] {
+            let mut pool = TestPool::new(10_000, 20_000, 1_000);
+            if replace_x {
+                pool.vault_x =
+                    token_account([4; 32].into(), &pool.mint_x, &attacker, 10_000).writable();
+            } else {
+                pool.vault_y =
+                    token_account([5; 32].into(), &pool.mint_y, &attacker, 20_000).writable();
+            }
+            assert_eq!(
+                withdraw(&mut pool, data(100, 1_000, 2_000), 100),
+                Err(ProgramError::InvalidAccountOwner)
+            );
+        }
+    }
+
+    #[test]
+    fn withdrawing_more_lp_than_held_is_rejected() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        assert_eq!(
+            withdraw(&mut pool, data(100, 1_000, 2_000), 99),
+            Err(ProgramError::InsufficientFunds)
+        );
+        assert_eq!(withdraw(&mut pool, data(100, 1_000, 2_000), 100), Ok(()));
+    }
+
+    #[test]
+    fn zero_minimums_are_rejected_only_under_the_slippage_policy() {
+        use crate::{SetMinSlippageProtectionInstructionData, instructions::AdminUpdate};
+        let policy = |required| SetMinSlippageProtectionInstructionData { required };
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        assert_eq!(withdraw(&mut pool, data(100, 0, 0), 100), Ok(()));
+
+        pool.update_config(|config| policy(1).apply(config).unwrap());
+        for (min_x, min_y) in [(0, 2_000), (1_000, 0)] {
+            assert_eq!(
+                withdraw(&mut pool, data(100, min_x, min_y), 100),
+                Err(AmmError::SlippageProtectionRequired.into())
+            );
+        }
+        assert_eq!(withdraw(&mut pool, data(100, 1, 1), 100), Ok(()));
+
+        pool.update_config(|config| policy(0).apply(config).unwrap());
+        assert_eq!(withdraw(&mut pool, data(100, 0, 0), 100), Ok(()));
+    }
+
+    #[test]
+    fn an_owned_but_uninitialized_config_is_rejected() {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        // CreateAccount 之后、set_inner 之前的 Config：归本程序所有但数据全零
+        let address = pool.config.view().address().clone();
+        pool.config = crate::test_utils::config_account(address, |_| {}).writable();
+        pool.update_config(|config| {
+            assert!(matches!(
+                AmmState::try_from(config.state()),
+                Ok(AmmState::Uninitialized)
+            ))
+        });
+        assert_eq!(
+            withdraw(&mut pool, data(100, 1_000, 2_000), 100),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn the_last_lp_takes_every_token_but_the_protocol_fees() {
+        // 金库里有直接转入的零头和未提取的协议手续费 (1 X / 3 Y)
+        let mut pool = TestPool::new(10_001, 20_003, 3);
+        pool.update_config(|config| config.set_protocol_fees(1, 3));
+        assert_eq!(withdraw(&mut pool, data(3, 10_000, 20_000), 3), Ok(()));
+        assert_eq!(
+            withdraw(&mut pool, data(3, 10_001, 20_000), 3),
+            Err(AmmError::SlippageExceeded.into())
+        );
+        // 非全额提取按比例向下取整：2 / 3 的 10_000 X 为 6_666
+        assert_eq!(withdraw(&mut pool, data(2, 6_666, 13_333), 2), Ok(()));
+        assert_eq!(
+            withdraw(&mut pool, data(2, 6_667, 13_333), 2),
+            Err(AmmError::SlippageExceeded.into())
+        );
+    }
+
+    #[test]
+    fn the_same_vault_cannot_be_passed_for_both_sides() {
+        let mut pool = TestPool::new(10_000, 10_000, 1_000);
+        // 两侧读到同一余额，取款会从同一个金库按两份储备转出
+        pool.vault_y = token_account(
+            [4; 32].into(),
+            &pool.mint_x,
+            pool.config.view().address(),
+            10_000,
+        )
+        .writable();
+        assert_eq!(
+            withdraw(&mut pool, data(100, 1_000, 1_000), 100),
+            Err(AmmError::DuplicateAccount.into())
+        );
+    }
+}
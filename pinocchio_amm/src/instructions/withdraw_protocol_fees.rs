@@ -0,0 +1,214 @@
+use pinocchio::{AccountView, ProgramResult, cpi::Signer, error::ProgramError};
+use pinocchio_token::{instructions::Transfer, state::TokenAccount};
+
+use crate::{Config, check_distinct_vaults, seeds::ConfigSeeds};
+
+/*
+    管理员提取已累计的协议手续费 (FeeMode::Protocol)。
+
+    只转出 Config 中记录的 protocol_fee_x / protocol_fee_y，LP 的资金不受影响，转出后计数清零。
+*/
+pub struct WithdrawProtocolFeesAccounts<'a> {
+    pub authority: &'a AccountView,
+    pub config: &'a AccountView,
+    pub vault_x: &'a AccountView,
+    pub vault_y: &'a AccountView,
+    pub recipient_x_ata: &'a AccountView,
+    pub recipient_y_ata: &'a AccountView,
+    pub token_program: &'a AccountView,
+}
+
+impl WithdrawProtocolFeesAccounts<'_> {
+    /// 指令所需的固定账户数量。多余的账户 (例如 CPI 所需的程序账户) 允许传入并被忽略。
+    pub const LEN: usize = 7;
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for WithdrawProtocolFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < Self::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut iter = accounts.iter();
+        let accounts = Self {
+            authority: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            config: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_x: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            vault_y: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            recipient_x_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            recipient_y_ata: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            token_program: iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+        };
+        check_distinct_vaults(accounts.vault_x, accounts.vault_y)?;
+        Ok(accounts)
+    }
+}
+
+pub struct WithdrawProtocolFees<'a> {
+    pub accounts: WithdrawProtocolFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for WithdrawProtocolFees<'a> {
+    type Error = ProgramError;
+
+    fn try_from((_data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawProtocolFeesAccounts::try_from(accounts)?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> WithdrawProtocolFees<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+
+        // 1. 校验管理员，读取并清零累计的协议手续费
+        //    CPI 期间不能持有 Config 的可变借用，因此先把签名所需的数据拷贝出来
+        let (fee_x, fee_y, config_seeds) = {
+            let mut config = Config::load_mut(accounts.config)?;
+            config.check_authority(accounts.authority)?;
+
+            // 2. 金库必须归 Config PDA 所有，收款 ATA 必须属于 fee_recipient 且币种匹配
+            {
+                let vault_x = TokenAccount::from_account_view(accounts.vault_x)?;
+                let vault_y = TokenAccount::from_account_view(accounts.vault_y)?;
+                Config::check_vaults(accounts.config, &vault_x, &vault_y)?;
+
+                let recipient_x = TokenAccount::from_account_view(accounts.recipient_x_ata)?;
+                let recipient_y = TokenAccount::from_account_view(accounts.recipient_y_ata)?;
+                if recipient_x.owner().ne(config.fee_recipient())
+                    || recipient_y.owner().ne(config.fee_recipient())
+                    || recipient_x.mint().ne(config.mint_x())
+                    || recipient_y.mint().ne(config.mint_y())
+                {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+
+            let fees = (config.protocol_fee_x(), config.protocol_fee_y());
+            config.set_protocol_fees(0, 0);
+            (fees.0, fees.1, ConfigSeeds::from_config(&config))
+        };
+
+        // 3. Config PDA 签名，从金库转出协议手续费
+        let config_seeds = config_seeds.as_seeds();
+        let signer = Signer::from(&config_seeds);
+
+        if fee_x > 0 {
+            Transfer {
+                from: accounts.vault_x,
+                to: accounts.recipient_x_ata,
+                authority: accounts.config,
+                amount: fee_x,
+            }
+            .invoke_signed(std::slice::from_ref(&signer))?;
+        }
+
+        if fee_y > 0 {
+            Transfer {
+                from: accounts.vault_y,
+                to: accounts.recipient_y_ata,
+                authority: accounts.config,
+                amount: fee_y,
+            }
+            .invoke_signed(&[signer])?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        FeeMode,
+        test_utils::{TestAccount, TestPool, empty_account, token_account},
+    };
+    use pinocchio::Address;
+
+    const RECIPIENT: Address = Address::new_from_array([12; 32]);
+
+    /// 累计了 100 X / 200 Y 协议手续费的 Protocol 模式池子
+    fn pool() -> TestPool {
+        let mut pool = TestPool::new(10_000, 20_000, 1_000);
+        pool.update_config(|config| {
+            config.set_fee_mode(FeeMode::Protocol as u8).unwrap();
+            config.set_fee_recipient(RECIPIENT);
+            config.set_protocol_fees(100, 200);
+        });
+        pool
+    }
+
+    fn withdraw_fees(
+        pool: &mut TestPool,
+        authority: Address,
+        recipient: &Address,
+    ) -> ProgramResult {
+        let mut authority = empty_account(authority).signer();
+        let mut recipient_x = token_account(
+            Address::new_from_array([13; 32]),
+            &pool.mint_x,
+            recipient,
+            0,
+        );
+        let mut recipient_y = token_account(
+            Address::new_from_array([14; 32]),
+            &pool.mint_y,
+            recipient,
+            0,
+        );
+        let mut token_program = TestAccount::new(pinocchio_token::ID, Address::default(), &[]);
+        let accounts = [
+            authority.view(),
+            pool.config.view(),
+            pool.vault_x.view(),
+            pool.vault_y.view(),
+            recipient_x.view(),
+            recipient_y.view(),
+            token_program.view(),
+        ];
+        WithdrawProtocolFees::try_from((&[][..], &accounts[..]))?.process()
+    }
+
+    fn protocol_fees(pool: &mut TestPool) -> (u64, u64) {
+        let view = pool.config.view();
+        let config = Config::load(&view).unwrap();
+        (config.protocol_fee_x(), config.protocol_fee_y())
+    }
+
+    #[test]
+    fn accrued_fees_are_excluded_from_lp_reserves() {
+        let mut pool = pool();
+        let view = pool.config.view();
+        assert_eq!(
+            Config::load(&view).unwrap().lp_reserves(10_000, 20_000),
+            Ok((9_900, 19_800))
+        );
+    }
+
+    #[test]
+    fn only_the_authority_withdraws_to_the_fee_recipient() {
+        let mut pool = pool();
+        assert_eq!(
+            withdraw_fees(&mut pool, Address::new_from_array([6; 32]), &RECIPIENT),
+            Err(ProgramError::IncorrectAuthority)
+        );
+        assert_eq!(
+            withdraw_fees(&mut pool, TestPool::AUTHORITY, &TestPool::AUTHORITY),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(protocol_fees(&mut pool), (100, 200));
+
+        // 转出之后计数清零，再次提取不会重复转出
+        assert_eq!(
+            withdraw_fees(&mut pool, TestPool::AUTHORITY, &RECIPIENT),
+            Ok(())
+        );
+        assert_eq!(protocol_fees(&mut pool), (0, 0));
+    }
+}
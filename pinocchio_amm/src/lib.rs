@@ -9,21 +9,174 @@ pub use instructions::*;
 pub mod state;
 pub use state::*;
 
+pub mod error;
+pub use error::*;
+
+pub mod ret;
+
+pub mod events;
+
+pub mod curve;
+
+pub mod seeds;
+
+// 部分辅助函数只被按 feature 启用的测试使用
+#[cfg(test)]
+#[allow(dead_code)]
+mod test_utils;
+
+#[cfg(feature = "client")]
+pub mod client;
+
 // 22222222222222222222222222222222222222222222
 declare_id!("22222222222222222222222222222222222222222222");
 
+/// 全部指令的判别符，下标即判别符的值。新增指令时取下一个序号并追加到末尾，
+/// 下面的编译期断言保证判别符互不重复且从 0 开始连续分配。
+const DISCRIMINATORS: [u8; 34] = [
+    *Initialize::DISCRIMINATOR,
+    *Deposit::DISCRIMINATOR,
+    *Withdraw::DISCRIMINATOR,
+    *Swap::DISCRIMINATOR,
+    *SetFeeMode::DISCRIMINATOR,
+    *InitializeMany::DISCRIMINATOR,
+    *WithdrawProtocolFees::DISCRIMINATOR,
+    *SetFlags::DISCRIMINATOR,
+    *CompleteInitialize::DISCRIMINATOR,
+    *GlobalPause::DISCRIMINATOR,
+    *SetBaseFee::DISCRIMINATOR,
+    *SetVolatilityFee::DISCRIMINATOR,
+    *GetSpotPrice::DISCRIMINATOR,
+    *InitializeWithLiquidity::DISCRIMINATOR,
+    *SetCooldown::DISCRIMINATOR,
+    *SetTwapInterval::DISCRIMINATOR,
+    *SetState::DISCRIMINATOR,
+    *HealthCheck::DISCRIMINATOR,
+    *SetMaxAbsFee::DISCRIMINATOR,
+    *SetLpHolderFee::DISCRIMINATOR,
+    *Migrate::DISCRIMINATOR,
+    *SetMinSlippageProtection::DISCRIMINATOR,
+    *GetSeed::DISCRIMINATOR,
+    *SwapExactInWithPath::DISCRIMINATOR,
+    *SetMaxSwapBps::DISCRIMINATOR,
+    *GetState::DISCRIMINATOR,
+    *SetDepositFee::DISCRIMINATOR,
+    *GetVersion::DISCRIMINATOR,
+    *SetSwapTip::DISCRIMINATOR,
+    *SetGate::DISCRIMINATOR,
+    *SetDirectionFees::DISCRIMINATOR,
+    *SetLockDuration::DISCRIMINATOR,
+    *ForceDisable::DISCRIMINATOR,
+    *ClaimLockedLp::DISCRIMINATOR,
+];
+
+const _: () = {
+    let mut i = 0;
+    while i < DISCRIMINATORS.len() {
+        assert!(DISCRIMINATORS[i] as usize == i);
+        i += 1;
+    }
+};
+
 fn process_instruction(
     _program_id: &Address,
     accounts: &[AccountView],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    match instruction_data.split_first() {
+    // 开启 `cu-log` 时在指令前后各打印一次剩余 CU，两者之差即为本条指令的消耗；关闭时完全编译掉
+    #[cfg(feature = "cu-log")]
+    solana_program_log::log_compute_units();
+
+    let result = match instruction_data.split_first() {
         Some((Initialize::DISCRIMINATOR, data)) => {
             Initialize::try_from((data, accounts))?.process()
         }
         Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((data, accounts))?.process(),
         Some((Withdraw::DISCRIMINATOR, data)) => Withdraw::try_from((data, accounts))?.process(),
         Some((Swap::DISCRIMINATOR, data)) => Swap::try_from((data, accounts))?.process(),
+        Some((SetFeeMode::DISCRIMINATOR, data)) => {
+            SetFeeMode::try_from((data, accounts))?.process()
+        }
+        Some((InitializeMany::DISCRIMINATOR, data)) => {
+            InitializeMany::try_from((data, accounts))?.process()
+        }
+        Some((WithdrawProtocolFees::DISCRIMINATOR, data)) => {
+            WithdrawProtocolFees::try_from((data, accounts))?.process()
+        }
+        Some((SetFlags::DISCRIMINATOR, data)) => SetFlags::try_from((data, accounts))?.process(),
+        Some((CompleteInitialize::DISCRIMINATOR, data)) => {
+            CompleteInitialize::try_from((data, accounts))?.process()
+        }
+        Some((GlobalPause::DISCRIMINATOR, data)) => {
+            GlobalPause::try_from((data, accounts))?.process()
+        }
+        Some((SetBaseFee::DISCRIMINATOR, data)) => {
+            SetBaseFee::try_from((data, accounts))?.process()
+        }
+        Some((SetVolatilityFee::DISCRIMINATOR, data)) => {
+            SetVolatilityFee::try_from((data, accounts))?.process()
+        }
+        Some((GetSpotPrice::DISCRIMINATOR, data)) => {
+            GetSpotPrice::try_from((data, accounts))?.process()
+        }
+        Some((InitializeWithLiquidity::DISCRIMINATOR, data)) => {
+            InitializeWithLiquidity::try_from((data, accounts))?.process()
+        }
+        Some((SetCooldown::DISCRIMINATOR, data)) => {
+            SetCooldown::try_from((data, accounts))?.process()
+        }
+        Some((SetTwapInterval::DISCRIMINATOR, data)) => {
+            SetTwapInterval::try_from((data, accounts))?.process()
+        }
+        Some((SetState::DISCRIMINATOR, data)) => SetState::try_from((data, accounts))?.process(),
+        Some((HealthCheck::DISCRIMINATOR, data)) => {
+            HealthCheck::try_from((data, accounts))?.process()
+        }
+        Some((SetMaxAbsFee::DISCRIMINATOR, data)) => {
+            SetMaxAbsFee::try_from((data, accounts))?.process()
+        }
+        Some((SetLpHolderFee::DISCRIMINATOR, data)) => {
+            SetLpHolderFee::try_from((data, accounts))?.process()
+        }
+        Some((Migrate::DISCRIMINATOR, data)) => Migrate::try_from((data, accounts))?.process(),
+        Some((SetMinSlippageProtection::DISCRIMINATOR, data)) => {
+            SetMinSlippageProtection::try_from((data, accounts))?.process()
+        }
+        Some((GetSeed::DISCRIMINATOR, data)) => GetSeed::try_from((data, accounts))?.process(),
+        Some((SwapExactInWithPath::DISCRIMINATOR, data)) => {
+            SwapExactInWithPath::try_from((data, accounts))?.process()
+        }
+        Some((SetMaxSwapBps::DISCRIMINATOR, data)) => {
+            SetMaxSwapBps::try_from((data, accounts))?.process()
+        }
+        Some((GetState::DISCRIMINATOR, data)) => GetState::try_from((data, accounts))?.process(),
+        Some((SetDepositFee::DISCRIMINATOR, data)) => {
+            SetDepositFee::try_from((data, accounts))?.process()
+        }
+        Some((GetVersion::DISCRIMINATOR, data)) => {
+            GetVersion::try_from((data, accounts))?.process()
+        }
+        Some((SetSwapTip::DISCRIMINATOR, data)) => {
+            SetSwapTip::try_from((data, accounts))?.process()
+        }
+        Some((SetGate::DISCRIMINATOR, data)) => SetGate::try_from((data, accounts))?.process(),
+        Some((SetDirectionFees::DISCRIMINATOR, data)) => {
+            SetDirectionFees::try_from((data, accounts))?.process()
+        }
+        Some((SetLockDuration::DISCRIMINATOR, data)) => {
+            SetLockDuration::try_from((data, accounts))?.process()
+        }
+        Some((ForceDisable::DISCRIMINATOR, data)) => {
+            ForceDisable::try_from((data, accounts))?.process()
+        }
+        Some((ClaimLockedLp::DISCRIMINATOR, data)) => {
+            ClaimLockedLp::try_from((data, accounts))?.process()
+        }
         _ => Err(ProgramError::InvalidInstructionData),
-    }
+    };
+
+    #[cfg(feature = "cu-log")]
+    solana_program_log::log_compute_units();
+
+    result
 }
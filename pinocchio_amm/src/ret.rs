@@ -0,0 +1,289 @@
+//! 只读指令通过 return data 返回的数据格式。
+//!
+//! 链上写入方与客户端读取方共用同一份定义，避免各指令各自拼接字节。
+//! 所有结构体都是 `#[repr(C, packed)]`、无填充，多字节字段按小端序存放。
+
+use pinocchio::{cpi::set_return_data, error::ProgramError};
+
+/// 可以写入 / 读出 return data 的响应结构体。
+///
+/// # Safety
+///
+/// 实现者必须是 `#[repr(C, packed)]` 的纯数据结构体 (无填充、无指针、任意字节均为合法值)。
+pub unsafe trait ReturnData: Copy + Sized {
+    const LEN: usize = size_of::<Self>();
+
+    /// 按内存布局返回原始字节
+    #[inline(always)]
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self as *const Self as *const u8, Self::LEN) }
+    }
+
+    /// 从原始字节解析，长度必须完全一致
+    #[inline(always)]
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        if bytes.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(unsafe { (bytes.as_ptr() as *const Self).read_unaligned() })
+    }
+
+    /// 写入当前指令的 return data
+    #[inline(always)]
+    fn set_return_data(&self) {
+        set_return_data(self.as_bytes());
+    }
+}
+
+/// 交换报价：输入、输出与手续费 (Quote / dry-run)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SwapQuote {
+    pub deposit: u64,
+    pub withdraw: u64,
+    pub fee: u64,
+}
+
+unsafe impl ReturnData for SwapQuote {}
+
+/// 池子概况 (PoolInfo)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct PoolInfo {
+    pub state: u8,
+    pub fee: u16,
+    pub reserve_x: u64,
+    pub reserve_y: u64,
+    pub lp_supply: u64,
+}
+
+unsafe impl ReturnData for PoolInfo {}
+
+/// 赎回价值：销毁给定数量 LP 可取回的 X / Y (RedeemValue)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct RedeemValue {
+    pub x: u64,
+    pub y: u64,
+}
+
+unsafe impl ReturnData for RedeemValue {}
+
+/// 双向边际价格，Q64.64 定点数，不含手续费 (GetSpotPrice)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SpotPrice {
+    /// 1 X 可兑换的 Y
+    pub x_in_y: u128,
+    /// 1 Y 可兑换的 X
+    pub y_in_x: u128,
+}
+
+unsafe impl ReturnData for SpotPrice {}
+
+/// 池子一致性检查的结果 (HealthCheck)，写入 `HealthReport::status`
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    Healthy = 0,
+    /// Config.state 不是合法的 `AmmState`，或池子尚未初始化
+    InvalidState = 1,
+    /// 金库的代币 authority 不是 Config PDA，或币种与 Config 不符
+    VaultMismatch = 2,
+    /// LP mint 的 mint_authority 不是 LP 权限 PDA
+    LpMintMismatch = 3,
+    /// 记录的协议手续费超过金库余额
+    ProtocolFeesExceedVaults = 4,
+    /// LP 供应量大于 0，但某一侧储备为 0
+    SupplyWithoutReserves = 5,
+}
+
+/// 一致性检查结果 (HealthCheck)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct HealthReport {
+    pub status: u8,
+}
+
+unsafe impl ReturnData for HealthReport {}
+
+/// Config 的关键字段 (GetConfig)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct ConfigInfo {
+    pub state: u8,
+    pub seed: u64,
+    pub authority: [u8; 32],
+    pub mint_x: [u8; 32],
+    pub mint_y: [u8; 32],
+    pub fee: u16,
+    pub config_bump: u8,
+}
+
+unsafe impl ReturnData for ConfigInfo {}
+
+/// Config PDA 的全部推导参数 (GetSeed)，客户端只凭 Config 地址即可找回 `seed`
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SeedInfo {
+    pub seed: u64,
+    pub mint_x: [u8; 32],
+    pub mint_y: [u8; 32],
+    pub config_bump: u8,
+}
+
+unsafe impl ReturnData for SeedInfo {}
+
+/// Config 的状态字节 `AmmState` (GetState)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct StateInfo {
+    pub state: u8,
+}
+
+unsafe impl ReturnData for StateInfo {}
+
+/// 部署的程序版本与 Config 账户长度 (GetVersion)
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct VersionInfo {
+    /// `PROGRAM_VERSION`：`major << 16 | minor << 8 | patch`
+    pub program_version: u32,
+    /// 本版本程序期望的 `Config::LEN`
+    pub config_len: u16,
+}
+
+unsafe impl ReturnData for VersionInfo {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 编码后再解码，字节必须完全一致；长度多 1 或少 1 都被拒绝
+    fn round_trip<T: ReturnData>(value: T) -> T {
+        let bytes = value.as_bytes();
+        assert_eq!(bytes.len(), T::LEN);
+        let decoded = T::from_bytes(bytes).unwrap();
+        assert_eq!(decoded.as_bytes(), bytes);
+
+        let mut longer = bytes.to_vec();
+        longer.push(0);
+        assert!(T::from_bytes(&longer).is_err());
+        assert!(T::from_bytes(&bytes[..T::LEN - 1]).is_err());
+        decoded
+    }
+
+    #[test]
+    fn swap_quote_round_trips() {
+        let quote = round_trip(SwapQuote {
+            deposit: 1,
+            withdraw: 2,
+            fee: 3,
+        });
+        assert_eq!(SwapQuote::LEN, 24);
+        assert_eq!(
+            ({ quote.deposit }, { quote.withdraw }, { quote.fee }),
+            (1, 2, 3)
+        );
+        // 字段按声明顺序、小端序紧密排列
+        assert_eq!(&quote.as_bytes()[8..10], &[2, 0]);
+    }
+
+    #[test]
+    fn pool_info_round_trips() {
+        let info = round_trip(PoolInfo {
+            state: 1,
+            fee: 0x1234,
+            reserve_x: u64::MAX,
+            reserve_y: 7,
+            lp_supply: 8,
+        });
+        assert_eq!(PoolInfo::LEN, 27);
+        assert_eq!(&info.as_bytes()[..3], &[1, 0x34, 0x12]);
+        assert_eq!(
+            ({ info.reserve_x }, { info.reserve_y }, { info.lp_supply }),
+            (u64::MAX, 7, 8)
+        );
+    }
+
+    #[test]
+    fn redeem_value_round_trips() {
+        let value = round_trip(RedeemValue { x: 5, y: 6 });
+        assert_eq!(RedeemValue::LEN, 16);
+        assert_eq!(({ value.x }, { value.y }), (5, 6));
+    }
+
+    #[test]
+    fn spot_price_round_trips() {
+        let price = round_trip(SpotPrice {
+            x_in_y: 1 << 64,
+            y_in_x: u128::MAX,
+        });
+        assert_eq!(SpotPrice::LEN, 32);
+        assert_eq!(({ price.x_in_y }, { price.y_in_x }), (1 << 64, u128::MAX));
+    }
+
+    #[test]
+    fn health_report_round_trips() {
+        let report = round_trip(HealthReport {
+            status: HealthStatus::SupplyWithoutReserves as u8,
+        });
+        assert_eq!(HealthReport::LEN, 1);
+        assert_eq!(report.status, 5);
+    }
+
+    #[test]
+    fn config_info_round_trips() {
+        let info = round_trip(ConfigInfo {
+            state: 1,
+            seed: 42,
+            authority: [1; 32],
+            mint_x: [2; 32],
+            mint_y: [3; 32],
+            fee: 30,
+            config_bump: 254,
+        });
+        assert_eq!(ConfigInfo::LEN, 108);
+        assert_eq!({ info.seed }, 42);
+        assert_eq!(
+            (info.authority, info.mint_x, info.mint_y),
+            ([1; 32], [2; 32], [3; 32])
+        );
+        assert_eq!(({ info.fee }, info.config_bump), (30, 254));
+    }
+
+    #[test]
+    fn seed_info_round_trips() {
+        let info = round_trip(SeedInfo {
+            seed: u64::MAX,
+            mint_x: [2; 32],
+            mint_y: [3; 32],
+            config_bump: 1,
+        });
+        assert_eq!(SeedInfo::LEN, 73);
+        assert_eq!(
+            ({ info.seed }, info.mint_x, info.mint_y),
+            (u64::MAX, [2; 32], [3; 32])
+        );
+        assert_eq!(info.config_bump, 1);
+    }
+
+    #[test]
+    fn state_info_round_trips() {
+        assert_eq!(StateInfo::LEN, 1);
+        assert_eq!(round_trip(StateInfo { state: 3 }).state, 3);
+    }
+
+    #[test]
+    fn version_info_round_trips() {
+        let info = round_trip(VersionInfo {
+            program_version: 0x0001_0203,
+            config_len: 298,
+        });
+        assert_eq!(VersionInfo::LEN, 6);
+        assert_eq!(
+            ({ info.program_version }, { info.config_len }),
+            (0x0001_0203, 298)
+        );
+    }
+}
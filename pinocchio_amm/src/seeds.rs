@@ -0,0 +1,189 @@
+//! 各 PDA 的签名种子。
+//!
+//! `Seed` 只借用字节，签名种子数组又必须活到 CPI 结束，因此这里不直接返回 `Signer`：
+//! 调用方先拿到种子数组，再在自己的作用域里构造 `Signer::from(&seeds)`。
+//! 种子前缀定义在 `state` 中，地址推导 (`find_program_address`) 使用相同的顺序。
+
+use pinocchio::{Address, cpi::Seed};
+
+use crate::{
+    CONFIG_SEED, Config, GLOBAL_SEED, LP_AUTHORITY_SEED, LP_ESCROW_SEED, MINT_LP_SEED,
+    USER_DEPOSIT_SEED, USER_SWAP_SEED,
+};
+
+/// Config PDA 的种子 `["config", seed, mint_x, mint_y, bump]`。
+///
+/// 持有种子字节的拷贝，构造签名时不需要继续借用 Config 账户，
+/// 因此可以在发起 CPI 之前释放 Config 的借用。
+pub struct ConfigSeeds {
+    seed: [u8; 8],
+    mint_x: Address,
+    mint_y: Address,
+    bump: [u8; 1],
+}
+
+impl ConfigSeeds {
+    #[inline(always)]
+    pub fn new(seed: u64, mint_x: Address, mint_y: Address, bump: [u8; 1]) -> Self {
+        Self {
+            seed: seed.to_le_bytes(),
+            mint_x,
+            mint_y,
+            bump,
+        }
+    }
+
+    /// 从已初始化的 Config 中拷贝种子
+    #[inline(always)]
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(
+            config.seed(),
+            config.mint_x().clone(),
+            config.mint_y().clone(),
+            config.config_bump(),
+        )
+    }
+
+    #[inline(always)]
+    pub fn as_seeds(&self) -> [Seed<'_>; 5] {
+        [
+            Seed::from(CONFIG_SEED),
+            Seed::from(&self.seed),
+            Seed::from(self.mint_x.as_ref()),
+            Seed::from(self.mint_y.as_ref()),
+            Seed::from(&self.bump),
+        ]
+    }
+}
+
+/// LP mint PDA 的种子 `["mint_lp", config, bump]`
+#[inline(always)]
+pub fn mint_lp_seeds<'a>(config: &'a Address, bump: &'a [u8; 1]) -> [Seed<'a>; 3] {
+    [
+        Seed::from(MINT_LP_SEED),
+        Seed::from(config.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// LP 权限 PDA 的种子 `["lp_authority", config, bump]`
+#[inline(always)]
+pub fn lp_authority_seeds<'a>(config: &'a Address, bump: &'a [u8; 1]) -> [Seed<'a>; 3] {
+    [
+        Seed::from(LP_AUTHORITY_SEED),
+        Seed::from(config.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// 用户交换记录 PDA 的种子 `["user_swap", config, user, bump]`
+#[inline(always)]
+pub fn user_swap_seeds<'a>(
+    config: &'a Address,
+    user: &'a Address,
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 4] {
+    [
+        Seed::from(USER_SWAP_SEED),
+        Seed::from(config.as_ref()),
+        Seed::from(user.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// 用户存款记录 PDA 的种子 `["user_deposit", config, user, bump]`
+#[inline(always)]
+pub fn user_deposit_seeds<'a>(
+    config: &'a Address,
+    user: &'a Address,
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 4] {
+    [
+        Seed::from(USER_DEPOSIT_SEED),
+        Seed::from(config.as_ref()),
+        Seed::from(user.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// 用户 LP 托管代币账户 PDA 的种子 `["lp_escrow", config, user, bump]`
+#[inline(always)]
+pub fn lp_escrow_seeds<'a>(
+    config: &'a Address,
+    user: &'a Address,
+    bump: &'a [u8; 1],
+) -> [Seed<'a>; 4] {
+    [
+        Seed::from(LP_ESCROW_SEED),
+        Seed::from(config.as_ref()),
+        Seed::from(user.as_ref()),
+        Seed::from(bump),
+    ]
+}
+
+/// 全局暂停单例 PDA 的种子 `["global", bump]`
+#[inline(always)]
+pub fn global_seeds(bump: &[u8; 1]) -> [Seed<'_>; 2] {
+    [Seed::from(GLOBAL_SEED), Seed::from(bump)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 签名种子对应的程序地址
+    fn signer_address(seeds: &[Seed]) -> Address {
+        let seeds: Vec<&[u8]> = seeds.iter().map(|seed| &**seed).collect();
+        Address::create_program_address(&seeds, &crate::ID).unwrap()
+    }
+
+    /// 按 `prefix` 推导出的 PDA 与 bump
+    fn find(prefix: &[&[u8]]) -> (Address, [u8; 1]) {
+        let (address, bump) = Address::find_program_address(prefix, &crate::ID);
+        (address, [bump])
+    }
+
+    #[test]
+    fn signer_seeds_derive_the_program_addresses() {
+        let (mint_x, mint_y) = (
+            Address::new_from_array([2; 32]),
+            Address::new_from_array([3; 32]),
+        );
+        let (config, bump) = find(&[
+            CONFIG_SEED,
+            &42u64.to_le_bytes(),
+            mint_x.as_ref(),
+            mint_y.as_ref(),
+        ]);
+        let seeds = ConfigSeeds::new(42, mint_x, mint_y, bump);
+        assert_eq!(signer_address(&seeds.as_seeds()), config);
+
+        let (mint_lp, bump) = find(&[MINT_LP_SEED, config.as_ref()]);
+        assert_eq!(signer_address(&mint_lp_seeds(&config, &bump)), mint_lp);
+        let (lp_authority, bump) = find(&[LP_AUTHORITY_SEED, config.as_ref()]);
+        assert_eq!(
+            signer_address(&lp_authority_seeds(&config, &bump)),
+            lp_authority
+        );
+
+        let user = Address::new_from_array([6; 32]);
+        let (user_swap, bump) = find(&[USER_SWAP_SEED, config.as_ref(), user.as_ref()]);
+        assert_eq!(
+            signer_address(&user_swap_seeds(&config, &user, &bump)),
+            user_swap
+        );
+        let (user_deposit, bump) = find(&[USER_DEPOSIT_SEED, config.as_ref(), user.as_ref()]);
+        assert_eq!(
+            signer_address(&user_deposit_seeds(&config, &user, &bump)),
+            user_deposit
+        );
+        let (lp_escrow, bump) = find(&[LP_ESCROW_SEED, config.as_ref(), user.as_ref()]);
+        assert_eq!(
+            signer_address(&lp_escrow_seeds(&config, &user, &bump)),
+            lp_escrow
+        );
+
+        let (global, bump) = find(&[GLOBAL_SEED]);
+        assert_eq!(signer_address(&global_seeds(&bump)), global);
+    }
+}
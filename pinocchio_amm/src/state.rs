@@ -1,10 +1,84 @@
 use core::mem::size_of;
+use pinocchio::cpi::Signer;
 use pinocchio::{
-    AccountView, Address,
+    AccountView, Address, ProgramResult,
     account::{Ref, RefMut},
     error::ProgramError,
+    sysvars::{Sysvar, clock::Clock, rent::Rent},
 };
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer as SystemTransfer};
+use pinocchio_token::state::TokenAccount;
 
+use crate::{AmmError, curve::spot_price};
+
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const MINT_LP_SEED: &[u8] = b"mint_lp";
+pub const GLOBAL_SEED: &[u8] = b"global";
+pub const USER_SWAP_SEED: &[u8] = b"user_swap";
+pub const LP_AUTHORITY_SEED: &[u8] = b"lp_authority";
+pub const SWAP_TIP_TREASURY_SEED: &[u8] = b"swap_tip_treasury";
+pub const USER_DEPOSIT_SEED: &[u8] = b"user_deposit";
+pub const LP_ESCROW_SEED: &[u8] = b"lp_escrow";
+
+/// 全局紧急暂停的管理员 (编译期常量)。
+/// 占位地址，部署前替换为实际管理员公钥。
+pub const GLOBAL_ADMIN: Address = Address::new_from_array([
+    0x0f, 0x1e, 0x6b, 0x14, 0x21, 0xc0, 0x4a, 0x07, 0x04, 0x31, 0x26, 0x5c, 0x19, 0xc5, 0xbb, 0xee,
+    0x19, 0x92, 0xba, 0xe8, 0xaf, 0xd1, 0xcd, 0x07, 0x8e, 0xf8, 0xaf, 0x70, 0x47, 0xdc, 0x11, 0xf7,
+]);
+
+/// 建池费 (lamports)：启用 `creation-fee` feature 时，Initialize 在创建账户之前
+/// 从 initializer 转给 [`TREASURY`]。
+#[cfg(feature = "creation-fee")]
+pub const POOL_CREATION_FEE: u64 = 100_000_000;
+
+/// 建池费的收款地址 (编译期常量)。
+/// 占位地址，部署前替换为实际的协议金库公钥。
+#[cfg(feature = "creation-fee")]
+pub const TREASURY: Address = Address::new_from_array([
+    0x7a, 0x3c, 0x91, 0x05, 0xd2, 0x48, 0xe6, 0x1f, 0x83, 0x2b, 0xc4, 0x5e, 0x0d, 0x97, 0x6a, 0xf1,
+    0x24, 0xb8, 0x53, 0xce, 0x19, 0x70, 0xaf, 0x3d, 0x62, 0x8e, 0x05, 0xdb, 0x4c, 0x11, 0x9a, 0x36,
+]);
+
+/// 程序版本，由 Cargo.toml 的 `version` 在编译期编码为 `major << 16 | minor << 8 | patch`
+pub const PROGRAM_VERSION: u32 = parse_version_part(env!("CARGO_PKG_VERSION_MAJOR")) << 16
+    | parse_version_part(env!("CARGO_PKG_VERSION_MINOR")) << 8
+    | parse_version_part(env!("CARGO_PKG_VERSION_PATCH"));
+
+/// 编译期把版本号的十进制字符串转换为整数
+const fn parse_version_part(part: &str) -> u32 {
+    let bytes = part.as_bytes();
+    let mut value = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        value = value * 10 + (bytes[i] - b'0') as u32;
+        i += 1;
+    }
+    value
+}
+
+/// 新建池子的 LP mint 小数位数
+pub const LP_DECIMALS: u8 = 6;
+
+/// 新建池子单笔交换输入占输入方储备的默认上限 (bps)，限制价格冲击与 MEV 空间
+pub const DEFAULT_MAX_SWAP_BPS: u16 = 3_000;
+
+/// 最低手续费 (bps)。部分部署要求手续费不为 0 以保证 LP 收益，可在编译前调高；
+/// 默认为 0，不做限制。
+pub const MIN_FEE: u16 = 0;
+
+// Config.flags 中的各个暂停位，可独立开关
+pub const FLAG_DEPOSITS_PAUSED: u8 = 1 << 0;
+pub const FLAG_SWAPS_PAUSED: u8 = 1 << 1;
+pub const FLAG_WITHDRAWALS_PAUSED: u8 = 1 << 2;
+pub const FLAGS_ALL: u8 = FLAG_DEPOSITS_PAUSED | FLAG_SWAPS_PAUSED | FLAG_WITHDRAWALS_PAUSED;
+
+/// AMM 池子的配置账户。
+///
+/// 布局为 `#[repr(C, packed)]`，按字段声明顺序紧密排列、无填充。
+/// 多字节整数以 `[u8; N]` 存放，统一按小端序 (little-endian) 由 getter / setter 转换，
+/// 因此账户数据与宿主平台的字节序无关。各字段的字节偏移由下方的编译期断言锁定，
+/// 新增字段时需同步更新断言以及 `from_bytes_checked`。
 #[repr(C, packed)]
 pub struct Config {
     state: u8,
@@ -14,8 +88,73 @@ pub struct Config {
     mint_y: Address,
     fee: [u8; 2],
     config_bump: [u8; 1],
+    fee_mode: u8,
+    fee_recipient: Address,
+    protocol_fee_x: [u8; 8],
+    protocol_fee_y: [u8; 8],
+    flags: u8,
+    volatility_fee: [u8; 2],
+    cooldown_slots: [u8; 8],
+    price_x_cumulative: [u8; 16],
+    price_y_cumulative: [u8; 16],
+    twap_last_update: [u8; 8],
+    twap_min_interval: [u8; 4],
+    lp_authority_bump: [u8; 1],
+    max_abs_fee: [u8; 8],
+    lp_holder_fee: [u8; 2],
+    lp_holder_min: [u8; 8],
+    mint_lp_bump: [u8; 1],
+    lp_decimals: u8,
+    require_slippage: u8,
+    swaps_enabled_at: [u8; 8],
+    max_swap_bps: [u8; 2],
+    deposit_fee: [u8; 2],
+    swap_tip: [u8; 8],
+    gate_mint: Address,
+    fee_x_to_y: [u8; 2],
+    fee_y_to_x: [u8; 2],
+    lock_duration: [u8; 8],
 }
 
+// 锁定 Config 的字节布局：字段重排或改变大小会让已存在的账户无法解析，直接在编译期报错
+const _: () = {
+    use core::mem::offset_of;
+    assert!(offset_of!(Config, state) == 0);
+    assert!(offset_of!(Config, seed) == 1);
+    assert!(offset_of!(Config, authority) == 9);
+    assert!(offset_of!(Config, mint_x) == 41);
+    assert!(offset_of!(Config, mint_y) == 73);
+    assert!(offset_of!(Config, fee) == 105);
+    assert!(offset_of!(Config, config_bump) == 107);
+    assert!(offset_of!(Config, fee_mode) == 108);
+    assert!(offset_of!(Config, fee_recipient) == 109);
+    assert!(offset_of!(Config, protocol_fee_x) == 141);
+    assert!(offset_of!(Config, protocol_fee_y) == 149);
+    assert!(offset_of!(Config, flags) == 157);
+    assert!(offset_of!(Config, volatility_fee) == 158);
+    assert!(offset_of!(Config, cooldown_slots) == 160);
+    assert!(offset_of!(Config, price_x_cumulative) == 168);
+    assert!(offset_of!(Config, price_y_cumulative) == 184);
+    assert!(offset_of!(Config, twap_last_update) == 200);
+    assert!(offset_of!(Config, twap_min_interval) == 208);
+    assert!(offset_of!(Config, lp_authority_bump) == 212);
+    assert!(offset_of!(Config, max_abs_fee) == 213);
+    assert!(offset_of!(Config, lp_holder_fee) == 221);
+    assert!(offset_of!(Config, lp_holder_min) == 223);
+    assert!(offset_of!(Config, mint_lp_bump) == 231);
+    assert!(offset_of!(Config, lp_decimals) == 232);
+    assert!(offset_of!(Config, require_slippage) == 233);
+    assert!(offset_of!(Config, swaps_enabled_at) == 234);
+    assert!(offset_of!(Config, max_swap_bps) == 242);
+    assert!(offset_of!(Config, deposit_fee) == 244);
+    assert!(offset_of!(Config, swap_tip) == 246);
+    assert!(offset_of!(Config, gate_mint) == 254);
+    assert!(offset_of!(Config, fee_x_to_y) == 286);
+    assert!(offset_of!(Config, fee_y_to_x) == 288);
+    assert!(offset_of!(Config, lock_duration) == 290);
+    assert!(Config::LEN == 298);
+};
+
 #[repr(u8)]
 pub enum AmmState {
     Uninitialized = 0u8,
@@ -24,6 +163,31 @@ pub enum AmmState {
     WithdrawOnly = 3u8,
 }
 
+impl TryFrom<u8> for AmmState {
+    type Error = ProgramError;
+
+    fn try_from(state: u8) -> Result<Self, Self::Error> {
+        match state {
+            0 => Ok(AmmState::Uninitialized),
+            1 => Ok(AmmState::Initialized),
+            2 => Ok(AmmState::Disabled),
+            3 => Ok(AmmState::WithdrawOnly),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// 交换手续费的去向
+#[repr(u8)]
+pub enum FeeMode {
+    /// 手续费留在储备中，归 LP 所有 (默认)
+    Lp = 0u8,
+    /// 手续费直接转给 `fee_recipient`
+    Recipient = 1u8,
+    /// 手续费留在金库但记为协议收入，由 `WithdrawProtocolFees` 提取给 `fee_recipient`
+    Protocol = 2u8,
+}
+
 impl Config {
     pub const LEN: usize = size_of::<Config>();
 
@@ -84,6 +248,74 @@ impl Config {
         unsafe { &mut *(bytes.as_mut_ptr() as *mut Config) }
     }
 
+    /// 不经过指针强转，逐字段按小端序从字节切片解析出 `Config`，每一步都做边界检查。
+    /// 适用于安全性优先于极致性能的场景，结果与 `from_bytes_unchecked` 完全一致。
+    #[cfg(feature = "safe-load")]
+    pub fn from_bytes_checked(bytes: &[u8]) -> Result<Self, ProgramError> {
+        fn take<const N: usize>(cursor: &mut &[u8]) -> Result<[u8; N], ProgramError> {
+            let (head, tail) = cursor
+                .split_at_checked(N)
+                .ok_or(ProgramError::InvalidAccountData)?;
+            *cursor = tail;
+            head.try_into()
+                .map_err(|_| ProgramError::InvalidAccountData)
+        }
+
+        if bytes.len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // 结构体字面量按书写顺序求值，因此这里必须与字段声明顺序保持一致
+        let mut cursor = bytes;
+        Ok(Self {
+            state: take::<1>(&mut cursor)?[0],
+            seed: take(&mut cursor)?,
+            authority: Address::new_from_array(take(&mut cursor)?),
+            mint_x: Address::new_from_array(take(&mut cursor)?),
+            mint_y: Address::new_from_array(take(&mut cursor)?),
+            fee: take(&mut cursor)?,
+            config_bump: take(&mut cursor)?,
+            fee_mode: take::<1>(&mut cursor)?[0],
+            fee_recipient: Address::new_from_array(take(&mut cursor)?),
+            protocol_fee_x: take(&mut cursor)?,
+            protocol_fee_y: take(&mut cursor)?,
+            flags: take::<1>(&mut cursor)?[0],
+            volatility_fee: take(&mut cursor)?,
+            cooldown_slots: take(&mut cursor)?,
+            price_x_cumulative: take(&mut cursor)?,
+            price_y_cumulative: take(&mut cursor)?,
+            twap_last_update: take(&mut cursor)?,
+            twap_min_interval: take(&mut cursor)?,
+            lp_authority_bump: take(&mut cursor)?,
+            max_abs_fee: take(&mut cursor)?,
+            lp_holder_fee: take(&mut cursor)?,
+            lp_holder_min: take(&mut cursor)?,
+            mint_lp_bump: take(&mut cursor)?,
+            lp_decimals: take::<1>(&mut cursor)?[0],
+            require_slippage: take::<1>(&mut cursor)?[0],
+            swaps_enabled_at: take(&mut cursor)?,
+            max_swap_bps: take(&mut cursor)?,
+            deposit_fee: take(&mut cursor)?,
+            swap_tip: take(&mut cursor)?,
+            gate_mint: Address::new_from_array(take(&mut cursor)?),
+            fee_x_to_y: take(&mut cursor)?,
+            fee_y_to_x: take(&mut cursor)?,
+            lock_duration: take(&mut cursor)?,
+        })
+    }
+
+    /// `load` 的安全版本：校验长度与所有者后，用 `from_bytes_checked` 拷贝出一份 `Config`。
+    #[cfg(feature = "safe-load")]
+    pub fn load_checked(account_view: &AccountView) -> Result<Self, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Self::from_bytes_checked(&account_view.try_borrow()?)
+    }
+
     // Getter methods for safe field access
     #[inline(always)]
     pub fn state(&self) -> u8 {
@@ -110,16 +342,331 @@ impl Config {
         &self.mint_y
     }
 
+    /// 基础手续费 (bps)
     #[inline(always)]
     pub fn fee(&self) -> u16 {
         u16::from_le_bytes(self.fee)
     }
 
+    /// 随波动率调整的附加手续费 (bps)
+    #[inline(always)]
+    pub fn volatility_fee(&self) -> u16 {
+        u16::from_le_bytes(self.volatility_fee)
+    }
+
+    /// 交换实际使用的手续费：`fee + volatility_fee`，setter 保证其小于 10_000
+    #[inline(always)]
+    pub fn effective_fee(&self) -> u16 {
+        self.fee().saturating_add(self.volatility_fee())
+    }
+
+    /// X -> Y 方向单独设置的基础费率 (bps)，0 表示沿用 `fee`
+    #[inline(always)]
+    pub fn fee_x_to_y(&self) -> u16 {
+        u16::from_le_bytes(self.fee_x_to_y)
+    }
+
+    /// Y -> X 方向单独设置的基础费率 (bps)，0 表示沿用 `fee`
+    #[inline(always)]
+    pub fn fee_y_to_x(&self) -> u16 {
+        u16::from_le_bytes(self.fee_y_to_x)
+    }
+
+    /// 指定方向的交换实际使用的手续费：该方向的基础费率 (未单独设置时为 `fee`) 加 `volatility_fee`。
+    /// `is_x` 与 `SwapInstructionData::is_x` 相同，true 表示 X -> Y
+    #[inline(always)]
+    pub fn effective_fee_for(&self, is_x: bool) -> u16 {
+        let direction_fee = if is_x {
+            self.fee_x_to_y()
+        } else {
+            self.fee_y_to_x()
+        };
+        let base_fee = if direction_fee == 0 {
+            self.fee()
+        } else {
+            direction_fee
+        };
+        base_fee.saturating_add(self.volatility_fee())
+    }
+
+    /// 两个方向的基础费率与 `lp_holder_fee` 中的最高值，
+    /// setter 用它保证其中任一个加上 `volatility_fee` 都小于 10_000
+    #[inline(always)]
+    fn max_base_fee(&self) -> u16 {
+        self.fee()
+            .max(self.fee_x_to_y())
+            .max(self.fee_y_to_x())
+            .max(self.lp_holder_fee())
+    }
+
+    /// 每笔交换收取手续费的绝对上限 (以输入代币计)，0 表示不设上限
+    #[inline(always)]
+    pub fn max_abs_fee(&self) -> u64 {
+        u64::from_le_bytes(self.max_abs_fee)
+    }
+
+    /// 持有足够 LP 的用户交换时使用的手续费 (bps)
+    #[inline(always)]
+    pub fn lp_holder_fee(&self) -> u16 {
+        u16::from_le_bytes(self.lp_holder_fee)
+    }
+
+    /// LP 持有者在指定方向交换时实际使用的手续费：`lp_holder_fee` 与常规费率一样叠加
+    /// `volatility_fee`，且不高于 `effective_fee_for(is_x)`
+    #[inline(always)]
+    pub fn lp_holder_fee_for(&self, is_x: bool) -> u16 {
+        self.lp_holder_fee()
+            .saturating_add(self.volatility_fee())
+            .min(self.effective_fee_for(is_x))
+    }
+
+    /// 享受 LP 持有者手续费所需的最少 LP 数量，0 表示未启用该优惠
+    #[inline(always)]
+    pub fn lp_holder_min(&self) -> u64 {
+        u64::from_le_bytes(self.lp_holder_min)
+    }
+
+    /// LP mint PDA (`["mint_lp", config]`) 的 bump
+    #[inline(always)]
+    pub fn mint_lp_bump(&self) -> [u8; 1] {
+        self.mint_lp_bump
+    }
+
+    /// LP mint 的小数位数 (由 `Migrate` 与实际的 LP mint 同步)
+    #[inline(always)]
+    pub fn lp_decimals(&self) -> u8 {
+        self.lp_decimals
+    }
+
+    /// 是否要求 Deposit / Withdraw / Swap 提供有效的滑点保护
+    #[inline(always)]
+    pub fn requires_slippage(&self) -> bool {
+        self.require_slippage != 0
+    }
+
+    /// 开放交换的时间 (unix 秒)：在此之前只允许存取款，0 表示创建后立即开放
+    #[inline(always)]
+    pub fn swaps_enabled_at(&self) -> i64 {
+        i64::from_le_bytes(self.swaps_enabled_at)
+    }
+
+    /// 单笔交换输入占输入方储备的上限 (bps)，0 表示不限制
+    #[inline(always)]
+    pub fn max_swap_bps(&self) -> u16 {
+        u16::from_le_bytes(self.max_swap_bps)
+    }
+
+    /// 存款手续费 (bps)：非首次存款时少铸造这部分 LP，对应的代币留给现有 LP
+    #[inline(always)]
+    pub fn deposit_fee(&self) -> u16 {
+        u16::from_le_bytes(self.deposit_fee)
+    }
+
+    /// 扣除 `deposit_fee` (向上取整) 之后实际铸造的 LP 数量
+    #[inline(always)]
+    pub fn lp_after_deposit_fee(&self, lp_amount: u64) -> u64 {
+        let lp_fee = (lp_amount as u128 * self.deposit_fee() as u128).div_ceil(10_000);
+        // deposit_fee < 10_000，因此 lp_fee <= lp_amount
+        lp_amount - lp_fee as u64
+    }
+
+    /// 每笔交换由用户额外支付给程序级 tip 金库 PDA (`["swap_tip_treasury"]`) 的 lamports，
+    /// 与代币手续费无关；0 表示不收取
+    #[inline(always)]
+    pub fn swap_tip(&self) -> u64 {
+        u64::from_le_bytes(self.swap_tip)
+    }
+
+    /// 准入代币：非全 0 时，交换者必须持有该 mint 余额大于 0 的代币账户才能交换
+    #[inline(always)]
+    pub fn gate_mint(&self) -> &Address {
+        &self.gate_mint
+    }
+
+    /// 存款之后多久 (秒) 才允许同一用户取款，0 表示不锁定
+    #[inline(always)]
+    pub fn lock_duration(&self) -> u64 {
+        u64::from_le_bytes(self.lock_duration)
+    }
+
+    /// `max_swap_bps` 允许的最大输入 (含手续费)，未限制时返回 None
+    #[inline(always)]
+    pub fn max_swap_input(&self, reserve_in: u64) -> Option<u64> {
+        let max_swap_bps = self.max_swap_bps();
+        // max_swap_bps <= 10_000，结果不超过 reserve_in
+        (max_swap_bps > 0).then(|| (reserve_in as u128 * max_swap_bps as u128 / 10_000) as u64)
+    }
+
+    /// 交换输入 `amount_in` 超过 `reserve_in` 的 `max_swap_bps` 时返回 `SwapTooLarge`
+    #[inline(always)]
+    pub fn check_max_swap(&self, amount_in: u64, reserve_in: u64) -> Result<(), ProgramError> {
+        let max_swap_bps = self.max_swap_bps();
+        if max_swap_bps > 0
+            && (amount_in as u128) * 10_000 > (reserve_in as u128) * max_swap_bps as u128
+        {
+            return Err(AmmError::SwapTooLarge.into());
+        }
+        Ok(())
+    }
+
+    /// 开放交换之前拒绝交换 (`unix_timestamp < swaps_enabled_at`)；没有 Clock 时不检查
+    #[inline(always)]
+    pub fn check_swaps_enabled(&self, clock: Option<&Clock>) -> Result<(), ProgramError> {
+        match clock {
+            Some(clock) if clock.unix_timestamp < self.swaps_enabled_at() => {
+                Err(AmmError::SwapsNotYetEnabled.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 滑点保护策略开启时，拒绝为 0 的下限 (等同于不设下限)
+    #[inline(always)]
+    pub fn check_slippage_min(&self, min: u64) -> Result<(), ProgramError> {
+        if self.requires_slippage() && min == 0 {
+            return Err(AmmError::SlippageProtectionRequired.into());
+        }
+        Ok(())
+    }
+
+    /// 滑点保护策略开启时，拒绝为 `u64::MAX` 的上限 (等同于不设上限)
+    #[inline(always)]
+    pub fn check_slippage_max(&self, max: u64) -> Result<(), ProgramError> {
+        if self.requires_slippage() && max == u64::MAX {
+            return Err(AmmError::SlippageProtectionRequired.into());
+        }
+        Ok(())
+    }
+
+    /// 校验传入的 `mint_lp` 确实是该池子的 LP mint。
+    /// 否则攻击者可以传入自己创建、mint_authority 指向本池子权限 PDA 的假 mint，伪造 LP 供应量。
+    #[inline(always)]
+    pub fn check_mint_lp(
+        &self,
+        config: &AccountView,
+        mint_lp: &AccountView,
+    ) -> Result<(), ProgramError> {
+        let expected = Address::create_program_address(
+            &[MINT_LP_SEED, config.address().as_ref(), &self.mint_lp_bump],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        if expected.ne(mint_lp.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    /// 同一用户两次交换之间至少间隔的 slot 数，0 表示不限制
+    #[inline(always)]
+    pub fn cooldown_slots(&self) -> u64 {
+        u64::from_le_bytes(self.cooldown_slots)
+    }
+
+    /// X 以 Y 计价的现货价格 (Q64.64) 对时间 (秒) 的累加值，溢出时回绕
+    #[inline(always)]
+    pub fn price_x_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_x_cumulative)
+    }
+
+    /// Y 以 X 计价的现货价格 (Q64.64) 对时间 (秒) 的累加值，溢出时回绕
+    #[inline(always)]
+    pub fn price_y_cumulative(&self) -> u128 {
+        u128::from_le_bytes(self.price_y_cumulative)
+    }
+
+    /// 上一次更新 TWAP 累加器的 unix 时间戳，0 表示尚未开始
+    #[inline(always)]
+    pub fn twap_last_update(&self) -> i64 {
+        i64::from_le_bytes(self.twap_last_update)
+    }
+
+    /// 两次 TWAP 累加器更新之间的最小间隔 (秒)，0 表示每笔交换都更新
+    #[inline(always)]
+    pub fn twap_min_interval(&self) -> u32 {
+        u32::from_le_bytes(self.twap_min_interval)
+    }
+
     #[inline(always)]
     pub fn config_bump(&self) -> [u8; 1] {
         self.config_bump
     }
 
+    /// LP mint 权限 PDA (`["lp_authority", config]`) 的 bump
+    #[inline(always)]
+    pub fn lp_authority_bump(&self) -> [u8; 1] {
+        self.lp_authority_bump
+    }
+
+    /// 由保存的 bump 推导 LP mint 权限 PDA 的地址
+    #[inline(always)]
+    pub fn lp_authority_address(&self, config: &Address) -> Result<Address, ProgramError> {
+        Address::create_program_address(
+            &[LP_AUTHORITY_SEED, config.as_ref(), &self.lp_authority_bump],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)
+    }
+
+    /// 校验传入的 `lp_authority` 确实是该池子的 LP mint 权限 PDA
+    #[inline(always)]
+    pub fn check_lp_authority(
+        &self,
+        config: &AccountView,
+        lp_authority: &AccountView,
+    ) -> Result<(), ProgramError> {
+        if self
+            .lp_authority_address(config.address())?
+            .ne(lp_authority.address())
+        {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn fee_mode(&self) -> u8 {
+        self.fee_mode
+    }
+
+    #[inline(always)]
+    pub fn fee_recipient(&self) -> &Address {
+        &self.fee_recipient
+    }
+
+    #[inline(always)]
+    pub fn protocol_fee_x(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fee_x)
+    }
+
+    #[inline(always)]
+    pub fn protocol_fee_y(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fee_y)
+    }
+
+    #[inline(always)]
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// 指定的暂停位 (`FLAG_*`) 是否被置位
+    #[inline(always)]
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// LP 实际拥有的储备：金库余额扣除尚未提取的协议手续费
+    #[inline(always)]
+    pub fn lp_reserves(&self, vault_x: u64, vault_y: u64) -> Result<(u64, u64), ProgramError> {
+        let reserve_x = vault_x
+            .checked_sub(self.protocol_fee_x())
+            .ok_or(ProgramError::InsufficientFunds)?;
+        let reserve_y = vault_y
+            .checked_sub(self.protocol_fee_y())
+            .ok_or(ProgramError::InsufficientFunds)?;
+        Ok((reserve_x, reserve_y))
+    }
+
     #[inline(always)]
     pub fn load_mut<'a>(account_view: &'a AccountView) -> Result<RefMut<'a, Self>, ProgramError> {
         if account_view.data_len() != Self::LEN {
@@ -135,6 +682,73 @@ impl Config {
         }))
     }
 
+    /// 可以由 `Migrate` 扩容的最短 Config：第一个版本 (state 到 config_bump) 的布局。
+    /// 字段只会追加在末尾，旧版本程序创建的 Config 都是当前布局的前缀
+    pub const MIN_MIGRATABLE_LEN: usize = core::mem::offset_of!(Config, fee_mode);
+
+    /// 把旧版本程序创建的 Config 扩容到 `Config::LEN`，已是当前长度时不做任何事。
+    ///
+    /// 新增的字段与 `set_inner` 创建的新池子取相同的默认值 (见 [`Config::init_appended_fields`])，
+    /// 其余功能开关都为 0 (未启用)，池子的行为与迁移前一致。
+    /// 扩容前由 `payer` 补足 `minimum_balance` (`Config::LEN` 的免租金额度) 与当前余额的差额；
+    /// System Program 需出现在交易的账户列表中
+    pub fn realloc_legacy(
+        account_view: &AccountView,
+        payer: &AccountView,
+        minimum_balance: u64,
+    ) -> ProgramResult {
+        let len = account_view.data_len();
+        if len == Self::LEN {
+            return Ok(());
+        }
+        if !(Self::MIN_MIGRATABLE_LEN..Self::LEN).contains(&len) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let top_up = minimum_balance.saturating_sub(account_view.lamports());
+        if top_up > 0 {
+            SystemTransfer {
+                from: payer,
+                to: account_view,
+                lamports: top_up,
+            }
+            .invoke()?;
+        }
+        account_view.resize(Self::LEN)?;
+
+        Self::load_mut(account_view)?.init_appended_fields(len, account_view.address())
+    }
+
+    /// 为长度为 `old_len` 的旧版 Config 新增的字段写入非 0 的默认值：
+    ///
+    /// * `lp_authority_bump` / `mint_lp_bump`：由 `config` 地址重新推导
+    /// * `lp_decimals`：`LP_DECIMALS`，`Migrate` 随后以 LP mint 的实际值覆盖
+    /// * `max_swap_bps`：`DEFAULT_MAX_SWAP_BPS`
+    fn init_appended_fields(&mut self, old_len: usize, config: &Address) -> ProgramResult {
+        use core::mem::offset_of;
+
+        if old_len <= offset_of!(Config, lp_authority_bump) {
+            let (_, bump) =
+                Address::find_program_address(&[LP_AUTHORITY_SEED, config.as_ref()], &crate::ID);
+            self.set_lp_authority_bump([bump]);
+        }
+        if old_len <= offset_of!(Config, mint_lp_bump) {
+            let (_, bump) =
+                Address::find_program_address(&[MINT_LP_SEED, config.as_ref()], &crate::ID);
+            self.set_mint_lp_bump([bump]);
+        }
+        if old_len <= offset_of!(Config, lp_decimals) {
+            self.set_lp_decimals(LP_DECIMALS);
+        }
+        if old_len <= offset_of!(Config, max_swap_bps) {
+            self.set_max_swap_bps(DEFAULT_MAX_SWAP_BPS)?;
+        }
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
         if state.ge(&(AmmState::WithdrawOnly as u8)) {
@@ -144,6 +758,38 @@ impl Config {
         Ok(())
     }
 
+    /// 受限的状态迁移，只允许：
+    ///
+    /// * `Initialized <-> Disabled`
+    /// * `Initialized -> WithdrawOnly`
+    /// * `WithdrawOnly -> Disabled`
+    ///
+    /// `Uninitialized` 只能通过初始化离开，也不能迁移回去。
+    /// 紧急停用不受这张表限制，见 [`Config::force_disable`]。
+    pub fn transition(&mut self, to: AmmState) -> Result<(), ProgramError> {
+        match (AmmState::try_from(self.state)?, &to) {
+            (AmmState::Initialized, AmmState::Disabled)
+            | (AmmState::Disabled, AmmState::Initialized)
+            | (AmmState::Initialized, AmmState::WithdrawOnly)
+            | (AmmState::WithdrawOnly, AmmState::Disabled) => {
+                self.state = to as u8;
+                Ok(())
+            }
+            _ => Err(AmmError::InvalidStateTransition.into()),
+        }
+    }
+
+    /// 紧急停用：绕过 `transition` 的迁移规则，从任何已初始化的状态 (包括已停用) 直接进入
+    /// `Disabled`，返回停用前的状态。`Uninitialized` 的池子不能停用。
+    pub fn force_disable(&mut self) -> Result<u8, ProgramError> {
+        let previous = self.state;
+        if let AmmState::Uninitialized = AmmState::try_from(previous)? {
+            return Err(AmmError::InvalidStateTransition.into());
+        }
+        self.state = AmmState::Disabled as u8;
+        Ok(previous)
+    }
+
     #[inline(always)]
     pub fn set_seed(&mut self, seed: u64) {
         self.seed = seed.to_le_bytes();
@@ -166,7 +812,7 @@ impl Config {
 
     #[inline(always)]
     pub fn set_fee(&mut self, fee: u16) -> Result<(), ProgramError> {
-        if fee.ge(&10_000) {
+        if fee.lt(&MIN_FEE) || (fee as u32 + self.volatility_fee() as u32).ge(&10_000) {
             return Err(ProgramError::InvalidAccountData);
         }
         self.fee = fee.to_le_bytes();
@@ -174,60 +820,1390 @@ impl Config {
     }
 
     #[inline(always)]
-    pub fn set_config_bump(&mut self, config_bump: [u8; 1]) {
-        self.config_bump = config_bump;
+    pub fn set_volatility_fee(&mut self, volatility_fee: u16) -> Result<(), ProgramError> {
+        if (self.max_base_fee() as u32 + volatility_fee as u32).ge(&10_000) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.volatility_fee = volatility_fee.to_le_bytes();
+        Ok(())
     }
 
+    /// 设置两个方向的基础费率，0 表示该方向沿用 `fee`
     #[inline(always)]
-    pub fn set_inner(
+    pub fn set_direction_fees(
         &mut self,
-        seed: u64,
-        authority: Address,
-        mint_x: Address,
-        mint_y: Address,
-        fee: u16,
-        config_bump: [u8; 1],
+        fee_x_to_y: u16,
+        fee_y_to_x: u16,
     ) -> Result<(), ProgramError> {
-        self.set_state(AmmState::Initialized as u8)?;
-        self.set_seed(seed);
-        self.set_authority(authority);
-        self.set_mint_x(mint_x);
-        self.set_mint_y(mint_y);
-        self.set_fee(fee)?;
-        self.set_config_bump(config_bump);
+        for fee in [fee_x_to_y, fee_y_to_x] {
+            if fee != 0
+                && (fee.lt(&MIN_FEE) || (fee as u32 + self.volatility_fee() as u32).ge(&10_000))
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        self.fee_x_to_y = fee_x_to_y.to_le_bytes();
+        self.fee_y_to_x = fee_y_to_x.to_le_bytes();
         Ok(())
     }
 
     #[inline(always)]
-    pub fn has_authority(&self) -> Option<Address> {
-        // read_unaligned：处理“对齐”问题
-        // // 1. 用“不检查对齐”的方法，把 self.authority 里的内容强行拷贝一份给 auth
-        // We use read_unaligned to safely copy the Address bytes into the 'auth' variable
-        let auth = unsafe { core::ptr::addr_of!(self.authority).read_unaligned() };
-
-        // 2. 检查复印出来的这个地址是不是全 0（默认地址）
-        if auth == Address::default() {
-            None // 全 0 说明没设置，返回“空”
-        } else {
-            // 3. 返回我们刚才“复印”好的 auth，而不是原件
-            // 这样就不用从原结构体里“撕”数据了，编译器就不会报错
-            // Return 'auth' (the local copy) instead of 'self.authority'
-            Some(auth)
+    pub fn set_lp_holder_fee(
+        &mut self,
+        lp_holder_fee: u16,
+        lp_holder_min: u64,
+    ) -> Result<(), ProgramError> {
+        // 与 set_fee 相同的范围：不低于 MIN_FEE，加上 volatility_fee 之后小于 10_000
+        if lp_holder_fee.lt(&MIN_FEE)
+            || (lp_holder_fee as u32 + self.volatility_fee() as u32).ge(&10_000)
+        {
+            return Err(ProgramError::InvalidAccountData);
         }
+        self.lp_holder_fee = lp_holder_fee.to_le_bytes();
+        self.lp_holder_min = lp_holder_min.to_le_bytes();
+        Ok(())
     }
 
-    /// 强制以可变引用加载账户数据，不检查所有者 (用于初始化)
-    /// # Safety
-    /// 调用者必须确保账户空间足够且已由程序控制
-    #[allow(clippy::mut_from_ref)]
     #[inline(always)]
-    pub unsafe fn load_mut_unchecked(
-        account_view: &AccountView,
-    ) -> Result<&mut Self, ProgramError> {
-        if account_view.data_len() != Self::LEN {
+    pub fn set_lp_decimals(&mut self, lp_decimals: u8) {
+        self.lp_decimals = lp_decimals;
+    }
+
+    #[inline(always)]
+    pub fn set_swaps_enabled_at(&mut self, swaps_enabled_at: i64) {
+        self.swaps_enabled_at = swaps_enabled_at.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_max_swap_bps(&mut self, max_swap_bps: u16) -> Result<(), ProgramError> {
+        if max_swap_bps.gt(&10_000) {
             return Err(ProgramError::InvalidAccountData);
         }
-        // 直接获取账户数据的原始指针并转换为可变结构体引用
-        Ok(unsafe { Self::from_bytes_unchecked_mut(account_view.borrow_unchecked_mut()) })
+        self.max_swap_bps = max_swap_bps.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_deposit_fee(&mut self, deposit_fee: u16) -> Result<(), ProgramError> {
+        if deposit_fee.ge(&10_000) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.deposit_fee = deposit_fee.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_swap_tip(&mut self, swap_tip: u64) {
+        self.swap_tip = swap_tip.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_gate_mint(&mut self, gate_mint: Address) {
+        self.gate_mint = gate_mint;
+    }
+
+    #[inline(always)]
+    pub fn set_lock_duration(&mut self, lock_duration: u64) {
+        self.lock_duration = lock_duration.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_require_slippage(&mut self, require_slippage: bool) {
+        self.require_slippage = require_slippage as u8;
+    }
+
+    #[inline(always)]
+    pub fn set_mint_lp_bump(&mut self, mint_lp_bump: [u8; 1]) {
+        self.mint_lp_bump = mint_lp_bump;
+    }
+
+    #[inline(always)]
+    pub fn set_max_abs_fee(&mut self, max_abs_fee: u64) {
+        self.max_abs_fee = max_abs_fee.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_cooldown_slots(&mut self, cooldown_slots: u64) {
+        self.cooldown_slots = cooldown_slots.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_twap_min_interval(&mut self, twap_min_interval: u32) {
+        self.twap_min_interval = twap_min_interval.to_le_bytes();
+    }
+
+    /// 以交换前的储备更新 TWAP 累加器：`cumulative += price * elapsed`，溢出时回绕，
+    /// 读取方用两次观测的差值除以时间差得到 TWAP。
+    ///
+    /// 距上次更新不足 `twap_min_interval` 秒时跳过；被跳过的时间会在下一次更新时
+    /// 一并以当时的价格计入，因此累加值始终覆盖完整的时间跨度。
+    ///
+    /// 这是一个近似：被跳过的交换改变了价格，但整段间隔都按最后的价格计价，
+    /// 间隔内较早的价格不会被计入。误差的时间范围不超过一次更新的间隔，
+    /// 对准确度要求高的读取方应让观测窗口远大于 `twap_min_interval`，或将其设为 0。
+    pub fn update_twap(
+        &mut self,
+        now: i64,
+        reserve_x: u64,
+        reserve_y: u64,
+    ) -> Result<(), ProgramError> {
+        let last = self.twap_last_update();
+        if last == 0 {
+            self.twap_last_update = now.to_le_bytes();
+            return Ok(());
+        }
+
+        let elapsed = now.saturating_sub(last);
+        if elapsed <= 0 || elapsed < self.twap_min_interval() as i64 {
+            return Ok(());
+        }
+        let elapsed = elapsed as u128;
+
+        let price_x = spot_price(reserve_x, reserve_y)?;
+        let price_y = spot_price(reserve_y, reserve_x)?;
+        self.price_x_cumulative = self
+            .price_x_cumulative()
+            .wrapping_add(price_x.wrapping_mul(elapsed))
+            .to_le_bytes();
+        self.price_y_cumulative = self
+            .price_y_cumulative()
+            .wrapping_add(price_y.wrapping_mul(elapsed))
+            .to_le_bytes();
+        self.twap_last_update = now.to_le_bytes();
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_config_bump(&mut self, config_bump: [u8; 1]) {
+        self.config_bump = config_bump;
+    }
+
+    #[inline(always)]
+    pub fn set_lp_authority_bump(&mut self, lp_authority_bump: [u8; 1]) {
+        self.lp_authority_bump = lp_authority_bump;
+    }
+
+    #[inline(always)]
+    pub fn set_fee_mode(&mut self, fee_mode: u8) -> Result<(), ProgramError> {
+        if fee_mode.gt(&(FeeMode::Protocol as u8)) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.fee_mode = fee_mode;
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn set_fee_recipient(&mut self, fee_recipient: Address) {
+        self.fee_recipient = fee_recipient;
+    }
+
+    #[inline(always)]
+    pub fn set_protocol_fees(&mut self, protocol_fee_x: u64, protocol_fee_y: u64) {
+        self.protocol_fee_x = protocol_fee_x.to_le_bytes();
+        self.protocol_fee_y = protocol_fee_y.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_flags(&mut self, flags: u8) -> Result<(), ProgramError> {
+        if flags & !FLAGS_ALL != 0 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        self.flags = flags;
+        Ok(())
+    }
+
+    /// 累加一笔交换产生的协议手续费 (`is_x` 表示手续费以 X 计价)
+    #[inline(always)]
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, fee: u64) -> Result<(), ProgramError> {
+        if is_x {
+            let total = self
+                .protocol_fee_x()
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.protocol_fee_x = total.to_le_bytes();
+        } else {
+            let total = self
+                .protocol_fee_y()
+                .checked_add(fee)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.protocol_fee_y = total.to_le_bytes();
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_inner(
+        &mut self,
+        seed: u64,
+        authority: Address,
+        mint_x: Address,
+        mint_y: Address,
+        fee: u16,
+        config_bump: [u8; 1],
+        lp_authority_bump: [u8; 1],
+        mint_lp_bump: [u8; 1],
+    ) -> Result<(), ProgramError> {
+        self.set_state(AmmState::Initialized as u8)?;
+        self.set_seed(seed);
+        self.set_authority(authority);
+        self.set_mint_x(mint_x);
+        self.set_mint_y(mint_y);
+        self.set_volatility_fee(0)?;
+        self.set_fee(fee)?;
+        self.set_config_bump(config_bump);
+        self.set_lp_authority_bump(lp_authority_bump);
+        self.set_mint_lp_bump(mint_lp_bump);
+        self.set_lp_decimals(LP_DECIMALS);
+        self.set_fee_mode(FeeMode::Lp as u8)?;
+        self.set_fee_recipient(Address::default());
+        self.set_protocol_fees(0, 0);
+        self.set_flags(0)?;
+        self.set_cooldown_slots(0);
+        self.price_x_cumulative = [0; 16];
+        self.price_y_cumulative = [0; 16];
+        self.twap_last_update = [0; 8];
+        self.set_twap_min_interval(0);
+        self.set_max_abs_fee(0);
+        self.set_lp_holder_fee(0, 0)?;
+        self.set_require_slippage(false);
+        self.set_swaps_enabled_at(0);
+        self.set_max_swap_bps(DEFAULT_MAX_SWAP_BPS)?;
+        self.set_deposit_fee(0)?;
+        self.set_swap_tip(0);
+        self.set_gate_mint(Address::default());
+        self.set_direction_fees(0, 0)?;
+        self.set_lock_duration(0);
+        Ok(())
+    }
+
+    /// 管理员是否未设置 (全 0)。
+    ///
+    /// 按 4 个 u64 做非对齐读取后求或，不需要把 32 字节的地址整体拷贝出来再与默认值比较。
+    #[inline(always)]
+    pub fn authority_is_unset(&self) -> bool {
+        let ptr = core::ptr::addr_of!(self.authority) as *const u64;
+        // Address 是 32 字节的纯数据，正好覆盖 4 个 u64，read_unaligned 不要求对齐
+        let word = |i: usize| unsafe { ptr.add(i).read_unaligned() };
+        (word(0) | word(1) | word(2) | word(3)) == 0
+    }
+
+    #[inline(always)]
+    pub fn has_authority(&self) -> Option<Address> {
+        if self.authority_is_unset() {
+            None
+        } else {
+            Some(self.authority().clone())
+        }
+    }
+
+    /// 校验 `authority` 是该池子的管理员且已签名。
+    /// 未设置管理员 (全 0) 的池子不可被修改。
+    #[inline(always)]
+    pub fn check_authority(&self, authority: &AccountView) -> Result<(), ProgramError> {
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        // 直接在原位比较，不拷贝管理员地址
+        if self.authority_is_unset() || self.authority().ne(authority.address()) {
+            return Err(ProgramError::IncorrectAuthority);
+        }
+        Ok(())
+    }
+
+    /// 校验从 Config 账户转出 `lamports` 之后仍满足免租金要求。
+    /// 任何会减少 Config lamports 的管理指令，在真正扣减之前都应调用此函数，
+    /// 避免账户余额跌破免租金门槛而被运行时回收。
+    #[inline(always)]
+    pub fn check_rent_exempt_after(
+        account_view: &AccountView,
+        lamports: u64,
+    ) -> Result<(), ProgramError> {
+        let minimum = Rent::get()?.try_minimum_balance(Self::LEN)?;
+        Self::check_balance_after(account_view, lamports, minimum)
+    }
+
+    /// 转出 `lamports` 之后账户余额不低于 `minimum_balance`
+    #[inline(always)]
+    fn check_balance_after(
+        account_view: &AccountView,
+        lamports: u64,
+        minimum_balance: u64,
+    ) -> Result<(), ProgramError> {
+        let remaining = account_view
+            .lamports()
+            .checked_sub(lamports)
+            .ok_or(ProgramError::InsufficientFunds)?;
+        if remaining < minimum_balance {
+            return Err(ProgramError::AccountNotRentExempt);
+        }
+        Ok(())
+    }
+
+    /// 校验两个金库的代币 authority 都是 Config PDA。
+    /// 否则攻击者可以传入自己能随意转移的代币账户，伪造储备数据。
+    #[inline(always)]
+    pub fn check_vaults(
+        config: &AccountView,
+        vault_x: &TokenAccount,
+        vault_y: &TokenAccount,
+    ) -> Result<(), ProgramError> {
+        if vault_x.owner().ne(config.address()) || vault_y.owner().ne(config.address()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(())
+    }
+
+    /// 校验两个金库的币种分别是 mint_x / mint_y，只读取 mint 字段。
+    /// 两个金库都归 Config PDA 所有时，仅靠 authority 无法发现 vault_x / vault_y 被传反。
+    #[inline(always)]
+    pub fn check_vault_mints(
+        &self,
+        vault_x: &AccountView,
+        vault_y: &AccountView,
+    ) -> Result<(), ProgramError> {
+        for (vault, mint) in [(vault_x, self.mint_x()), (vault_y, self.mint_y())] {
+            let data = vault.try_borrow()?;
+            token_account_fields(&data, vault)?;
+            if data[TOKEN_ACCOUNT_MINT].ne(mint.as_ref()) {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        Ok(())
+    }
+
+    /// 读取两个金库的余额 `(vault_x, vault_y)`，同时校验它们归 Config PDA 所有。
+    /// 返回的是金库的原始余额，属于 LP 的储备需再经过 `lp_reserves` 扣除协议手续费。
+    #[inline(always)]
+    pub fn read_reserves(
+        config: &AccountView,
+        vault_x: &AccountView,
+        vault_y: &AccountView,
+    ) -> Result<(u64, u64), ProgramError> {
+        let vault_x = TokenAccount::from_account_view(vault_x)?;
+        let vault_y = TokenAccount::from_account_view(vault_y)?;
+        Self::check_vaults(config, &vault_x, &vault_y)?;
+        Ok((vault_x.amount(), vault_y.amount()))
+    }
+
+    /// `read_reserves` 的 unchecked 版本：跳过借用检查，且只读取 authority 与 amount 两个字段，
+    /// 不做完整反序列化，以节省计算单元
+    /// # Safety
+    /// 调用者必须确保两个金库账户当前没有被可变借用
+    #[inline(always)]
+    pub unsafe fn read_reserves_unchecked(
+        config: &AccountView,
+        vault_x: &AccountView,
+        vault_y: &AccountView,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (authority_x, amount_x) =
+            token_account_fields(unsafe { vault_x.borrow_unchecked() }, vault_x)?;
+        let (authority_y, amount_y) =
+            token_account_fields(unsafe { vault_y.borrow_unchecked() }, vault_y)?;
+        if authority_x.ne(config.address().as_ref()) || authority_y.ne(config.address().as_ref()) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok((amount_x, amount_y))
+    }
+
+    /// 转入金库之后重新读取余额，确认至少增加了 `expected`。
+    /// 防御 Token 程序返回成功却没有实际转账的情况；`before` 为转账前的余额。
+    #[inline(always)]
+    pub fn check_vault_received(
+        vault: &AccountView,
+        before: u64,
+        expected: u64,
+    ) -> Result<(), ProgramError> {
+        let expected_after = before
+            .checked_add(expected)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if vault_amount(vault)? < expected_after {
+            return Err(AmmError::TransferNotReceived.into());
+        }
+        Ok(())
+    }
+
+    /// 从金库转出 `amount` 之前确认余额 `vault_amount` 足够。储备来自金库余额，正常情况下总能满足；
+    /// 不满足说明储备数据或曲线出错，此时给出明确的错误，而不是让 Transfer CPI 失败
+    #[inline(always)]
+    pub fn check_vault_covers(vault_amount: u64, amount: u64) -> Result<(), ProgramError> {
+        if amount > vault_amount {
+            return Err(AmmError::InsufficientLiquidity.into());
+        }
+        Ok(())
+    }
+
+    /// 强制以可变引用加载账户数据，不检查所有者，也不经过借用检查。
+    ///
+    /// 只应在 `Initialize` 中 `CreateAccount` 刚把账户分配给本程序之后使用：此时账户必然属于
+    /// 本程序，省掉所有者检查与借用计数。debug 构建中仍会断言所有者，及早发现误用。
+    /// # Safety
+    /// 调用者必须确保账户空间足够且已由程序控制，并且在返回的引用存活期间
+    /// 没有其他对该账户数据的借用 (包括 CPI)
+    #[allow(clippy::mut_from_ref)]
+    #[inline(always)]
+    pub unsafe fn load_mut_unchecked(
+        account_view: &AccountView,
+    ) -> Result<&mut Self, ProgramError> {
+        debug_assert!(
+            account_view.owned_by(&crate::ID),
+            "Config::load_mut_unchecked called on an account not owned by this program"
+        );
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // 直接获取账户数据的原始指针并转换为可变结构体引用
+        Ok(unsafe { Self::from_bytes_unchecked_mut(account_view.borrow_unchecked_mut()) })
+    }
+}
+
+/// SPL Token 账户中 `amount` (u64，小端序) 的字节偏移：紧跟在 32 字节的 mint 与 owner 之后。
+/// 绕过 `TokenAccount` 直接读取余额的快速路径都应使用该常量，而不是硬编码 64。
+pub const TOKEN_AMOUNT_OFFSET: usize = 64;
+
+/// SPL Token 账户中 mint、owner (authority) 与 amount 字段的字节范围
+const TOKEN_ACCOUNT_MINT: core::ops::Range<usize> = 0..32;
+const TOKEN_ACCOUNT_OWNER: core::ops::Range<usize> = 32..TOKEN_AMOUNT_OFFSET;
+const TOKEN_ACCOUNT_AMOUNT: core::ops::Range<usize> = TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8;
+
+// 上面的偏移按 SPL Token 账户的 165 字节布局给出，pinocchio-token 的布局变化时在编译期报错
+const _: () = assert!(size_of::<TokenAccount>() == 165);
+// Initialize 按 `size_of::<Mint>()` 为 LP mint 分配空间并计算免租金额度，
+// 必须等于 Token 程序要求的 82 字节，否则 InitializeMint2 会拒绝或账户租金不足
+const _: () = assert!(size_of::<pinocchio_token::state::Mint>() == 82);
+
+/// 从代币账户数据中只取出 authority 与 amount，校验账户属于 Token 程序且长度正确
+#[inline(always)]
+fn token_account_fields<'a>(
+    data: &'a [u8],
+    account_view: &AccountView,
+) -> Result<(&'a [u8], u64), ProgramError> {
+    if !account_view.owned_by(&pinocchio_token::ID) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    if data.len() != size_of::<TokenAccount>() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let amount = data[TOKEN_ACCOUNT_AMOUNT]
+        .try_into()
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    Ok((&data[TOKEN_ACCOUNT_OWNER], u64::from_le_bytes(amount)))
+}
+
+/// 读取 Clock sysvar。启用 `clock-free` feature 时完全不访问 sysvar，返回 `None`。
+#[inline(always)]
+pub fn get_clock() -> Result<Option<Clock>, ProgramError> {
+    #[cfg(feature = "clock-free")]
+    {
+        Ok(None)
+    }
+    #[cfg(not(feature = "clock-free"))]
+    {
+        Clock::get().map(Some)
+    }
+}
+
+/// 校验订单未过期 (`unix_timestamp <= expiration`)；没有 Clock 时不检查
+#[inline(always)]
+pub fn check_expiration(clock: Option<&Clock>, expiration: i64) -> Result<(), ProgramError> {
+    match clock {
+        Some(clock) if clock.unix_timestamp > expiration => Err(ProgramError::InvalidArgument),
+        _ => Ok(()),
+    }
+}
+
+/// 校验传入的程序账户是 SPL Token 程序
+#[inline(always)]
+pub fn check_token_program(token_program: &AccountView) -> Result<(), ProgramError> {
+    if token_program.address().ne(&pinocchio_token::ID) {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// 校验 vault_x 与 vault_y 不是同一个账户。
+/// 同一个金库传入两次时两侧储备读到的是同一余额，存款、交换与取款的记账都会错乱
+#[inline(always)]
+pub fn check_distinct_vaults(
+    vault_x: &AccountView,
+    vault_y: &AccountView,
+) -> Result<(), ProgramError> {
+    if vault_x.address().eq(vault_y.address()) {
+        return Err(AmmError::DuplicateAccount.into());
+    }
+    Ok(())
+}
+
+/// 只读取代币账户的 amount 字段，不做完整反序列化。
+/// 需要同时校验 mint / authority 等字段时请使用 `TokenAccount::from_account_view`。
+#[inline(always)]
+pub fn vault_amount(account_view: &AccountView) -> Result<u64, ProgramError> {
+    let data = account_view.try_borrow()?;
+    Ok(token_account_fields(&data, account_view)?.1)
+}
+
+/// 创建属于 `owner` 程序、地址为 PDA 的账户，`signers` 是该 PDA 的签名种子。
+///
+/// 任何人都可以提前向可预测的 PDA 地址转入 lamports，`CreateAccount` 遇到已有余额的地址会失败，
+/// 因此地址上已有 lamports 时改为 "补足 `minimum_balance` + Allocate + Assign"。
+/// 补足的部分由 `payer` 支付；System Program 需出现在交易的账户列表中
+pub fn create_program_account(
+    account: &AccountView,
+    payer: &AccountView,
+    space: usize,
+    minimum_balance: u64,
+    owner: &Address,
+    signers: &[Signer],
+) -> ProgramResult {
+    let lamports = account.lamports();
+    if lamports == 0 {
+        return CreateAccount {
+            from: payer,
+            to: account,
+            lamports: minimum_balance,
+            space: space as u64,
+            owner,
+        }
+        .invoke_signed(signers);
+    }
+
+    // 只有转账可以在不签名的情况下改变一个账户，已被分配给其他程序或写入过数据的账户不是预存款
+    if !account.owned_by(&pinocchio_system::ID) || account.data_len() != 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let top_up = minimum_balance.saturating_sub(lamports);
+    if top_up > 0 {
+        SystemTransfer {
+            from: payer,
+            to: account,
+            lamports: top_up,
+        }
+        .invoke()?;
+    }
+    Allocate {
+        account,
+        space: space as u64,
+    }
+    .invoke_signed(signers)?;
+    Assign { account, owner }.invoke_signed(signers)
+}
+
+/// 程序级单例 PDA (`["global"]`)，保存全局紧急暂停开关。
+///
+/// 只有 `GlobalPause` 会创建该账户，因此 "属于本程序且长度为 `GlobalState::LEN`"
+/// 即可确认就是这个单例；尚未创建时则需校验地址。
+#[repr(C, packed)]
+pub struct GlobalState {
+    paused: u8,
+    bump: [u8; 1],
+}
+
+impl GlobalState {
+    pub const LEN: usize = size_of::<GlobalState>();
+
+    #[inline(always)]
+    pub fn load<'a>(account_view: &'a AccountView) -> Result<Ref<'a, Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const GlobalState)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut<'a>(account_view: &'a AccountView) -> Result<RefMut<'a, Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account_view.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut GlobalState)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused as u8;
+    }
+
+    #[inline(always)]
+    pub fn set_bump(&mut self, bump: [u8; 1]) {
+        self.bump = bump;
+    }
+
+    /// 若程序处于全局暂停状态则返回错误。
+    /// 账户尚未创建 (从未暂停过) 时校验其确实是单例 PDA，防止传入任意空账户绕过检查。
+    #[inline(always)]
+    pub fn check_not_paused(account_view: &AccountView) -> Result<(), ProgramError> {
+        if account_view.data_len() == 0 {
+            let (expected, _) = Address::find_program_address(&[GLOBAL_SEED], &crate::ID);
+            if expected.ne(account_view.address()) {
+                return Err(ProgramError::InvalidSeeds);
+            }
+            return Ok(());
+        }
+        if Self::load(account_view)?.is_paused() {
+            return Err(AmmError::GloballyPaused.into());
+        }
+        Ok(())
+    }
+}
+
+/// 每个用户在每个池子下的交换记录 PDA (`["user_swap", config, user]`)，用于交换冷却。
+///
+/// 仅在 `Config.cooldown_slots > 0` 时由 `Swap` 按需创建，费用由用户支付。
+#[repr(C, packed)]
+pub struct UserSwapState {
+    last_swap_slot: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl UserSwapState {
+    pub const LEN: usize = size_of::<UserSwapState>();
+
+    #[inline(always)]
+    pub fn load<'a>(account_view: &'a AccountView) -> Result<Ref<'a, Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const UserSwapState)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut<'a>(account_view: &'a AccountView) -> Result<RefMut<'a, Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account_view.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut UserSwapState)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn last_swap_slot(&self) -> u64 {
+        u64::from_le_bytes(self.last_swap_slot)
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_last_swap_slot(&mut self, slot: u64) {
+        self.last_swap_slot = slot.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_bump(&mut self, bump: [u8; 1]) {
+        self.bump = bump;
+    }
+}
+
+/// 每个用户在每个池子下的存款记录 PDA (`["user_deposit", config, user]`)，用于 LP 锁定期。
+///
+/// 锁定跟随 LP 本身而不是钱包：`Config.lock_duration > 0` 时 `Deposit` 把铸造的 LP 存入
+/// 该用户的托管代币账户 (`["lp_escrow", config, user]`，authority 为 LP 权限 PDA)，并刷新
+/// `deposited_at`；锁定期结束后由 `ClaimLockedLp` 转给用户。锁定期内的 LP 不在用户手里，
+/// 既不能取款也不能转给别的钱包。两个账户都由 `Deposit` 按需创建，费用由用户支付。
+#[repr(C, packed)]
+pub struct UserDepositState {
+    deposited_at: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl UserDepositState {
+    pub const LEN: usize = size_of::<UserDepositState>();
+
+    #[inline(always)]
+    pub fn load<'a>(account_view: &'a AccountView) -> Result<Ref<'a, Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(Ref::map(account_view.try_borrow()?, |data| unsafe {
+            &*(data.as_ptr() as *const UserDepositState)
+        }))
+    }
+
+    #[inline(always)]
+    pub fn load_mut<'a>(account_view: &'a AccountView) -> Result<RefMut<'a, Self>, ProgramError> {
+        if account_view.data_len() != Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !account_view.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        Ok(RefMut::map(account_view.try_borrow_mut()?, |data| unsafe {
+            &mut *(data.as_mut_ptr() as *mut UserDepositState)
+        }))
+    }
+
+    /// 校验已存在的记录属于 `(config, user)`：属于本程序且长度正确并不能说明是这个用户的记录，
+    /// 必须用保存的 bump 重新推导地址。返回最近一次存款的时间 (unix 秒)
+    pub fn check_address(
+        account_view: &AccountView,
+        config: &AccountView,
+        user: &AccountView,
+    ) -> Result<i64, ProgramError> {
+        let state = Self::load(account_view)?;
+        let expected = Address::create_program_address(
+            &[
+                USER_DEPOSIT_SEED,
+                config.address().as_ref(),
+                user.address().as_ref(),
+                &state.bump(),
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+        if expected.ne(account_view.address()) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        Ok(state.deposited_at())
+    }
+
+    #[inline(always)]
+    pub fn deposited_at(&self) -> i64 {
+        i64::from_le_bytes(self.deposited_at)
+    }
+
+    /// 最近一次存款在 `deposited_at` 的 LP 在 `now` 时是否已过锁定期。
+    /// 时钟回拨或记录时间晚于当前时间时按仍在锁定期处理
+    #[inline(always)]
+    pub fn check_unlocked(deposited_at: i64, now: i64, lock_duration: u64) -> ProgramResult {
+        if now < deposited_at || ((now - deposited_at) as u64) < lock_duration {
+            return Err(AmmError::LpLocked.into());
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub fn bump(&self) -> [u8; 1] {
+        self.bump
+    }
+
+    #[inline(always)]
+    pub fn set_deposited_at(&mut self, deposited_at: i64) {
+        self.deposited_at = deposited_at.to_le_bytes();
+    }
+
+    #[inline(always)]
+    pub fn set_bump(&mut self, bump: [u8; 1]) {
+        self.bump = bump;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{TestAccount, TestPool, empty_account, token_account};
+
+    #[test]
+    fn config_load_checks_length_and_owner() {
+        let mut pool = TestPool::new(0, 0, 0);
+        assert_eq!(
+            Config::load(&pool.config.view()).unwrap().mint_x(),
+            &pool.mint_x
+        );
+
+        let data = pool.config.data().to_vec();
+        let address = Address::new_from_array([1; 32]);
+        let mut foreign = TestAccount::new(address.clone(), pinocchio_token::ID, &data);
+        assert_eq!(
+            Config::load(&foreign.view()).err(),
+            Some(ProgramError::InvalidAccountOwner)
+        );
+        let mut short = TestAccount::new(address, crate::ID, &data[..Config::LEN - 1]);
+        assert_eq!(
+            Config::load_mut(&short.view()).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn reserves_are_read_only_from_vaults_owned_by_config() {
+        let mut pool = TestPool::new(1_000, 2_000, 0);
+        let config = pool.config.view();
+        assert_eq!(
+            Config::read_reserves(&config, &pool.vault_x.view(), &pool.vault_y.view()),
+            Ok((1_000, 2_000))
+        );
+
+        let attacker = Address::new_from_array([42; 32]);
+        let mut fake = token_account(Address::new_from_array([4; 32]), &pool.mint_x, &attacker, 1);
+        assert_eq!(
+            Config::read_reserves(&config, &fake.view(), &pool.vault_y.view()),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+        assert_eq!(
+            unsafe { Config::read_reserves_unchecked(&config, &fake.view(), &pool.vault_y.view()) },
+            Err(ProgramError::InvalidAccountOwner)
+        );
+    }
+
+    #[test]
+    fn transition_allows_only_the_documented_matrix() {
+        let allowed = [(1, 2), (2, 1), (1, 3), (3, 2)];
+        for from in 0..=3u8 {
+            for to in 0..=3u8 {
+                let mut data = [0u8; Config::LEN];
+                let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+                config.state = from;
+                let result = config.transition(AmmState::try_from(to).unwrap());
+                if allowed.contains(&(from, to)) {
+                    assert_eq!(result, Ok(()), "{from} -> {to}");
+                    assert_eq!(config.state(), to);
+                } else {
+                    assert_eq!(
+                        result,
+                        Err(AmmError::InvalidStateTransition.into()),
+                        "{from} -> {to}"
+                    );
+                    assert_eq!(config.state(), from);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transition_rejects_an_unknown_current_state() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.state = 4;
+        assert_eq!(
+            config.transition(AmmState::Disabled),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    /// `Config` 的原始字节
+    #[cfg(feature = "safe-load")]
+    fn config_bytes(config: &Config) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(config as *const Config as *const u8, Config::LEN) }
+    }
+
+    #[cfg(feature = "safe-load")]
+    #[test]
+    fn checked_and_unchecked_parsing_agree_on_every_byte() {
+        // 每个字节都不同，字段顺序或宽度解析错位都会被发现
+        let data: Vec<u8> = (0..Config::LEN).map(|i| (i * 7 + 1) as u8).collect();
+        let checked = Config::from_bytes_checked(&data).unwrap();
+        let unchecked = unsafe { Config::from_bytes_unchecked(&data) };
+        assert_eq!(config_bytes(&checked), config_bytes(unchecked));
+        assert_eq!(config_bytes(&checked), &data[..]);
+
+        assert!(Config::from_bytes_checked(&data[..Config::LEN - 1]).is_err());
+        assert!(Config::from_bytes_checked(&[data.as_slice(), &[0]].concat()).is_err());
+    }
+
+    #[cfg(feature = "safe-load")]
+    #[test]
+    fn load_checked_matches_load() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            config.set_fee(30).unwrap();
+            config.set_lock_duration(3_600);
+        });
+        let view = pool.config.view();
+        let checked = Config::load_checked(&view).unwrap();
+        assert_eq!(
+            config_bytes(&checked),
+            config_bytes(&Config::load(&view).unwrap())
+        );
+
+        let data = pool.config.data().to_vec();
+        let mut foreign =
+            TestAccount::new(Address::new_from_array([1; 32]), pinocchio_token::ID, &data);
+        assert_eq!(
+            Config::load_checked(&foreign.view()).err(),
+            Some(ProgramError::InvalidAccountOwner)
+        );
+    }
+
+    #[test]
+    fn token_amount_offset_matches_pinocchio_token() {
+        // 每个字节都不同，由 pinocchio-token 自己的解析给出各字段的位置
+        let data: Vec<u8> = (0..size_of::<TokenAccount>())
+            .map(|i| (i * 3 + 1) as u8)
+            .collect();
+        let mut account =
+            TestAccount::new(Address::new_from_array([4; 32]), pinocchio_token::ID, &data);
+        let view = account.view();
+        let token = TokenAccount::from_account_view(&view).unwrap();
+
+        let amount = u64::from_le_bytes(
+            data[TOKEN_AMOUNT_OFFSET..TOKEN_AMOUNT_OFFSET + 8]
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(token.amount(), amount);
+        assert_eq!(token.mint().as_ref(), &data[TOKEN_ACCOUNT_MINT]);
+        assert_eq!(token.owner().as_ref(), &data[TOKEN_ACCOUNT_OWNER]);
+        drop(token);
+        assert_eq!(vault_amount(&view), Ok(amount));
+    }
+
+    #[test]
+    fn token_account_fields_check_owner_and_length() {
+        let data = [0u8; 165];
+        let mut foreign = TestAccount::new(Address::new_from_array([4; 32]), crate::ID, &data);
+        assert_eq!(
+            vault_amount(&foreign.view()),
+            Err(ProgramError::InvalidAccountOwner)
+        );
+        let mut short = TestAccount::new(
+            Address::new_from_array([4; 32]),
+            pinocchio_token::ID,
+            &data[..164],
+        );
+        assert_eq!(
+            vault_amount(&short.view()),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn force_disable_works_from_every_initialized_state() {
+        for from in [
+            AmmState::Initialized,
+            AmmState::WithdrawOnly,
+            AmmState::Disabled,
+        ] {
+            let mut data = [0u8; Config::LEN];
+            let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+            let from = from as u8;
+            config.state = from;
+            assert_eq!(config.force_disable(), Ok(from));
+            assert_eq!(config.state(), AmmState::Disabled as u8);
+        }
+    }
+
+    #[test]
+    fn force_disable_rejects_an_uninitialized_pool() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        assert_eq!(
+            config.force_disable(),
+            Err(AmmError::InvalidStateTransition.into())
+        );
+        assert_eq!(config.state(), AmmState::Uninitialized as u8);
+    }
+
+    #[test]
+    fn lp_holder_fee_is_bounded_like_the_base_fee() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_volatility_fee(100).unwrap();
+        assert_eq!(config.set_lp_holder_fee(9_899, 1), Ok(()));
+        assert_eq!(
+            config.set_lp_holder_fee(9_900, 1),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(config.lp_holder_fee(), 9_899);
+        // 反过来，volatility_fee 也不能把 lp_holder_fee 推到 10_000 以上
+        assert_eq!(
+            config.set_volatility_fee(101),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn lp_holder_fee_includes_the_volatility_fee() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_fee(50).unwrap();
+        config.set_volatility_fee(20).unwrap();
+        config.set_lp_holder_fee(10, 1).unwrap();
+        assert_eq!(config.lp_holder_fee_for(true), 30);
+        // 优惠费率不会高于常规费率
+        config.set_lp_holder_fee(80, 1).unwrap();
+        assert_eq!(config.lp_holder_fee_for(true), 70);
+        config.set_direction_fees(0, 40).unwrap();
+        assert_eq!(config.lp_holder_fee_for(false), 60);
+    }
+
+    #[test]
+    fn direction_fees_override_the_base_fee_per_direction() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_fee(30).unwrap();
+        config.set_volatility_fee(20).unwrap();
+        config.set_direction_fees(100, 0).unwrap();
+        // 0 表示沿用 `fee`，volatility_fee 叠加在两个方向上
+        assert_eq!(
+            (
+                config.effective_fee_for(true),
+                config.effective_fee_for(false)
+            ),
+            (120, 50)
+        );
+        // 任一方向加上 volatility_fee 之后都必须小于 10_000，失败时两个方向都不改
+        for (fee_x_to_y, fee_y_to_x) in [(9_980, 0), (0, 9_980)] {
+            assert_eq!(
+                config.set_direction_fees(fee_x_to_y, fee_y_to_x),
+                Err(ProgramError::InvalidAccountData)
+            );
+        }
+        assert_eq!(
+            (
+                config.effective_fee_for(true),
+                config.effective_fee_for(false)
+            ),
+            (120, 50)
+        );
+        config.set_direction_fees(0, 9_979).unwrap();
+        assert_eq!(config.effective_fee_for(false), 9_999);
+    }
+
+    #[test]
+    fn program_accounts_can_be_created_on_a_prefunded_address() {
+        let mut payer = empty_account(Address::new_from_array([8; 32]))
+            .signer()
+            .writable()
+            .lamports(1_000_000);
+        // 有人提前向 PDA 地址转入了 1 lamport
+        let mut prefunded = empty_account(Address::new_from_array([9; 32]))
+            .writable()
+            .lamports(1);
+        assert_eq!(
+            create_program_account(&prefunded.view(), &payer.view(), 8, 1_000, &crate::ID, &[]),
+            Ok(())
+        );
+
+        // 已经属于其他程序的账户不是预存款，不能被接管
+        let mut assigned = TestAccount::new(Address::new_from_array([9; 32]), crate::ID, &[])
+            .writable()
+            .lamports(1);
+        assert_eq!(
+            create_program_account(&assigned.view(), &payer.view(), 8, 1_000, &crate::ID, &[]),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+
+    #[test]
+    fn locked_lp_unlocks_after_the_full_duration() {
+        assert_eq!(
+            UserDepositState::check_unlocked(1_000, 1_059, 60),
+            Err(AmmError::LpLocked.into())
+        );
+        assert_eq!(UserDepositState::check_unlocked(1_000, 1_060, 60), Ok(()));
+        // 时钟回拨
+        assert_eq!(
+            UserDepositState::check_unlocked(1_000, 999, 0),
+            Err(AmmError::LpLocked.into())
+        );
+        assert_eq!(UserDepositState::check_unlocked(1_000, 1_000, 0), Ok(()));
+    }
+
+    /// `fixtures/config_prefix.hex` 中两个 crate 共有的 Config 前缀
+    fn shared_prefix_fixture() -> Vec<u8> {
+        let hex: String = include_str!("../../fixtures/config_prefix.hex")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect();
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn shared_prefix_matches_the_native_crate() {
+        // blueshift_native_amm 的 state.rs 以相同的字段值与同一个文件比较
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_state(AmmState::Initialized as u8).unwrap();
+        config.set_seed(0x0102_0304_0506_0708);
+        config.set_authority(Address::new_from_array([9; 32]));
+        config.set_mint_x(Address::new_from_array([2; 32]));
+        config.set_mint_y(Address::new_from_array([3; 32]));
+        config.set_fee(30).unwrap();
+        config.set_config_bump([254]);
+        assert_eq!(
+            &data[..Config::MIN_MIGRATABLE_LEN],
+            &shared_prefix_fixture()[..]
+        );
+    }
+
+    #[test]
+    fn setters_write_little_endian_bytes_at_the_pinned_offsets() {
+        let mut data = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data) };
+        config.set_seed(0x0102_0304_0506_0708);
+        config.set_fee(0x0102).unwrap();
+        config.set_volatility_fee(0x0203).unwrap();
+        config.set_protocol_fees(0x1112_1314_1516_1718, 0x2122_2324_2526_2728);
+        config.set_cooldown_slots(0x3132_3334_3536_3738);
+        config.set_twap_min_interval(0x4142_4344);
+        config.set_max_abs_fee(0x5152_5354_5556_5758);
+        config
+            .set_lp_holder_fee(0x0304, 0x6162_6364_6566_6768)
+            .unwrap();
+        config.set_swaps_enabled_at(0x7172_7374_7576_7778);
+        config.set_max_swap_bps(0x0405).unwrap();
+        config.set_deposit_fee(0x0506).unwrap();
+        config.set_swap_tip(0x8182_8384_8586_8788);
+        config.set_direction_fees(0x0607, 0x0708).unwrap();
+        config.set_lock_duration(0x9192_9394_9596_9798);
+
+        let at = |offset: usize, len: usize| data[offset..offset + len].to_vec();
+        assert_eq!(at(1, 8), 0x0102_0304_0506_0708u64.to_le_bytes());
+        assert_eq!(at(105, 2), [0x02, 0x01]);
+        assert_eq!(at(158, 2), [0x03, 0x02]);
+        assert_eq!(at(141, 8), 0x1112_1314_1516_1718u64.to_le_bytes());
+        assert_eq!(at(149, 8), 0x2122_2324_2526_2728u64.to_le_bytes());
+        assert_eq!(at(160, 8), 0x3132_3334_3536_3738u64.to_le_bytes());
+        assert_eq!(at(208, 4), [0x44, 0x43, 0x42, 0x41]);
+        assert_eq!(at(213, 8), 0x5152_5354_5556_5758u64.to_le_bytes());
+        assert_eq!(at(221, 2), [0x04, 0x03]);
+        assert_eq!(at(223, 8), 0x6162_6364_6566_6768u64.to_le_bytes());
+        assert_eq!(at(234, 8), 0x7172_7374_7576_7778i64.to_le_bytes());
+        assert_eq!(at(242, 2), [0x05, 0x04]);
+        assert_eq!(at(244, 2), [0x06, 0x05]);
+        assert_eq!(at(246, 8), 0x8182_8384_8586_8788u64.to_le_bytes());
+        assert_eq!(at(286, 2), [0x07, 0x06]);
+        assert_eq!(at(288, 2), [0x08, 0x07]);
+        assert_eq!(at(290, 8), 0x9192_9394_9596_9798u64.to_le_bytes());
+        // 其他字段保持为 0，setter 不会写到相邻的字段
+        assert_eq!(data[0], 0);
+        assert!(data[9..105].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn lamport_moves_keep_the_config_rent_exempt() {
+        let minimum = crate::test_utils::rent()
+            .try_minimum_balance(Config::LEN)
+            .unwrap();
+        let mut config = TestAccount::new(Address::new_from_array([1; 32]), crate::ID, &[])
+            .lamports(minimum + 100);
+        let config = config.view();
+        assert_eq!(Config::check_balance_after(&config, 100, minimum), Ok(()));
+        assert_eq!(
+            Config::check_balance_after(&config, 101, minimum),
+            Err(ProgramError::AccountNotRentExempt)
+        );
+        assert_eq!(
+            Config::check_balance_after(&config, minimum + 101, minimum),
+            Err(ProgramError::InsufficientFunds)
+        );
+    }
+
+    #[test]
+    fn payouts_larger_than_the_vault_are_rejected() {
+        assert_eq!(Config::check_vault_covers(1_000, 1_000), Ok(()));
+        assert_eq!(
+            Config::check_vault_covers(1_000, 1_001),
+            Err(AmmError::InsufficientLiquidity.into())
+        );
+    }
+
+    #[test]
+    fn set_fee_enforces_the_minimum_fee_policy() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            assert_eq!(config.set_fee(MIN_FEE), Ok(()));
+            // MIN_FEE 在编译前调高时，低于它的费率被拒绝 (包括 0)
+            if let Some(below) = MIN_FEE.checked_sub(1) {
+                assert_eq!(config.set_fee(below), Err(ProgramError::InvalidAccountData));
+            }
+            // 基础费率与波动费率合计必须低于 100%
+            config.set_volatility_fee(100).unwrap();
+            assert_eq!(config.set_fee(9_899), Ok(()));
+            assert_eq!(config.set_fee(9_900), Err(ProgramError::InvalidAccountData));
+            assert_eq!(config.fee(), 9_899);
+        });
+    }
+
+    #[test]
+    fn vault_must_grow_by_the_transferred_amount() {
+        let mint = Address::new_from_array([2; 32]);
+        let mut vault = token_account([4; 32].into(), &mint, &[1; 32].into(), 1_500);
+        let vault = vault.view();
+        assert_eq!(Config::check_vault_received(&vault, 1_000, 500), Ok(()));
+        // 手续费型代币或空操作的 Token 程序到账不足
+        assert_eq!(
+            Config::check_vault_received(&vault, 1_000, 501),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            Config::check_vault_received(&vault, 1_500, 1),
+            Err(AmmError::TransferNotReceived.into())
+        );
+        assert_eq!(
+            Config::check_vault_received(&vault, u64::MAX, 1),
+            Err(ProgramError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn throttled_twap_updates_cover_the_whole_span() {
+        let one = 1u128 << crate::curve::PRICE_FRACTION_BITS;
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            config.set_twap_min_interval(60);
+            // 第一次只记录时间
+            config.update_twap(1_000, 1_000, 2_000).unwrap();
+            assert_eq!(config.price_x_cumulative(), 0);
+
+            // 60 秒内的交换不更新累加器，也不推进 twap_last_update
+            for now in [1_010, 1_030, 1_059] {
+                config.update_twap(now, 1_000, 1_000).unwrap();
+                assert_eq!(config.twap_last_update(), 1_000);
+            }
+            assert_eq!(config.price_x_cumulative(), 0);
+
+            // 跨过三个间隔后一次补齐：整个 200 秒按更新时的价格 (1 X = 2 Y) 计入
+            config.update_twap(1_200, 1_000, 2_000).unwrap();
+            assert_eq!(config.twap_last_update(), 1_200);
+            assert_eq!(config.price_x_cumulative(), 2 * one * 200);
+            assert_eq!(config.price_y_cumulative(), one / 2 * 200);
+
+            // 之后的观测窗口按实际间隔累加，两次观测之差除以时间差即为 TWAP
+            let before = config.price_x_cumulative();
+            config.update_twap(1_300, 1_000, 4_000).unwrap();
+            assert_eq!((config.price_x_cumulative() - before) / 100, 4 * one);
+        });
+    }
+
+    /// `unix_timestamp` 时刻的 Clock；主机上读不到 Clock sysvar
+    fn clock(unix_timestamp: i64) -> Clock {
+        Clock {
+            slot: 0,
+            epoch_start_timestamp: 0,
+            epoch: 0,
+            leader_schedule_epoch: 0,
+            unix_timestamp,
+        }
+    }
+
+    #[test]
+    fn expiration_is_inclusive() {
+        assert_eq!(check_expiration(Some(&clock(999)), 1_000), Ok(()));
+        assert_eq!(check_expiration(Some(&clock(1_000)), 1_000), Ok(()));
+        assert_eq!(
+            check_expiration(Some(&clock(1_001)), 1_000),
+            Err(ProgramError::InvalidArgument)
+        );
+        // clock-free 构建读不到时间，不检查过期
+        assert_eq!(check_expiration(None, i64::MIN), Ok(()));
+    }
+
+    #[test]
+    fn clock_free_builds_never_touch_the_sysvar() {
+        // 主机上没有 Clock sysvar：只有 clock-free 构建能拿到结果 (None)，默认构建照常读取并失败
+        if cfg!(feature = "clock-free") {
+            assert!(get_clock().unwrap().is_none());
+        } else {
+            assert!(get_clock().is_err());
+        }
+    }
+
+    #[test]
+    fn every_authority_byte_counts_towards_being_set() {
+        // 从奇数偏移开始，authority 字段不按 8 字节对齐
+        let mut data = vec![0u8; Config::LEN + 1];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut data[1..]) };
+        assert!(config.authority_is_unset());
+        assert_eq!(config.has_authority(), None);
+
+        for i in 0..32 {
+            let mut authority = [0u8; 32];
+            authority[i] = 0x80;
+            config.set_authority(authority.into());
+            assert!(!config.authority_is_unset(), "byte {i}");
+            assert_eq!(config.has_authority(), Some(authority.into()));
+        }
+    }
+
+    #[test]
+    fn swaps_open_at_the_configured_timestamp() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            // 0 表示创建后立即开放
+            assert_eq!(config.check_swaps_enabled(Some(&clock(0))), Ok(()));
+
+            config.set_swaps_enabled_at(1_000);
+            for now in [0, 500, 999] {
+                assert_eq!(
+                    config.check_swaps_enabled(Some(&clock(now))),
+                    Err(AmmError::SwapsNotYetEnabled.into())
+                );
+            }
+            for now in [1_000, 1_001, i64::MAX] {
+                assert_eq!(config.check_swaps_enabled(Some(&clock(now))), Ok(()));
+            }
+            assert_eq!(config.check_swaps_enabled(None), Ok(()));
+        });
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "not owned by this program")]
+    fn unchecked_load_of_a_foreign_account_panics_in_debug_builds() {
+        let mut foreign = TestAccount::new([1; 32].into(), [11; 32].into(), &[0; Config::LEN]);
+        let view = foreign.view();
+        let _ = unsafe { Config::load_mut_unchecked(&view) };
+    }
+
+    #[test]
+    fn unchecked_load_still_checks_the_length() {
+        let mut owned = TestAccount::new([1; 32].into(), crate::ID, &[0; Config::LEN]);
+        let view = owned.view();
+        assert!(unsafe { Config::load_mut_unchecked(&view) }.is_ok());
+
+        let mut short = TestAccount::new([1; 32].into(), crate::ID, &[0; Config::LEN - 1]);
+        let view = short.view();
+        assert_eq!(
+            unsafe { Config::load_mut_unchecked(&view) }.err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn deposit_fee_is_withheld_from_the_minted_lp() {
+        let mut pool = TestPool::new(0, 0, 0);
+        pool.update_config(|config| {
+            assert_eq!(config.lp_after_deposit_fee(1_000), 1_000);
+
+            config.set_deposit_fee(100).unwrap();
+            assert_eq!(config.lp_after_deposit_fee(1_000), 990);
+            // 手续费向上取整，少铸的 LP 不会因取整变成 0
+            assert_eq!(config.lp_after_deposit_fee(1_001), 990);
+            assert_eq!(config.lp_after_deposit_fee(1), 0);
+
+            config.set_deposit_fee(9_999).unwrap();
+            assert_eq!(config.lp_after_deposit_fee(u64::MAX), u64::MAX / 10_000);
+            assert_eq!(
+                config.set_deposit_fee(10_000),
+                Err(ProgramError::InvalidAccountData)
+            );
+        });
     }
 }
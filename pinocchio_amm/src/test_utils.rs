@@ -0,0 +1,277 @@
+//! 主机单元测试用的内存账户。
+//!
+//! 按运行时传给程序的输入布局 (`RuntimeAccount` 头 + 账户数据 + 可扩容区) 分配缓冲区，
+//! 再由此构造 `AccountView`，不需要 SVM 就能驱动账户解析与校验逻辑。
+//! 主机上的 CPI 是空操作 (`black_box`)，依赖 CPI 结果的路径请用链上集成测试覆盖。
+
+use pinocchio::{
+    AccountView, Address,
+    account::{MAX_PERMITTED_DATA_INCREASE, NOT_BORROWED, RuntimeAccount},
+    sysvars::rent::Rent,
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+use crate::{
+    AUTHORITY_MODE_AS_GIVEN, CONFIG_SEED, Config, DECIMALS_DIFF_UNCHECKED,
+    InitializeInstructionData, MINT_LP_SEED,
+};
+
+/// 一个内存中的账户。缓冲区按 8 字节对齐，与运行时的输入区一致
+pub struct TestAccount {
+    buf: Vec<u64>,
+}
+
+impl TestAccount {
+    pub fn new(address: Address, owner: Address, data: &[u8]) -> Self {
+        let header = size_of::<RuntimeAccount>();
+        let len = header + data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let mut account = Self {
+            buf: vec![0u64; len.div_ceil(8)],
+        };
+        let raw = account.raw();
+        unsafe {
+            raw.write(RuntimeAccount {
+                borrow_state: NOT_BORROWED,
+                is_signer: 0,
+                is_writable: 0,
+                executable: 0,
+                resize_delta: 0,
+                address,
+                owner,
+                lamports: 0,
+                data_len: data.len() as u64,
+            });
+        }
+        account.bytes_mut()[header..header + data.len()].copy_from_slice(data);
+        account
+    }
+
+    pub fn signer(mut self) -> Self {
+        unsafe { (*self.raw()).is_signer = 1 };
+        self
+    }
+
+    pub fn writable(mut self) -> Self {
+        unsafe { (*self.raw()).is_writable = 1 };
+        self
+    }
+
+    pub fn lamports(mut self, lamports: u64) -> Self {
+        unsafe { (*self.raw()).lamports = lamports };
+        self
+    }
+
+    /// 构造指向该缓冲区的 `AccountView`；`TestAccount` 必须比返回的视图活得更久
+    pub fn view(&mut self) -> AccountView {
+        unsafe { AccountView::new_unchecked(self.raw()) }
+    }
+
+    /// 账户当前的数据 (按 `data_len`，包含扩容后的部分)
+    pub fn data(&self) -> &[u8] {
+        let header = size_of::<RuntimeAccount>();
+        let len = unsafe { (*(self.buf.as_ptr() as *const RuntimeAccount)).data_len } as usize;
+        let bytes = unsafe {
+            core::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.buf.len() * 8)
+        };
+        &bytes[header..header + len]
+    }
+
+    fn raw(&mut self) -> *mut RuntimeAccount {
+        self.buf.as_mut_ptr() as *mut RuntimeAccount
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            core::slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut u8, self.buf.len() * 8)
+        }
+    }
+}
+
+/// 主网参数的 Rent (每字节 3480 lamports / 年，豁免阈值 2 年)；主机上无法读取 Rent sysvar
+pub fn rent() -> Rent {
+    let bytes = [3_480u64, u64::from_le_bytes(2f64.to_le_bytes())];
+    let bytes = unsafe { core::slice::from_raw_parts(bytes.as_ptr() as *const u8, 16) };
+    Rent::from_bytes(bytes).unwrap().clone()
+}
+
+/// 属于本程序的 Config 账户，字段由 `init` 写入 (初始全零)
+pub fn config_account(address: Address, init: impl FnOnce(&mut Config)) -> TestAccount {
+    let mut data = [0u8; Config::LEN];
+    init(unsafe { Config::from_bytes_unchecked_mut(&mut data) });
+    TestAccount::new(address, crate::ID, &data)
+}
+
+/// 已初始化的 SPL Token 账户
+pub fn token_account(
+    address: Address,
+    mint: &Address,
+    owner: &Address,
+    amount: u64,
+) -> TestAccount {
+    let mut data = [0u8; size_of::<TokenAccount>()];
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    // state = Initialized
+    data[108] = 1;
+    TestAccount::new(address, pinocchio_token::ID, &data)
+}
+
+/// 已初始化、没有 mint / freeze 权限的 SPL Token mint
+pub fn mint_account(address: Address, supply: u64, decimals: u8) -> TestAccount {
+    let mut data = [0u8; size_of::<Mint>()];
+    data[36..44].copy_from_slice(&supply.to_le_bytes());
+    data[44] = decimals;
+    // is_initialized
+    data[45] = 1;
+    TestAccount::new(address, pinocchio_token::ID, &data)
+}
+
+/// 不属于任何程序、没有数据的账户 (例如尚未创建的 PDA 或钱包)
+pub fn empty_account(address: Address) -> TestAccount {
+    TestAccount::new(address, Address::default(), &[])
+}
+
+/// 已创建的全局暂停单例，`paused` 为其暂停状态
+pub fn global_state_account(paused: bool) -> TestAccount {
+    let (address, bump) = Address::find_program_address(&[crate::GLOBAL_SEED], &crate::ID);
+    TestAccount::new(address, crate::ID, &[paused as u8, bump]).writable()
+}
+
+/// 一个已初始化的池子：LP mint、LP 权限与全局暂停单例都按 PDA 规则派生，
+/// 两个金库归 Config 所有。管理员为 `TestPool::AUTHORITY`，费率为 0
+pub struct TestPool {
+    pub config: TestAccount,
+    pub mint_x: Address,
+    pub mint_y: Address,
+    pub mint_lp: TestAccount,
+    pub lp_authority: TestAccount,
+    pub vault_x: TestAccount,
+    pub vault_y: TestAccount,
+    pub global_state: TestAccount,
+}
+
+impl TestPool {
+    pub const AUTHORITY: Address = Address::new_from_array([9; 32]);
+
+    pub fn new(reserve_x: u64, reserve_y: u64, lp_supply: u64) -> Self {
+        let config = Address::new_from_array([1; 32]);
+        let mint_x = Address::new_from_array([2; 32]);
+        let mint_y = Address::new_from_array([3; 32]);
+        let (lp_authority, lp_authority_bump) =
+            Address::find_program_address(&[crate::LP_AUTHORITY_SEED, config.as_ref()], &crate::ID);
+        let (mint_lp, mint_lp_bump) =
+            Address::find_program_address(&[crate::MINT_LP_SEED, config.as_ref()], &crate::ID);
+        let (global_state, _) = Address::find_program_address(&[crate::GLOBAL_SEED], &crate::ID);
+
+        Self {
+            config: config_account(config.clone(), |c| {
+                c.set_inner(
+                    0,
+                    Self::AUTHORITY,
+                    mint_x.clone(),
+                    mint_y.clone(),
+                    0,
+                    [255],
+                    [lp_authority_bump],
+                    [mint_lp_bump],
+                )
+                .unwrap()
+            })
+            .writable(),
+            vault_x: token_account(
+                Address::new_from_array([4; 32]),
+                &mint_x,
+                &config,
+                reserve_x,
+            )
+            .writable(),
+            vault_y: token_account(
+                Address::new_from_array([5; 32]),
+                &mint_y,
+                &config,
+                reserve_y,
+            )
+            .writable(),
+            mint_lp: mint_account(mint_lp, lp_supply, crate::LP_DECIMALS).writable(),
+            lp_authority: empty_account(lp_authority),
+            global_state: empty_account(global_state),
+            mint_x,
+            mint_y,
+        }
+    }
+
+    /// 修改池子的 Config
+    pub fn update_config(&mut self, update: impl FnOnce(&mut Config)) {
+        let view = self.config.view();
+        update(&mut Config::load_mut(&view).unwrap());
+    }
+}
+
+/// 一个待初始化的池子。主机上的 CreateAccount 是空操作，因此 config 直接给出创建之后的样子
+/// (归本程序所有、数据全零)；mint_lp 仍是尚未创建的空账户
+pub struct NewPool {
+    pub initializer: TestAccount,
+    pub mint_lp: TestAccount,
+    pub config: TestAccount,
+    pub data: InitializeInstructionData,
+}
+
+impl NewPool {
+    pub const INITIALIZER: Address = Address::new_from_array([6; 32]);
+
+    /// seed 为 7、费率 30 bps、管理员为 `[0xAA; 32]` 的池子，bump 都按 PDA 规则推导
+    pub fn new(mint_x: [u8; 32], mint_y: [u8; 32]) -> Self {
+        let (config, config_bump) = Address::find_program_address(
+            &[CONFIG_SEED, &7u64.to_le_bytes(), &mint_x, &mint_y],
+            &crate::ID,
+        );
+        let (mint_lp, lp_bump) =
+            Address::find_program_address(&[MINT_LP_SEED, config.as_ref()], &crate::ID);
+        Self {
+            initializer: empty_account(Self::INITIALIZER)
+                .signer()
+                .writable()
+                .lamports(u64::MAX),
+            mint_lp: empty_account(mint_lp).writable(),
+            config: TestAccount::new(config, crate::ID, &[0; Config::LEN]).writable(),
+            data: InitializeInstructionData {
+                seed: 7,
+                fee: 30,
+                mint_x,
+                mint_y,
+                config_bump: [config_bump],
+                lp_bump: [lp_bump],
+                authority_mode: AUTHORITY_MODE_AS_GIVEN,
+                allow_high_fee: 0,
+                swap_delay: 0,
+                max_decimals_diff: DECIMALS_DIFF_UNCHECKED,
+                authority: [0xAA; 32],
+            },
+        }
+    }
+
+    /// 按 Initialize 的账户顺序 (creation-fee 时包括协议金库) 初始化该池子
+    pub fn initialize(&mut self) -> pinocchio::ProgramResult {
+        #[cfg(feature = "creation-fee")]
+        let mut treasury = empty_account(crate::TREASURY).writable();
+        let accounts = [
+            self.initializer.view(),
+            self.mint_lp.view(),
+            self.config.view(),
+            #[cfg(feature = "creation-fee")]
+            treasury.view(),
+        ];
+        crate::Initialize {
+            accounts: crate::InitializeAccounts::try_from(&accounts[..])?,
+            instruction_data: self.data,
+        }
+        .process_with_rent(&rent())
+    }
+
+    /// 读取初始化之后的 Config
+    pub fn config<R>(&mut self, read: impl FnOnce(&Config) -> R) -> R {
+        let view = self.config.view();
+        read(&Config::load(&view).unwrap())
+    }
+}